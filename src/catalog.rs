@@ -0,0 +1,210 @@
+//! Link-time registry of declared cheat documentation.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::text::json_string;
+use crate::{CheatMeta, Location, Severity};
+
+/// One declared piece of cheat documentation, registered at link time.
+///
+/// Every [`cheat_profile!`](crate::cheat_profile) and
+/// [`cheat_doc!`](crate::cheat_doc) in the binary adds one, so
+/// [`cheat_catalog`] can list them without running any check. The fields are
+/// built the first time they are read, like the profile itself.
+#[derive(Debug)]
+pub struct CheatEntry {
+    id: Option<&'static str>,
+    meta: fn() -> &'static CheatMeta,
+    location: Location,
+}
+
+impl CheatEntry {
+    #[doc(hidden)]
+    pub const fn new(
+        id: Option<&'static str>,
+        meta: fn() -> &'static CheatMeta,
+        location: Location,
+    ) -> Self {
+        CheatEntry { id, meta, location }
+    }
+
+    /// The stable ID given with `id = "..."`, used to track the entry across
+    /// renames.
+    pub fn id(&self) -> Option<&'static str> {
+        self.id
+    }
+
+    /// The declared documentation.
+    pub fn meta(&self) -> &'static CheatMeta {
+        (self.meta)()
+    }
+
+    /// This entry as a single-line JSON object.
+    pub fn to_json(&self) -> String {
+        let cheats = self
+            .cheats()
+            .iter()
+            .map(|c| json_string(c))
+            .collect::<Vec<_>>()
+            .join(",");
+        let id = match self.id {
+            Some(id) => json_string(id),
+            None => "null".to_string(),
+        };
+        format!(
+            "{{\"id\":{},\"protects\":{},\"severity\":{},\"cheats\":[{}],\"consequence\":{},\"file\":{},\"line\":{},\"column\":{}}}",
+            id,
+            json_string(self.protects()),
+            json_string(self.severity().as_str()),
+            cheats,
+            json_string(self.consequence()),
+            json_string(self.location.file),
+            self.location.line,
+            self.location.column,
+        )
+    }
+
+    pub fn protects(&self) -> &'static str {
+        &self.meta().protects
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.meta().severity
+    }
+
+    pub fn cheats(&self) -> &'static [String] {
+        &self.meta().cheats
+    }
+
+    pub fn consequence(&self) -> &'static str {
+        &self.meta().consequence
+    }
+
+    /// Where the declaration is in the source.
+    pub fn location(&self) -> Location {
+        self.location
+    }
+}
+
+inventory::collect!(CheatEntry);
+
+/// Every registered [`CheatEntry`], sorted by source location.
+///
+/// Only declarations are registered: `cheat_profile!` and `cheat_doc!`, not inline
+/// `cheat_bail!`/`cheat_check!` calls, whose fields may depend on runtime
+/// values. Use `profile = NAME` to put a check's cheats in the catalog.
+pub fn cheat_catalog() -> Vec<&'static CheatEntry> {
+    let mut entries: Vec<&'static CheatEntry> = inventory::iter::<CheatEntry>.into_iter().collect();
+    entries.sort_by_key(|entry| {
+        let location = entry.location;
+        (location.file, location.line, location.column)
+    });
+    entries
+}
+
+/// The [`cheat_catalog`] as a JSON array, one entry per line.
+///
+/// The output only changes when a declaration does, so it can be checked in
+/// and diffed in review to catch a cheat guard being removed.
+pub fn catalog_json_string() -> String {
+    let entries = cheat_catalog()
+        .iter()
+        .map(|entry| format!("  {}", entry.to_json()))
+        .collect::<Vec<_>>();
+    if entries.is_empty() {
+        return "[]\n".to_string();
+    }
+    format!("[\n{}\n]\n", entries.join(",\n"))
+}
+
+/// Check that no two [`CheatEntry`]s share an ID.
+///
+/// Returns every ID registered more than once, sorted, so CI can assert the
+/// IDs dashboards key on stay unique. Entries without an ID are ignored.
+pub fn validate_catalog() -> Result<(), Vec<String>> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for id in cheat_catalog().iter().filter_map(|entry| entry.id) {
+        *counts.entry(id).or_default() += 1;
+    }
+    let duplicates: Vec<String> = counts
+        .into_iter()
+        .filter(|&(_, count)| count > 1)
+        .map(|(id, _)| id.to_string())
+        .collect();
+    if duplicates.is_empty() {
+        Ok(())
+    } else {
+        Err(duplicates)
+    }
+}
+
+/// Write [`catalog_json_string`] to `path`, replacing the file.
+pub fn dump_catalog_json(path: &Path) -> io::Result<()> {
+    fs::write(path, catalog_json_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    crate::cheat_profile!(
+        ROOT_MOUNTED,
+        id = "TEST-DUP-001",
+        protects = "Root filesystem is mounted",
+        severity = "CRITICAL",
+        cheats = ["Check only that /mnt exists"],
+        consequence = "Installation writes to the live image",
+    );
+
+    crate::cheat_profile!(
+        ROOT_WRITABLE,
+        id = "TEST-DUP-001",
+        protects = "Root filesystem is writable",
+        severity = "HIGH",
+        cheats = [],
+        consequence = "Installation fails halfway",
+    );
+
+    #[test]
+    fn test_validate_catalog_reports_duplicate_ids() {
+        let catalog = cheat_catalog();
+        let tagged: Vec<&str> = catalog
+            .iter()
+            .filter(|entry| entry.id() == Some("TEST-DUP-001"))
+            .map(|entry| entry.protects())
+            .collect();
+        assert_eq!(
+            tagged,
+            ["Root filesystem is mounted", "Root filesystem is writable"]
+        );
+        assert_eq!(validate_catalog(), Err(vec!["TEST-DUP-001".to_string()]));
+    }
+
+    #[test]
+    fn test_dump_catalog_json_round_trips() {
+        let path =
+            std::env::temp_dir().join(format!("cheat-guard-catalog-{}.json", std::process::id()));
+        dump_catalog_json(&path).unwrap();
+        let written = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(written, catalog_json_string());
+
+        let parsed: Vec<serde_json::Value> = serde_json::from_str(&written).unwrap();
+        let catalog = cheat_catalog();
+        assert!(!catalog.is_empty());
+        assert_eq!(parsed.len(), catalog.len());
+        for (json, entry) in parsed.iter().zip(&catalog) {
+            assert_eq!(json["id"], serde_json::json!(entry.id()));
+            assert_eq!(json["protects"], entry.protects());
+            assert_eq!(json["severity"], entry.severity().as_str());
+            assert_eq!(json["cheats"], serde_json::json!(entry.cheats()));
+            assert_eq!(json["consequence"], entry.consequence());
+            assert_eq!(json["file"], entry.location().file);
+            assert_eq!(json["line"], entry.location().line);
+            assert_eq!(json["column"], entry.location().column);
+        }
+    }
+}
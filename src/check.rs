@@ -0,0 +1,316 @@
+//! Per-check results recorded by [`cheat_check!`](crate::cheat_check).
+
+use std::fmt;
+
+/// CheckResult for use with cheat_check! macro.
+/// Mirrors the install-tests CheckResult enum.
+///
+/// With the `serde` feature this serializes internally tagged, e.g.
+/// `{"type":"pass","message":"..."}`,
+/// `{"type":"fail","expected":"...","actual":"..."}`,
+/// `{"type":"skip","reason":"..."}`, or `{"type":"warn","message":"..."}`.
+///
+/// Equality compares the variant and every field, so results from two runs
+/// can be diffed directly; `Hash` agrees with it, for `HashSet`/`HashMap` keys.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "repr::CheckResultRepr", from = "repr::CheckResultRepr")
+)]
+pub enum CheckResult {
+    Pass(String),
+    Fail {
+        expected: String,
+        actual: String,
+    },
+    /// The check does not apply here (e.g. a UEFI check on BIOS); carries the reason.
+    Skip(String),
+    /// Something is off-nominal but should not fail the step.
+    Warn {
+        message: String,
+    },
+}
+
+impl CheckResult {
+    /// Whether the check did not fail. Skips and warnings count as passed.
+    pub fn passed(&self) -> bool {
+        !matches!(self, CheckResult::Fail { .. })
+    }
+
+    pub fn skipped(&self) -> bool {
+        matches!(self, CheckResult::Skip(_))
+    }
+
+    pub fn warned(&self) -> bool {
+        matches!(self, CheckResult::Warn { .. })
+    }
+
+    /// The `(expected, actual)` pair of a `Fail`.
+    pub fn as_fail(&self) -> Option<(&str, &str)> {
+        match self {
+            CheckResult::Fail { expected, actual } => Some((expected, actual)),
+            _ => None,
+        }
+    }
+
+    /// The message of a `Pass`.
+    pub fn message(&self) -> Option<&str> {
+        match self {
+            CheckResult::Pass(message) => Some(message),
+            _ => None,
+        }
+    }
+}
+
+/// One line for logs: `PASS: <message>`, `FAIL: expected <x>, got <y>`,
+/// `SKIP: <reason>`, or `WARN: <message>`.
+impl fmt::Display for CheckResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckResult::Pass(message) => write!(f, "PASS: {}", message),
+            CheckResult::Fail { expected, actual } => {
+                write!(f, "FAIL: expected {}, got {}", expected, actual)
+            }
+            CheckResult::Skip(reason) => write!(f, "SKIP: {}", reason),
+            CheckResult::Warn { message } => write!(f, "WARN: {}", message),
+        }
+    }
+}
+
+/// How [`cheat_check!`](crate::cheat_check) stores `expected` and `actual`:
+/// strings as-is, anything else through `Debug`.
+///
+/// The macro calls `(&CheckField(&value)).to_check_field()` with both traits
+/// in scope. Method lookup tries the receiver `&CheckField` before
+/// auto-referencing to `&&CheckField`, so [`StrCheckField`] wins for
+/// string-like values and [`DebugCheckField`] is the fallback.
+pub struct CheckField<'a, T: ?Sized>(pub &'a T);
+
+pub trait StrCheckField {
+    fn to_check_field(&self) -> String;
+}
+
+impl<T: AsRef<str> + ?Sized> StrCheckField for CheckField<'_, T> {
+    fn to_check_field(&self) -> String {
+        self.0.as_ref().to_string()
+    }
+}
+
+/// The `Debug` fallback of [`StrCheckField`].
+pub trait DebugCheckField {
+    fn to_check_field(&self) -> String;
+}
+
+impl<T: fmt::Debug + ?Sized> DebugCheckField for &CheckField<'_, T> {
+    fn to_check_field(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+/// Serde cannot internally tag a newtype variant holding a string, so
+/// `CheckResult` round-trips through this struct-variant mirror.
+#[cfg(feature = "serde")]
+mod repr {
+    use super::CheckResult;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[serde(tag = "type", rename_all = "lowercase")]
+    pub enum CheckResultRepr {
+        Pass { message: String },
+        Fail { expected: String, actual: String },
+        Skip { reason: String },
+        Warn { message: String },
+    }
+
+    impl From<CheckResult> for CheckResultRepr {
+        fn from(result: CheckResult) -> Self {
+            match result {
+                CheckResult::Pass(message) => CheckResultRepr::Pass { message },
+                CheckResult::Fail { expected, actual } => {
+                    CheckResultRepr::Fail { expected, actual }
+                }
+                CheckResult::Skip(reason) => CheckResultRepr::Skip { reason },
+                CheckResult::Warn { message } => CheckResultRepr::Warn { message },
+            }
+        }
+    }
+
+    impl From<CheckResultRepr> for CheckResult {
+        fn from(repr: CheckResultRepr) -> Self {
+            match repr {
+                CheckResultRepr::Pass { message } => CheckResult::Pass(message),
+                CheckResultRepr::Fail { expected, actual } => {
+                    CheckResult::Fail { expected, actual }
+                }
+                CheckResultRepr::Skip { reason } => CheckResult::Skip(reason),
+                CheckResultRepr::Warn { message } => CheckResult::Warn { message },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fail() -> CheckResult {
+        CheckResult::Fail {
+            expected: "vda1".into(),
+            actual: "nothing".into(),
+        }
+    }
+
+    fn warn() -> CheckResult {
+        CheckResult::Warn {
+            message: "odd layout".into(),
+        }
+    }
+
+    #[test]
+    fn test_passed() {
+        assert!(CheckResult::Pass("ok".into()).passed());
+        assert!(!fail().passed());
+        assert!(CheckResult::Skip("BIOS system".into()).passed());
+        assert!(warn().passed());
+    }
+
+    #[test]
+    fn test_skipped() {
+        assert!(CheckResult::Skip("BIOS system".into()).skipped());
+        assert!(!CheckResult::Pass("ok".into()).skipped());
+        assert!(!fail().skipped());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            CheckResult::Pass("vda1 exists".into()).to_string(),
+            "PASS: vda1 exists"
+        );
+        assert_eq!(fail().to_string(), "FAIL: expected vda1, got nothing");
+        assert_eq!(
+            CheckResult::Skip("BIOS system".into()).to_string(),
+            "SKIP: BIOS system"
+        );
+        assert_eq!(warn().to_string(), "WARN: odd layout");
+    }
+
+    #[test]
+    fn test_as_fail() {
+        assert_eq!(fail().as_fail(), Some(("vda1", "nothing")));
+        assert_eq!(CheckResult::Pass("ok".into()).as_fail(), None);
+        assert_eq!(CheckResult::Skip("BIOS system".into()).as_fail(), None);
+        assert_eq!(warn().as_fail(), None);
+    }
+
+    #[test]
+    fn test_message() {
+        assert_eq!(CheckResult::Pass("ok".into()).message(), Some("ok"));
+        assert_eq!(fail().message(), None);
+        assert_eq!(CheckResult::Skip("BIOS system".into()).message(), None);
+        assert_eq!(warn().message(), None);
+    }
+
+    #[test]
+    fn test_eq_compares_variant_and_fields() {
+        let pass = || CheckResult::Pass("vda1".into());
+        let skip = || CheckResult::Skip("BIOS system".into());
+        let all = [pass(), fail(), skip(), warn()];
+        for (i, a) in all.iter().enumerate() {
+            for (j, b) in all.iter().enumerate() {
+                assert_eq!(a == b, i == j, "{a:?} vs {b:?}");
+            }
+        }
+
+        assert_ne!(pass(), CheckResult::Pass("vda2".into()));
+        assert_ne!(
+            fail(),
+            CheckResult::Fail {
+                expected: "vda1".into(),
+                actual: "vda2".into(),
+            }
+        );
+        assert_ne!(
+            fail(),
+            CheckResult::Fail {
+                expected: "vda2".into(),
+                actual: "nothing".into(),
+            }
+        );
+        assert_ne!(skip(), CheckResult::Skip("UEFI".into()));
+        assert_ne!(
+            warn(),
+            CheckResult::Warn {
+                message: "other".into(),
+            }
+        );
+        // Same text in different variants is still different.
+        assert_ne!(CheckResult::Pass("x".into()), CheckResult::Skip("x".into()));
+    }
+
+    #[test]
+    fn test_hash_collapses_equal_results() {
+        use std::collections::HashSet;
+
+        let results: HashSet<CheckResult> = [
+            CheckResult::Pass("vda1".into()),
+            fail(),
+            CheckResult::Pass("vda1".into()),
+            fail(),
+            CheckResult::Fail {
+                expected: "vda1".into(),
+                actual: "vda2".into(),
+            },
+            warn(),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(results.len(), 4);
+        assert!(results.contains(&fail()));
+    }
+
+    #[test]
+    fn test_warned() {
+        assert!(warn().warned());
+        assert!(!fail().warned());
+        assert!(!CheckResult::Skip("BIOS system".into()).warned());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_pass_round_trip() {
+        let json = serde_json::to_string(&CheckResult::Pass("vda1 exists".into())).unwrap();
+        assert_eq!(json, r#"{"type":"pass","message":"vda1 exists"}"#);
+
+        let back: CheckResult = serde_json::from_str(&json).unwrap();
+        assert!(matches!(back, CheckResult::Pass(ref m) if m == "vda1 exists"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_fail_round_trip() {
+        let json = serde_json::to_string(&fail()).unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"fail","expected":"vda1","actual":"nothing"}"#
+        );
+
+        let back: CheckResult = serde_json::from_str(&json).unwrap();
+        assert!(matches!(
+            back,
+            CheckResult::Fail { ref expected, ref actual }
+                if expected == "vda1" && actual == "nothing"
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_skip_round_trip() {
+        let json = serde_json::to_string(&CheckResult::Skip("BIOS".into())).unwrap();
+        assert_eq!(json, r#"{"type":"skip","reason":"BIOS"}"#);
+
+        let back: CheckResult = serde_json::from_str(&json).unwrap();
+        assert!(matches!(back, CheckResult::Skip(ref r) if r == "BIOS"));
+    }
+}
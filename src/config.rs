@@ -0,0 +1,325 @@
+//! Runtime configuration read from the environment or set programmatically.
+
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use crate::Severity;
+
+/// Minimum severity that still bails; anything lower is downgraded to a warning.
+pub(crate) const MIN_SEVERITY_ENV: &str = "CHEAT_GUARD_MIN_SEVERITY";
+
+/// Error message format for bails: `human` (default), `json`, or `compact`.
+pub(crate) const FORMAT_ENV: &str = "CHEAT_GUARD_FORMAT";
+
+/// How a [`CheatError`](crate::CheatError) renders itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OutputFormat {
+    /// The multi-line `CHEAT-GUARDED FAILURE` banner.
+    Human,
+    /// A single-line JSON object.
+    Json,
+    /// A single `[SEVERITY] key="value"` line.
+    Compact,
+}
+
+/// The format selected by `CHEAT_GUARD_FORMAT`; anything unrecognized is human.
+pub(crate) fn output_format() -> OutputFormat {
+    match std::env::var(FORMAT_ENV) {
+        Ok(value) if value.trim().eq_ignore_ascii_case("json") => OutputFormat::Json,
+        Ok(value) if value.trim().eq_ignore_ascii_case("compact") => OutputFormat::Compact,
+        _ => OutputFormat::Human,
+    }
+}
+
+/// With the `tracing` feature, `1` or `true` replaces stderr banners with
+/// the tracing events.
+pub(crate) const TRACING_ONLY_ENV: &str = "CHEAT_GUARD_TRACING_ONLY";
+
+/// Whether stderr banners are suppressed in favor of tracing events.
+pub(crate) fn tracing_only() -> bool {
+    cfg!(feature = "tracing")
+        && std::env::var(TRACING_ONLY_ENV).is_ok_and(|value| value == "1" || value == "true")
+}
+
+/// Banner color: `always`, `never`, or `auto` (default).
+pub(crate) const COLOR_ENV: &str = "CHEAT_GUARD_COLOR";
+
+/// The <https://no-color.org> convention: any non-empty value disables `auto` color.
+pub(crate) const NO_COLOR_ENV: &str = "NO_COLOR";
+
+/// Whether banners should colorize the severity and borders.
+///
+/// `auto` colors only when stderr is a terminal and `NO_COLOR` is unset.
+pub(crate) fn color_enabled() -> bool {
+    match std::env::var(COLOR_ENV) {
+        Ok(value) if value.trim().eq_ignore_ascii_case("always") => true,
+        Ok(value) if value.trim().eq_ignore_ascii_case("never") => false,
+        _ => {
+            std::env::var_os(NO_COLOR_ENV).is_none_or(|value| value.is_empty())
+                && std::io::stderr().is_terminal()
+        }
+    }
+}
+
+/// `always`, `never`, or `auto` (the default): whether a `doc_url` is printed
+/// as a clickable OSC 8 terminal hyperlink.
+pub(crate) const LINKS_ENV: &str = "CHEAT_GUARD_LINKS";
+
+/// Whether banners should print `SEE:` URLs as terminal hyperlinks.
+///
+/// `auto` links exactly when [`color_enabled`] colors.
+pub(crate) fn links_enabled() -> bool {
+    match std::env::var(LINKS_ENV) {
+        Ok(value) if value.trim().eq_ignore_ascii_case("always") => true,
+        Ok(value) if value.trim().eq_ignore_ascii_case("never") => false,
+        _ => color_enabled(),
+    }
+}
+
+/// Border width of printed banners, clamped to `MIN_WIDTH..=MAX_WIDTH`.
+pub(crate) const WIDTH_ENV: &str = "CHEAT_GUARD_WIDTH";
+
+const MIN_WIDTH: usize = 20;
+const MAX_WIDTH: usize = crate::text::BORDER.len();
+
+/// Width set by [`set_cheat_width`]; `0` means unset.
+static WIDTH: AtomicUsize = AtomicUsize::new(0);
+
+/// Set the border width of all banners, overriding `CHEAT_GUARD_WIDTH`.
+///
+/// The width is clamped to 20..=200.
+pub fn set_cheat_width(width: usize) {
+    WIDTH.store(width.clamp(MIN_WIDTH, MAX_WIDTH), Ordering::Relaxed);
+}
+
+/// Undo [`set_cheat_width`], going back to `CHEAT_GUARD_WIDTH` or the defaults.
+pub fn reset_cheat_width() {
+    WIDTH.store(0, Ordering::Relaxed);
+}
+
+/// The configured banner width, if any; banners fall back to their own default.
+pub(crate) fn banner_width() -> Option<usize> {
+    let width = match WIDTH.load(Ordering::Relaxed) {
+        0 => std::env::var(WIDTH_ENV).ok()?.trim().parse().ok()?,
+        width => width,
+    };
+    Some(width.clamp(MIN_WIDTH, MAX_WIDTH))
+}
+
+/// Longest `actual` value, in bytes, a failed check keeps; default 1024.
+pub(crate) const MAX_ACTUAL_ENV: &str = "CHEAT_GUARD_MAX_ACTUAL";
+
+const DEFAULT_MAX_ACTUAL: usize = 1024;
+
+/// The configured `actual` limit; unset or unparseable means 1024.
+pub(crate) fn max_actual() -> usize {
+    std::env::var(MAX_ACTUAL_ENV)
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(DEFAULT_MAX_ACTUAL)
+}
+
+/// `1` or `true` makes [`cheat_todo!`](crate::cheat_todo) bail instead of warn.
+pub(crate) const STRICT_TODO_ENV: &str = "CHEAT_GUARD_STRICT_TODO";
+
+/// Whether unimplemented checks should fail the run.
+pub(crate) fn strict_todo() -> bool {
+    std::env::var(STRICT_TODO_ENV).is_ok_and(|value| value == "1" || value == "true")
+}
+
+/// `1` or `true` makes warnings and downgraded bails fail like any other bail.
+pub(crate) const STRICT_ENV: &str = "CHEAT_GUARD_STRICT";
+
+/// Whether the run is gated strictly: nothing is tolerated as a warning.
+pub(crate) fn strict() -> bool {
+    std::env::var(STRICT_ENV).is_ok_and(|value| value == "1" || value == "true")
+}
+
+/// `jsonl` makes [`cheat_check!`](crate::cheat_check) write one JSON object
+/// per check to the banner writer.
+pub(crate) const STREAM_ENV: &str = "CHEAT_GUARD_STREAM";
+
+/// Whether checks are streamed as JSON Lines instead of printed for humans.
+pub(crate) fn stream_jsonl() -> bool {
+    std::env::var(STREAM_ENV).is_ok_and(|value| value.trim().eq_ignore_ascii_case("jsonl"))
+}
+
+/// Milliseconds within which a repeated banner is suppressed; 0 disables.
+pub(crate) const DEDUP_ENV: &str = "CHEAT_GUARD_DEDUP_MS";
+
+/// The banner de-duplication window; unset, 0, or unparseable means none.
+pub(crate) fn dedup_window() -> Option<Duration> {
+    std::env::var(DEDUP_ENV)
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .filter(|&ms| ms > 0)
+        .map(Duration::from_millis)
+}
+
+/// Failures of one check, per protects and location, after which further
+/// ones are printed one severity higher; 0 disables.
+pub(crate) const ESCALATE_AFTER_ENV: &str = "CHEAT_GUARD_ESCALATE_AFTER";
+
+/// The escalation threshold; unset, 0, or unparseable means none.
+pub(crate) fn escalate_after() -> Option<usize> {
+    std::env::var(ESCALATE_AFTER_ENV)
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .filter(|&after| after > 0)
+}
+
+/// Milliseconds a [`cheat_check!`](crate::cheat_check) condition may take
+/// before its duration is printed.
+pub(crate) const SLOW_ENV: &str = "CHEAT_GUARD_SLOW_MS";
+
+/// The slow-check threshold; unset or unparseable means one second.
+pub(crate) fn slow_threshold() -> Duration {
+    std::env::var(SLOW_ENV)
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .map_or(Duration::from_secs(1), Duration::from_millis)
+}
+
+/// Set to `true` by GitHub Actions runners.
+pub(crate) const GITHUB_ACTIONS_ENV: &str = "GITHUB_ACTIONS";
+
+/// Whether we are running inside a GitHub Actions workflow.
+pub(crate) fn in_github_actions() -> bool {
+    std::env::var(GITHUB_ACTIONS_ENV).is_ok_and(|value| value == "true")
+}
+
+/// The configured bail threshold, if `CHEAT_GUARD_MIN_SEVERITY` is set and valid.
+pub(crate) fn min_severity() -> Option<Severity> {
+    std::env::var(MIN_SEVERITY_ENV).ok()?.trim().parse().ok()
+}
+
+/// Whether a bail at `severity` should be downgraded to a stderr warning.
+/// Never under `CHEAT_GUARD_STRICT`.
+pub(crate) fn is_downgraded(severity: Severity) -> bool {
+    !strict() && min_severity().is_some_and(|min| severity < min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::EnvGuard;
+
+    #[test]
+    fn test_output_format_defaults_to_human() {
+        let _env = EnvGuard::unset(&[FORMAT_ENV]);
+        assert_eq!(output_format(), OutputFormat::Human);
+    }
+
+    #[test]
+    fn test_output_format_json() {
+        let _env = EnvGuard::set(&[(FORMAT_ENV, "JSON")]);
+        assert_eq!(output_format(), OutputFormat::Json);
+    }
+
+    #[test]
+    fn test_output_format_compact() {
+        let _env = EnvGuard::set(&[(FORMAT_ENV, " compact ")]);
+        assert_eq!(output_format(), OutputFormat::Compact);
+    }
+
+    #[test]
+    fn test_max_actual() {
+        let _env = EnvGuard::unset(&[MAX_ACTUAL_ENV]);
+        assert_eq!(max_actual(), 1024);
+        std::env::set_var(MAX_ACTUAL_ENV, "64");
+        assert_eq!(max_actual(), 64);
+        std::env::set_var(MAX_ACTUAL_ENV, "lots");
+        assert_eq!(max_actual(), 1024);
+    }
+
+    #[test]
+    fn test_dedup_window() {
+        let _env = EnvGuard::unset(&[DEDUP_ENV]);
+        assert_eq!(dedup_window(), None);
+        std::env::set_var(DEDUP_ENV, "0");
+        assert_eq!(dedup_window(), None);
+        std::env::set_var(DEDUP_ENV, " 250 ");
+        assert_eq!(dedup_window(), Some(Duration::from_millis(250)));
+        std::env::set_var(DEDUP_ENV, "soon");
+        assert_eq!(dedup_window(), None);
+    }
+
+    #[test]
+    fn test_slow_threshold() {
+        let _env = EnvGuard::unset(&[SLOW_ENV]);
+        assert_eq!(slow_threshold(), Duration::from_secs(1));
+        std::env::set_var(SLOW_ENV, "50");
+        assert_eq!(slow_threshold(), Duration::from_millis(50));
+        std::env::set_var(SLOW_ENV, "slow");
+        assert_eq!(slow_threshold(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_color_overrides() {
+        let _env = EnvGuard::set(&[(COLOR_ENV, "Always"), (NO_COLOR_ENV, "1")]);
+        assert!(color_enabled());
+        std::env::set_var(COLOR_ENV, "never");
+        assert!(!color_enabled());
+    }
+
+    #[test]
+    fn test_no_color_disables_auto() {
+        let _env = EnvGuard::set(&[(COLOR_ENV, "auto"), (NO_COLOR_ENV, "1")]);
+        assert!(!color_enabled());
+    }
+
+    #[test]
+    fn test_width_from_env_is_clamped() {
+        let _env = EnvGuard::set(&[(WIDTH_ENV, "40")]);
+        assert_eq!(banner_width(), Some(40));
+        std::env::set_var(WIDTH_ENV, "5");
+        assert_eq!(banner_width(), Some(20));
+        std::env::set_var(WIDTH_ENV, "1000");
+        assert_eq!(banner_width(), Some(200));
+        std::env::set_var(WIDTH_ENV, "wide");
+        assert_eq!(banner_width(), None);
+    }
+
+    #[test]
+    fn test_set_cheat_width_overrides_env() {
+        let _env = EnvGuard::set(&[(WIDTH_ENV, "40")]);
+        set_cheat_width(64);
+        assert_eq!(banner_width(), Some(64));
+        set_cheat_width(0);
+        assert_eq!(banner_width(), Some(20));
+        reset_cheat_width();
+        assert_eq!(banner_width(), Some(40));
+    }
+
+    #[test]
+    fn test_unset_downgrades_nothing() {
+        let _env = EnvGuard::unset(&[MIN_SEVERITY_ENV]);
+        assert!(!is_downgraded(Severity::Low));
+    }
+
+    #[test]
+    fn test_threshold_is_exclusive_below() {
+        let _env = EnvGuard::set(&[(MIN_SEVERITY_ENV, "high")]);
+        assert!(is_downgraded(Severity::Low));
+        assert!(is_downgraded(Severity::Medium));
+        assert!(!is_downgraded(Severity::High));
+        assert!(!is_downgraded(Severity::Critical));
+    }
+
+    #[test]
+    fn test_strict_overrides_threshold() {
+        let _env = EnvGuard::set(&[(MIN_SEVERITY_ENV, "high"), (STRICT_ENV, "1")]);
+        assert!(strict());
+        assert!(!is_downgraded(Severity::Low));
+        std::env::set_var(STRICT_ENV, "0");
+        assert!(!strict());
+        assert!(is_downgraded(Severity::Low));
+    }
+
+    #[test]
+    fn test_unparseable_downgrades_nothing() {
+        let _env = EnvGuard::set(&[(MIN_SEVERITY_ENV, "sometimes")]);
+        assert!(!is_downgraded(Severity::Low));
+    }
+}
@@ -0,0 +1,158 @@
+//! The console interface behind [`cheat_exec_contains!`](crate::cheat_exec_contains).
+
+use std::fmt;
+use std::time::Duration;
+
+/// A console that runs shell commands and captures their output.
+///
+/// Implement it for your test harness's console type (such as the
+/// install-tests `Console`) so [`cheat_exec_contains!`](crate::cheat_exec_contains)
+/// can run commands on it. The returned string is what the banner shows as
+/// STDOUT when the check fails.
+pub trait ConsoleExec {
+    /// Why a command could not be run, such as a timeout or a dead console.
+    type Error: fmt::Display;
+
+    /// Run `command`, waiting at most `timeout`, and return its output.
+    fn exec(&mut self, command: &str, timeout: Duration) -> Result<String, Self::Error>;
+}
+
+/// Calls [`ConsoleExec::exec`] under a name no inherent method shadows, so
+/// the macro can use method syntax (and its auto-borrowing) on both owned
+/// consoles and `&mut` bindings.
+pub trait ConsoleExecExt: ConsoleExec {
+    fn cheat_guard_exec(
+        &mut self,
+        command: &str,
+        timeout: Duration,
+    ) -> Result<String, Self::Error> {
+        ConsoleExec::exec(self, command, timeout)
+    }
+}
+
+impl<T: ConsoleExec + ?Sized> ConsoleExecExt for T {}
+
+#[cfg(all(test, feature = "anyhow"))]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::test_util::EnvGuard;
+    use crate::{config, CheatError};
+
+    #[derive(Default)]
+    struct MockConsole {
+        outputs: HashMap<&'static str, &'static str>,
+        ran: Vec<String>,
+    }
+
+    impl ConsoleExec for MockConsole {
+        type Error = String;
+
+        fn exec(&mut self, command: &str, _timeout: Duration) -> Result<String, String> {
+            self.ran.push(command.to_string());
+            match self.outputs.get(command) {
+                Some(output) => Ok(output.to_string()),
+                None => Err(format!("{command}: timed out")),
+            }
+        }
+    }
+
+    fn console() -> MockConsole {
+        MockConsole {
+            outputs: HashMap::from([("sfdisk /dev/vda", "Created /dev/vda1\nCreated /dev/vda2")]),
+            ..MockConsole::default()
+        }
+    }
+
+    fn partition(console: &mut MockConsole, needle: &str) -> anyhow::Result<String> {
+        let output = crate::cheat_exec_contains!(
+            console,
+            "sfdisk /dev/vda",
+            Duration::from_secs(30),
+            needle,
+            protects = "Disk is partitioned",
+            severity = "CRITICAL",
+            cheats = ["Accept exit code without verification"],
+            consequence = "No partitions, installation fails"
+        );
+        Ok(output)
+    }
+
+    #[test]
+    fn test_exec_contains_present() {
+        let _env = EnvGuard::unset(&[config::MIN_SEVERITY_ENV]);
+        let mut console = console();
+
+        let output = partition(&mut console, "vda2").unwrap();
+
+        assert_eq!(output, "Created /dev/vda1\nCreated /dev/vda2");
+        assert_eq!(console.ran, ["sfdisk /dev/vda"]);
+    }
+
+    #[test]
+    fn test_exec_contains_absent() {
+        let _env = EnvGuard::unset(&[config::FORMAT_ENV, config::MIN_SEVERITY_ENV]);
+        let mut console = console();
+
+        let err = partition(&mut console, "vda3").unwrap_err();
+        let cheat = CheatError::from_anyhow(&err).unwrap();
+
+        assert_eq!(
+            cheat.message,
+            "\"vda3\" not found in the output of `sfdisk /dev/vda`"
+        );
+        assert_eq!(
+            cheat.stdout.as_deref(),
+            Some("Created /dev/vda1\nCreated /dev/vda2")
+        );
+        assert_eq!(
+            cheat.metadata,
+            [("command".into(), "sfdisk /dev/vda".into())]
+        );
+        let banner = err.to_string();
+        assert!(banner.contains("sfdisk /dev/vda"));
+        assert!(banner.contains("Created /dev/vda2"));
+    }
+
+    #[test]
+    fn test_exec_contains_exec_error() {
+        let _env = EnvGuard::unset(&[config::MIN_SEVERITY_ENV]);
+        let mut console = MockConsole::default();
+
+        let err = partition(&mut console, "vda1").unwrap_err();
+        let cheat = CheatError::from_anyhow(&err).unwrap();
+
+        assert_eq!(
+            cheat.message,
+            "`sfdisk /dev/vda` failed: sfdisk /dev/vda: timed out"
+        );
+        assert_eq!(cheat.stdout, None);
+    }
+
+    #[test]
+    fn test_exec_error_is_never_downgraded() {
+        let _env = EnvGuard::set(&[(config::MIN_SEVERITY_ENV, "CRITICAL")]);
+        let mut console = MockConsole::default();
+        let result: anyhow::Result<String> = (|| {
+            let output = crate::cheat_exec_contains!(
+                console,
+                "sfdisk /dev/vda",
+                Duration::from_secs(30),
+                "vda1",
+                protects = "Disk is partitioned",
+                severity = "LOW",
+                cheats = [],
+                consequence = "No partitions"
+            );
+            Ok(output)
+        })();
+
+        let err = result.unwrap_err();
+        let cheat = CheatError::from_anyhow(&err).unwrap();
+        assert_eq!(cheat.severity, crate::Severity::Low);
+        assert!(cheat
+            .message
+            .ends_with("failed: sfdisk /dev/vda: timed out"));
+    }
+}
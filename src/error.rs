@@ -0,0 +1,1021 @@
+//! Structured error produced by cheat-guarded failures.
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt;
+
+#[cfg(feature = "std")]
+use crate::redact::redact;
+use crate::severity::{IntoSeverity, ParseSeverityError};
+use crate::text::{
+    border, dedupe_cheats, format_cheats, format_metadata, hyperlink, json_string, paint, wrap,
+};
+use crate::{CheatMeta, Severity};
+
+/// A cheat-guarded failure with its cheat documentation.
+///
+/// `Display` renders with the active [`CheatFormatter`](crate::CheatFormatter):
+/// the full `CHEAT-GUARDED FAILURE` banner by default, or
+/// [`to_json`](Self::to_json) when `CHEAT_GUARD_FORMAT=json`. [`cheat_bail!`](crate::cheat_bail)
+/// bails with this type, so it can be recovered from an `anyhow::Error` with
+/// `downcast_ref::<CheatError>()`. The rendered text is never colored or
+/// hyperlinked; only banners printed to stderr are.
+///
+/// `anyhow::Error::from(err)`, or `?` in a function returning
+/// `anyhow::Result`, keeps it whole: the `anyhow::Error` displays the same
+/// banner and `downcast_ref::<CheatError>()` returns it. That conversion is
+/// anyhow's own blanket `From` impl for `Error + Send + Sync + 'static`
+/// types; the same holds for `eyre::Report`.
+///
+/// It is `Clone + Debug + Error`, so it can be one variant of a downstream
+/// error enum. New fields may be added, so build it with [`CheatError::new`]
+/// and the `with_*` methods rather than a struct literal.
+///
+/// ```rust,ignore
+/// #[derive(Debug, thiserror::Error)]
+/// enum InstallError {
+///     #[error(transparent)]
+///     Cheat(#[from] CheatError),
+///     #[error("disk {0} not found")]
+///     MissingDisk(String),
+/// }
+///
+/// match install() {
+///     Err(InstallError::Cheat(err)) => report_cheat(&err),
+///     Err(other) => return Err(other),
+///     Ok(()) => {}
+/// }
+/// ```
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct CheatError {
+    /// What user scenario the failed check protects.
+    pub protects: String,
+    /// How bad it is for users if the check is cheated.
+    pub severity: Severity,
+    /// Ways the check could be cheated.
+    pub cheats: Vec<String>,
+    /// What users experience if the check is cheated.
+    pub consequence: String,
+    /// How to fix the failure for real, shown as `SUGGESTED FIX:` if set.
+    pub remedy: Option<String>,
+    /// Where the check is documented, shown as `SEE:` if set.
+    pub doc_url: Option<String>,
+    /// Extra `key: value` context, in insertion order, shown as `METADATA:`.
+    pub metadata: Vec<(String, String)>,
+    /// The banner's title line in place of `CHEAT-GUARDED FAILURE`, if set.
+    pub title: Option<String>,
+    /// Captured standard output of the command under test, shown as `STDOUT:`.
+    pub stdout: Option<String>,
+    /// Captured standard error of the command under test, shown as `STDERR:`.
+    pub stderr: Option<String>,
+    /// The actual error message.
+    pub message: String,
+    /// Where the failing check is in the caller's source, if known.
+    pub location: Option<Location>,
+    /// The underlying error, returned by [`Error::source`].
+    pub source: Option<Arc<dyn Error + Send + Sync + 'static>>,
+}
+
+/// A source position captured with `file!()`, `line!()`, and `column!()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Location {
+    pub file: &'static str,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl Location {
+    pub const fn new(file: &'static str, line: u32, column: u32) -> Self {
+        Location { file, line, column }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+impl CheatError {
+    /// Cheats that repeat an earlier one, ignoring surrounding whitespace,
+    /// are dropped. The message goes through the
+    /// [redactor](crate::set_cheat_redactor), if one is installed.
+    pub fn new(
+        protects: impl Into<String>,
+        severity: Severity,
+        cheats: impl IntoIterator<Item = impl Into<String>>,
+        consequence: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        #[cfg(feature = "std")]
+        let message = redact(message.into());
+        #[cfg(not(feature = "std"))]
+        let message = message.into();
+        CheatError {
+            protects: protects.into(),
+            severity,
+            cheats: dedupe_cheats(cheats.into_iter().map(Into::into)),
+            consequence: consequence.into(),
+            remedy: None,
+            doc_url: None,
+            metadata: Vec::new(),
+            title: None,
+            stdout: None,
+            stderr: None,
+            message,
+            location: None,
+            source: None,
+        }
+    }
+
+    /// Start building a `CheatError` field by field, for cheat documentation
+    /// that is only known at runtime.
+    ///
+    /// The result renders exactly as the [`cheat_bail!`](crate::cheat_bail)
+    /// error for the same fields and location. Unlike the macro, building one
+    /// neither prints nor counts it; return it with `Err(err.into())`.
+    ///
+    /// ```rust,ignore
+    /// for def in &loaded_defs {
+    ///     let err = CheatError::builder()
+    ///         .protects(&def.protects)
+    ///         .severity(def.severity.as_str())
+    ///         .cheats(&def.cheats)
+    ///         .consequence(&def.consequence)
+    ///         .message(format!("{} failed", def.name))
+    ///         .build();
+    ///     return Err(err.into());
+    /// }
+    /// ```
+    pub fn builder() -> CheatErrorBuilder {
+        CheatErrorBuilder::default()
+    }
+
+    /// Attach the underlying error that caused this failure.
+    pub fn with_source(
+        mut self,
+        source: impl Into<Box<dyn Error + Send + Sync + 'static>>,
+    ) -> Self {
+        self.source = Some(Arc::from(source.into()));
+        self
+    }
+
+    /// Attach a suggested fix, printed after the consequence.
+    pub fn with_remedy(mut self, remedy: impl Into<String>) -> Self {
+        self.remedy = Some(remedy.into());
+        self
+    }
+
+    /// Attach a link to the check's documentation, e.g. a wiki page.
+    pub fn with_doc_url(mut self, doc_url: impl Into<String>) -> Self {
+        self.doc_url = Some(doc_url.into());
+        self
+    }
+
+    /// Add one `key: value` line to the `METADATA:` section.
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.push((key.into(), value.into()));
+        self
+    }
+
+    /// Append one cheat, unless it repeats one already listed.
+    pub fn with_cheat(mut self, cheat: impl Into<String>) -> Self {
+        self.cheats = dedupe_cheats(self.cheats.into_iter().chain([cheat.into()]));
+        self
+    }
+
+    /// Replace the banner's title line, keeping its borders and sections.
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Attach the captured standard output of the command that was checked.
+    /// It goes through the [redactor](crate::set_cheat_redactor) like the message.
+    pub fn with_stdout(mut self, stdout: impl Into<String>) -> Self {
+        let stdout = stdout.into();
+        #[cfg(feature = "std")]
+        let stdout = redact(stdout);
+        self.stdout = Some(stdout);
+        self
+    }
+
+    /// Attach the captured standard error of the command that was checked.
+    /// It goes through the [redactor](crate::set_cheat_redactor) like the message.
+    pub fn with_stderr(mut self, stderr: impl Into<String>) -> Self {
+        let stderr = stderr.into();
+        #[cfg(feature = "std")]
+        let stderr = redact(stderr);
+        self.stderr = Some(stderr);
+        self
+    }
+
+    /// Attach the source location of the failing check.
+    pub fn with_location(mut self, location: Location) -> Self {
+        self.location = Some(location);
+        self
+    }
+
+    /// The `CheatError` inside `err`, if it came from a cheat-guarded failure.
+    #[cfg(feature = "anyhow")]
+    pub fn from_anyhow(err: &anyhow::Error) -> Option<&CheatError> {
+        err.downcast_ref::<CheatError>()
+    }
+}
+
+/// Builder returned by [`CheatError::builder`].
+#[derive(Debug, Clone, Default)]
+pub struct CheatErrorBuilder {
+    protects: Option<String>,
+    severity: Option<Result<Severity, ParseSeverityError>>,
+    cheats: Vec<String>,
+    consequence: Option<String>,
+    message: Option<String>,
+    remedy: Option<String>,
+    doc_url: Option<String>,
+    metadata: Vec<(String, String)>,
+    title: Option<String>,
+    stdout: Option<String>,
+    stderr: Option<String>,
+    location: Option<Location>,
+    source: Option<Arc<dyn Error + Send + Sync + 'static>>,
+}
+
+impl CheatErrorBuilder {
+    pub fn protects(mut self, protects: impl Into<String>) -> Self {
+        self.protects = Some(protects.into());
+        self
+    }
+
+    /// A [`Severity`], or one of "CRITICAL", "HIGH", "MEDIUM", "LOW".
+    pub fn severity(mut self, severity: impl IntoSeverity) -> Self {
+        self.severity = Some(severity.into_severity());
+        self
+    }
+
+    /// Add one cheat vector; call once per cheat, in banner order.
+    pub fn cheat(mut self, cheat: impl Into<String>) -> Self {
+        self.cheats.push(cheat.into());
+        self
+    }
+
+    /// Add several cheat vectors at once.
+    pub fn cheats<I>(mut self, cheats: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.cheats
+            .extend(cheats.into_iter().map(|cheat| cheat.as_ref().to_string()));
+        self
+    }
+
+    pub fn consequence(mut self, consequence: impl Into<String>) -> Self {
+        self.consequence = Some(consequence.into());
+        self
+    }
+
+    /// Take protects, severity, cheats, and consequence from `meta`,
+    /// replacing any set so far.
+    pub fn meta(mut self, meta: &CheatMeta) -> Self {
+        self.protects = Some(meta.protects.clone());
+        self.severity = Some(Ok(meta.severity));
+        self.cheats = meta.cheats.clone();
+        self.consequence = Some(meta.consequence.clone());
+        self
+    }
+
+    /// The error message, the `ERROR:` section of the banner.
+    pub fn message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// See [`CheatError::with_remedy`].
+    pub fn remedy(mut self, remedy: impl Into<String>) -> Self {
+        self.remedy = Some(remedy.into());
+        self
+    }
+
+    /// See [`CheatError::with_doc_url`].
+    pub fn doc_url(mut self, doc_url: impl Into<String>) -> Self {
+        self.doc_url = Some(doc_url.into());
+        self
+    }
+
+    /// See [`CheatError::with_metadata`].
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.push((key.into(), value.into()));
+        self
+    }
+
+    /// See [`CheatError::with_title`].
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// See [`CheatError::with_stdout`].
+    pub fn stdout(mut self, stdout: impl Into<String>) -> Self {
+        self.stdout = Some(stdout.into());
+        self
+    }
+
+    /// See [`CheatError::with_stderr`].
+    pub fn stderr(mut self, stderr: impl Into<String>) -> Self {
+        self.stderr = Some(stderr.into());
+        self
+    }
+
+    /// See [`CheatError::with_source`].
+    pub fn source(mut self, source: impl Into<Box<dyn Error + Send + Sync + 'static>>) -> Self {
+        self.source = Some(Arc::from(source.into()));
+        self
+    }
+
+    /// Where the failure is reported from; defaults to the caller of
+    /// [`build`](Self::build).
+    pub fn location(mut self, location: Location) -> Self {
+        self.location = Some(location);
+        self
+    }
+
+    /// # Panics
+    ///
+    /// If `protects`, `severity`, `consequence`, or `message` was never set,
+    /// or the severity name is unknown.
+    #[track_caller]
+    pub fn build(self) -> CheatError {
+        let severity = match self.severity {
+            Some(severity) => severity.unwrap_or_else(|err| panic!("{}", err)),
+            None => panic!("CheatError::builder() is missing `severity`"),
+        };
+        let caller = core::panic::Location::caller();
+        let location =
+            self.location
+                .unwrap_or(Location::new(caller.file(), caller.line(), caller.column()));
+        let mut err = CheatError::new(
+            self.protects
+                .expect("CheatError::builder() is missing `protects`"),
+            severity,
+            self.cheats,
+            self.consequence
+                .expect("CheatError::builder() is missing `consequence`"),
+            self.message
+                .expect("CheatError::builder() is missing `message`"),
+        )
+        .with_location(location);
+        err.remedy = self.remedy;
+        err.doc_url = self.doc_url;
+        err.metadata = self.metadata;
+        err.title = self.title;
+        err.source = self.source;
+        if let Some(stdout) = self.stdout {
+            err = err.with_stdout(stdout);
+        }
+        if let Some(stderr) = self.stderr {
+            err = err.with_stderr(stderr);
+        }
+        err
+    }
+}
+
+/// Whether `err` is a cheat-guarded failure rather than a plain error.
+#[cfg(feature = "anyhow")]
+pub fn is_cheat_error(err: &anyhow::Error) -> bool {
+    CheatError::from_anyhow(err).is_some()
+}
+
+/// What the bail macros return in `Err`. With `anyhow` this is
+/// `anyhow::Error`, holding the [`CheatError`] for `downcast_ref`.
+#[cfg(feature = "anyhow")]
+pub type BailError = anyhow::Error;
+
+/// What the bail macros return in `Err`. With `eyre` but not `anyhow` this is
+/// `eyre::Report`, holding the [`CheatError`] for `downcast_ref`.
+#[cfg(all(feature = "eyre", not(feature = "anyhow")))]
+pub type BailError = eyre::Report;
+
+/// What the bail macros return in `Err`. Without `anyhow` or `eyre` this is
+/// the [`CheatError`] itself.
+#[cfg(all(feature = "std", not(any(feature = "anyhow", feature = "eyre"))))]
+pub type BailError = CheatError;
+
+/// `err` wrapped in an `anyhow::Error`.
+#[cfg(feature = "anyhow")]
+pub fn bail_error(err: CheatError) -> BailError {
+    anyhow::Error::new(err)
+}
+
+/// `err` wrapped in an `eyre::Report`.
+#[cfg(all(feature = "eyre", not(feature = "anyhow")))]
+pub fn bail_error(err: CheatError) -> BailError {
+    eyre::Report::new(err)
+}
+
+/// `err` unchanged, as it already is the bail macros' error type.
+#[cfg(all(feature = "std", not(any(feature = "anyhow", feature = "eyre"))))]
+pub fn bail_error(err: CheatError) -> BailError {
+    err
+}
+
+/// An unknown severity name wrapped in an `anyhow::Error`, which
+/// `downcast_ref::<ParseSeverityError>()` recovers.
+#[cfg(feature = "anyhow")]
+pub fn severity_error(err: ParseSeverityError) -> BailError {
+    anyhow::Error::new(err)
+}
+
+/// An unknown severity name wrapped in an `eyre::Report`, which
+/// `downcast_ref::<ParseSeverityError>()` recovers.
+#[cfg(all(feature = "eyre", not(feature = "anyhow")))]
+pub fn severity_error(err: ParseSeverityError) -> BailError {
+    eyre::Report::new(err)
+}
+
+/// An unknown severity name, which cannot be returned when the error type is
+/// [`CheatError`]: this panics with the parse error, as
+/// [`cheat_check!`](crate::cheat_check) does.
+#[cfg(all(feature = "std", not(any(feature = "anyhow", feature = "eyre"))))]
+pub fn severity_error(err: ParseSeverityError) -> BailError {
+    panic!("{}", err)
+}
+
+/// A `cheat_matches!` pattern that failed to compile, as a plain
+/// `anyhow::Error` naming the pattern.
+#[cfg(all(feature = "regex", feature = "anyhow"))]
+pub fn pattern_error(pattern: &str, err: regex::Error) -> BailError {
+    anyhow::anyhow!("invalid cheat_matches! pattern {:?}: {}", pattern, err)
+}
+
+/// A `cheat_matches!` pattern that failed to compile, as a plain
+/// `eyre::Report` naming the pattern.
+#[cfg(all(feature = "regex", feature = "eyre", not(feature = "anyhow")))]
+pub fn pattern_error(pattern: &str, err: regex::Error) -> BailError {
+    eyre::eyre!("invalid cheat_matches! pattern {:?}: {}", pattern, err)
+}
+
+/// A `cheat_matches!` pattern that failed to compile. Without `anyhow` or
+/// `eyre` there is no plain error to return, so this panics naming the
+/// pattern, like [`severity_error`].
+#[cfg(all(feature = "regex", not(any(feature = "anyhow", feature = "eyre"))))]
+pub fn pattern_error(pattern: &str, err: regex::Error) -> BailError {
+    panic!("invalid cheat_matches! pattern {:?}: {}", pattern, err)
+}
+
+impl CheatError {
+    /// Render as a single-line JSON object with `protects`, `severity`,
+    /// `cheats`, `consequence`, `error`, `location`, `cause`, and `doc_url`
+    /// (`null` when absent), and a `metadata` object (`{}` when empty).
+    pub fn to_json(&self) -> String {
+        let cheats = self
+            .cheats
+            .iter()
+            .map(|c| json_string(c))
+            .collect::<Vec<_>>()
+            .join(",");
+        let location = match &self.location {
+            Some(location) => json_string(&location.to_string()),
+            None => "null".to_string(),
+        };
+        let cause = match &self.source {
+            Some(source) => json_string(&source.to_string()),
+            None => "null".to_string(),
+        };
+        let doc_url = match &self.doc_url {
+            Some(doc_url) => json_string(doc_url),
+            None => "null".to_string(),
+        };
+        let metadata = self
+            .metadata
+            .iter()
+            .map(|(key, value)| format!("{}:{}", json_string(key), json_string(value)))
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            "{{\"protects\":{},\"severity\":{},\"cheats\":[{}],\"consequence\":{},\"error\":{},\"location\":{},\"cause\":{},\"doc_url\":{},\"metadata\":{{{}}}}}",
+            json_string(&self.protects),
+            json_string(self.severity.as_str()),
+            cheats,
+            json_string(&self.consequence),
+            json_string(&self.message),
+            location,
+            cause,
+            doc_url,
+            metadata
+        )
+    }
+
+    /// Render as one line: `[SEVERITY] protects="..." error="..." cheats=N`.
+    ///
+    /// String values are quoted and escaped as in [`to_json`](Self::to_json),
+    /// so the result never contains a newline.
+    pub fn to_compact(&self) -> String {
+        format!(
+            "[{}] protects={} error={} cheats={}",
+            self.severity,
+            json_string(&self.protects),
+            json_string(&self.message),
+            self.cheats.len()
+        )
+    }
+
+    /// Write the `CHEAT-GUARDED FAILURE` banner to `out`.
+    ///
+    /// Unlike `Display`, this ignores any installed formatter, and it works
+    /// without the `std` feature. The banner is uncolored, and without `std`
+    /// 70 columns wide.
+    pub fn write_banner<W: fmt::Write + ?Sized>(&self, out: &mut W) -> fmt::Result {
+        write!(out, "{}", self.banner(&failure_title()))
+    }
+
+    /// The `CHEAT-GUARDED FAILURE` banner with everything that changes from
+    /// run to run normalized, for snapshot tests.
+    ///
+    /// The location is replaced with `<LOCATION>`, and the banner is always
+    /// uncolored, unlinked, and 70 columns wide whatever the environment
+    /// says. The title is the error's own `title`, or `CHEAT-GUARDED FAILURE`
+    /// even if [`set_cheat_title`](crate::set_cheat_title) changed the
+    /// default. `Display` is unaffected.
+    pub fn render_stable(&self) -> String {
+        Banner {
+            err: self,
+            title: FAILURE_TITLE,
+            styled: false,
+            stable: true,
+        }
+        .to_string()
+    }
+
+    /// Render the banner under a different title line, uncolored.
+    pub(crate) fn banner<'a>(&'a self, title: &'a str) -> Banner<'a> {
+        Banner {
+            err: self,
+            title,
+            styled: false,
+            stable: false,
+        }
+    }
+
+    /// [`banner`](Self::banner), colored and linked as configured when
+    /// `styled`.
+    #[cfg(feature = "std")]
+    pub(crate) fn styled_banner<'a>(&'a self, title: &'a str, styled: bool) -> Banner<'a> {
+        Banner {
+            styled,
+            ..self.banner(title)
+        }
+    }
+}
+
+impl fmt::Display for CheatError {
+    #[cfg(feature = "std")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&crate::formatter::render(self))
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_banner(f)
+    }
+}
+
+pub(crate) const FAILURE_TITLE: &str = "CHEAT-GUARDED FAILURE";
+
+/// The failure banner title: the one set with
+/// [`set_cheat_title`](crate::set_cheat_title), or `CHEAT-GUARDED FAILURE`.
+#[cfg(feature = "std")]
+pub(crate) fn failure_title() -> String {
+    crate::formatter::title().unwrap_or_else(|| FAILURE_TITLE.to_string())
+}
+
+/// Without `std` there is no global title to set.
+#[cfg(not(feature = "std"))]
+pub(crate) fn failure_title() -> String {
+    FAILURE_TITLE.to_string()
+}
+
+/// Default border width of [`CheatError`] banners.
+pub(crate) const BAIL_WIDTH: usize = 70;
+
+/// The ANSI color, border width, and whether to hyperlink the `doc_url` for
+/// a banner of `severity`. Only a `styled` banner, one printed to stderr,
+/// gets color and links.
+#[cfg(feature = "std")]
+fn banner_style(severity: Severity, styled: bool) -> (Option<&'static str>, usize, bool) {
+    let color = (styled && crate::config::color_enabled()).then(|| severity.ansi_color());
+    let links = styled && crate::config::links_enabled();
+    (color, crate::output::width(BAIL_WIDTH), links)
+}
+
+/// Without `std` there is no environment to configure banners from.
+#[cfg(not(feature = "std"))]
+fn banner_style(_severity: Severity, _styled: bool) -> (Option<&'static str>, usize, bool) {
+    (None, BAIL_WIDTH, false)
+}
+
+/// The banner for a [`CheatError`] with a chosen title.
+pub(crate) struct Banner<'a> {
+    err: &'a CheatError,
+    title: &'a str,
+    /// Color and link it per `CHEAT_GUARD_COLOR`, for printing to stderr.
+    styled: bool,
+    /// Render for [`CheatError::render_stable`].
+    stable: bool,
+}
+
+impl fmt::Display for Banner<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let err = self.err;
+        let (color, width, links) = if self.stable {
+            (None, BAIL_WIDTH, false)
+        } else {
+            banner_style(err.severity, self.styled)
+        };
+        let border = paint(border(width), color);
+        let title = err.title.as_deref().unwrap_or(self.title);
+        let severity = paint(err.severity.as_str(), color);
+        let cheats = format_cheats(&err.cheats);
+        let location = match &err.location {
+            Some(_) if self.stable => "LOCATION: <LOCATION>\n".to_string(),
+            Some(location) => format!("LOCATION: {}\n", location),
+            None => String::new(),
+        };
+        let see = match &err.doc_url {
+            Some(doc_url) => format!("SEE: {}\n", hyperlink(doc_url, links)),
+            None => String::new(),
+        };
+        let cause = match &err.source {
+            Some(source) => format!("\nCAUSE:\n{}\n", source),
+            None => String::new(),
+        };
+        let remedy = match &err.remedy {
+            Some(remedy) => format!("SUGGESTED FIX:\n{}\n\n", wrap(remedy, width, 0)),
+            None => String::new(),
+        };
+        let captured = [("STDOUT", &err.stdout), ("STDERR", &err.stderr)]
+            .into_iter()
+            .filter_map(|(label, text)| {
+                let text = text.as_deref()?.trim_end_matches('\n');
+                Some(format!("\n{}:\n{}\n", label, text))
+            })
+            .collect::<String>();
+        let metadata = if err.metadata.is_empty() {
+            String::new()
+        } else {
+            format!("METADATA:\n{}\n\n", format_metadata(&err.metadata))
+        };
+
+        write!(
+            f,
+            "\n{border}\n\
+             === {title} ===\n\
+             {border}\n\n\
+             PROTECTS: {protects}\n\
+             SEVERITY: {severity}\n\
+             {location}\
+             {see}\n\
+             CHEAT VECTORS:\n\
+             {cheats}\n\n\
+             USER CONSEQUENCE:\n\
+             {consequence}\n\n\
+             {remedy}\
+             {metadata}\
+             ERROR:\n\
+             {error}\n\
+             {cause}\
+             {captured}\
+             {border}\n",
+            border = border,
+            title = title,
+            protects = wrap(&err.protects, width, 10),
+            severity = severity,
+            location = location,
+            see = see,
+            cheats = cheats,
+            consequence = wrap(&err.consequence, width, 0),
+            remedy = remedy,
+            metadata = metadata,
+            error = wrap(&err.message, width, 0),
+            cause = cause,
+            captured = captured
+        )
+    }
+}
+
+impl Error for CheatError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn Error + 'static))
+    }
+}
+
+/// Severity maps to the diagnostic level; cheats and consequence become the help.
+#[cfg(feature = "miette")]
+impl miette::Diagnostic for CheatError {
+    fn severity(&self) -> Option<miette::Severity> {
+        Some(match self.severity {
+            Severity::Critical | Severity::High => miette::Severity::Error,
+            Severity::Medium => miette::Severity::Warning,
+            Severity::Low => miette::Severity::Advice,
+        })
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(format!(
+            "cheat vectors:\n{}\nuser consequence: {}",
+            format_cheats(&self.cheats),
+            self.consequence
+        )))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::config;
+    use crate::test_util::{lock_globals, EnvGuard};
+
+    #[test]
+    fn test_display_renders_banner() {
+        let _env = EnvGuard::set(&[(config::FORMAT_ENV, "human"), (config::COLOR_ENV, "never")]);
+        let err = CheatError::new(
+            "Both partitions were created",
+            Severity::Critical,
+            [
+                "Check vda1 OR vda2 instead of AND",
+                "Skip verification entirely",
+            ],
+            "Missing partition causes mount failure",
+            "Expected vda1 AND vda2, got: vda disk",
+        );
+        let border = "=".repeat(70);
+        let expected = format!(
+            "\n{border}\n=== CHEAT-GUARDED FAILURE ===\n{border}\n\n\
+             PROTECTS: Both partitions were created\n\
+             SEVERITY: CRITICAL\n\n\
+             CHEAT VECTORS:\n\
+             \x20 1. Check vda1 OR vda2 instead of AND\n\
+             \x20 2. Skip verification entirely\n\n\
+             USER CONSEQUENCE:\n\
+             Missing partition causes mount failure\n\n\
+             ERROR:\n\
+             Expected vda1 AND vda2, got: vda disk\n\
+             {border}\n"
+        );
+        assert_eq!(err.to_string(), expected);
+    }
+
+    #[test]
+    fn test_styled_banner_color_always() {
+        let _env = EnvGuard::set(&[(config::COLOR_ENV, "always"), (config::FORMAT_ENV, "human")]);
+        let err = CheatError::new("p", Severity::Critical, ["c"], "q", "m");
+        let rendered = err.styled_banner(FAILURE_TITLE, true).to_string();
+        let border = format!("\x1b[31m{}\x1b[0m", "=".repeat(70));
+        assert!(rendered.starts_with(&format!("\n{border}\n")));
+        assert!(rendered.ends_with(&format!("{border}\n")));
+        assert!(rendered.contains("SEVERITY: \x1b[31mCRITICAL\x1b[0m\n"));
+
+        let plain = err.to_string();
+        assert!(!plain.contains('\x1b'), "{plain:?}");
+        assert!(plain.contains("SEVERITY: CRITICAL\n"));
+    }
+
+    #[test]
+    fn test_display_color_never() {
+        let _env = EnvGuard::set(&[(config::COLOR_ENV, "never"), (config::FORMAT_ENV, "human")]);
+        let rendered = CheatError::new("p", Severity::Low, ["c"], "q", "m").to_string();
+        assert!(!rendered.contains('\x1b'));
+        assert!(rendered.contains("SEVERITY: LOW\n"));
+    }
+
+    #[test]
+    fn test_display_wraps_long_sections() {
+        let _env = EnvGuard::set(&[
+            (config::WIDTH_ENV, "40"),
+            (config::FORMAT_ENV, "human"),
+            (config::COLOR_ENV, "never"),
+        ]);
+        let consequence = "The installed system boots to an emergency shell because \
+                           the root partition was never formatted";
+        let err = CheatError::new(
+            "Root is formatted as ext4 before install",
+            Severity::High,
+            ["c"],
+            consequence,
+            "m",
+        );
+
+        let rendered = err.to_string();
+        assert!(
+            rendered.lines().all(|line| line.chars().count() <= 40),
+            "{rendered}"
+        );
+        assert!(
+            rendered.contains("PROTECTS: Root is formatted as ext4\n          before install\n")
+        );
+        let words: Vec<_> = rendered
+            .split("USER CONSEQUENCE:\n")
+            .nth(1)
+            .and_then(|rest| rest.split("\n\n").next())
+            .unwrap()
+            .split_whitespace()
+            .collect();
+        assert_eq!(words, consequence.split_whitespace().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_new_dedupes_cheats() {
+        let _env = EnvGuard::set(&[(config::FORMAT_ENV, "human")]);
+        let err = CheatError::new(
+            "p",
+            Severity::Low,
+            [
+                "Skip check",
+                "Accept any output",
+                "  Skip check",
+                "Accept any output",
+            ],
+            "q",
+            "m",
+        );
+        assert_eq!(err.cheats, ["Skip check", "Accept any output"]);
+        assert!(err
+            .to_string()
+            .contains("CHEAT VECTORS:\n  1. Skip check\n  2. Accept any output\n\n"));
+    }
+
+    #[test]
+    fn test_display_renders_location() {
+        let _env = EnvGuard::set(&[(config::COLOR_ENV, "never")]);
+        let err = CheatError::new("p", Severity::High, ["c"], "q", "m")
+            .with_location(Location::new("src/steps/disk.rs", 42, 9));
+        assert!(err
+            .to_string()
+            .contains("SEVERITY: HIGH\nLOCATION: src/steps/disk.rs:42:9\n\nCHEAT VECTORS:\n"));
+    }
+
+    #[test]
+    fn test_render_stable_normalizes_location() {
+        let _env = EnvGuard::set(&[(config::COLOR_ENV, "always"), (config::WIDTH_ENV, "40")]);
+        let at = |line| {
+            CheatError::new("Disk is partitioned", Severity::High, ["Skip it"], "q", "m")
+                .with_location(Location::new("src/steps/disk.rs", line, 9))
+        };
+        let (first, second) = (at(42), at(97));
+
+        let border = "=".repeat(70);
+        assert_eq!(
+            first.render_stable(),
+            format!(
+                "\n{border}\n=== CHEAT-GUARDED FAILURE ===\n{border}\n\n\
+                 PROTECTS: Disk is partitioned\n\
+                 SEVERITY: HIGH\n\
+                 LOCATION: <LOCATION>\n\n\
+                 CHEAT VECTORS:\n\
+                 \x20 1. Skip it\n\n\
+                 USER CONSEQUENCE:\n\
+                 q\n\n\
+                 ERROR:\n\
+                 m\n\
+                 {border}\n"
+            )
+        );
+        assert_eq!(first.render_stable(), second.render_stable());
+        let mut banner = String::new();
+        first.write_banner(&mut banner).unwrap();
+        assert!(banner.contains("LOCATION: src/steps/disk.rs:42:9\n"));
+    }
+
+    #[test]
+    fn test_banner_links_doc_url_when_enabled() {
+        let _env = EnvGuard::set(&[(config::LINKS_ENV, "always"), (config::FORMAT_ENV, "human")]);
+        let err =
+            CheatError::new("p", Severity::Low, ["c"], "q", "m").with_doc_url("https://x.dev");
+        let link = "SEE: \x1b]8;;https://x.dev\x1b\\https://x.dev\x1b]8;;\x1b\\\n";
+        let styled = || err.styled_banner(FAILURE_TITLE, true).to_string();
+        assert!(styled().contains(link));
+        assert!(err.to_string().contains("SEE: https://x.dev\n"));
+        assert!(err.render_stable().contains("SEE: https://x.dev\n"));
+        std::env::set_var(config::LINKS_ENV, "never");
+        assert!(!styled().contains("\x1b]8"));
+    }
+
+    #[test]
+    fn test_builder_defaults_location_to_caller() {
+        let meta = CheatMeta::builder()
+            .protects("p")
+            .severity(Severity::Low)
+            .consequence("q")
+            .build();
+        let builder = CheatError::builder().meta(&meta).message("m");
+        let (err, line) = (builder.build(), line!());
+        let location = err.location.unwrap();
+        assert_eq!((location.file, location.line), (file!(), line));
+        assert_eq!((err.protects.as_str(), err.severity), ("p", Severity::Low));
+    }
+
+    #[test]
+    #[should_panic(expected = "missing `message`")]
+    fn test_builder_panics_on_missing_message() {
+        CheatError::builder()
+            .protects("p")
+            .severity("LOW")
+            .consequence("q")
+            .build();
+    }
+
+    #[test]
+    fn test_display_renders_metadata() {
+        let _globals = lock_globals();
+        let err = CheatError::new("p", Severity::High, ["c"], "q", "m");
+        assert!(!err.to_string().contains("METADATA:"));
+
+        let err = err
+            .with_metadata("device", "/dev/vda")
+            .with_metadata("scenario", "UEFI-3")
+            .with_metadata("cmd", "sfdisk");
+        assert!(err.to_string().contains(
+            "USER CONSEQUENCE:\nq\n\n\
+             METADATA:\n  device:   /dev/vda\n  scenario: UEFI-3\n  cmd:      sfdisk\n\n\
+             ERROR:\nm\n"
+        ));
+    }
+
+    #[test]
+    fn test_display_json_format() {
+        let _env = EnvGuard::set(&[(config::FORMAT_ENV, "json")]);
+        let err = CheatError::new(
+            "p",
+            Severity::Low,
+            ["a", "b \"quoted\""],
+            "q",
+            "line1\nline2",
+        );
+
+        let rendered = err.to_string();
+        assert!(!rendered.contains('\n'));
+        let json: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+        assert_eq!(json["severity"], "LOW");
+        assert_eq!(json["cheats"], serde_json::json!(["a", "b \"quoted\""]));
+        assert_eq!(json["error"], "line1\nline2");
+        assert!(json["location"].is_null());
+        assert!(json["cause"].is_null());
+        assert_eq!(json["metadata"], serde_json::json!({}));
+    }
+
+    #[test]
+    fn test_display_compact_format() {
+        let _env = EnvGuard::set(&[(config::FORMAT_ENV, "compact")]);
+        let err = CheatError::new(
+            "Both partitions were created",
+            Severity::Critical,
+            ["Check vda1 OR vda2", "Skip verification"],
+            "q",
+            "Expected vda1 AND vda2\ngot: vda",
+        );
+
+        let rendered = err.to_string();
+        assert!(!rendered.contains('\n'));
+        assert_eq!(
+            rendered,
+            "[CRITICAL] protects=\"Both partitions were created\" \
+             error=\"Expected vda1 AND vda2\\ngot: vda\" cheats=2"
+        );
+    }
+
+    #[cfg(feature = "miette")]
+    #[test]
+    fn test_miette_report_shows_cheats() {
+        use miette::Diagnostic;
+
+        let _env = EnvGuard::set(&[(config::FORMAT_ENV, "compact")]);
+        let err = CheatError::new(
+            "p",
+            Severity::Medium,
+            ["Check vda1 OR vda2", "Skip verification"],
+            "Missing partition causes mount failure",
+            "m",
+        );
+        assert_eq!(err.severity(), Some(miette::Severity::Warning));
+
+        let rendered = format!("{:?}", miette::Report::new(err));
+        assert!(rendered.contains("1. Check vda1 OR vda2"), "{rendered}");
+        assert!(rendered.contains("2. Skip verification"));
+        assert!(rendered.contains("Missing partition causes mount failure"));
+    }
+
+    #[cfg(feature = "anyhow")]
+    #[test]
+    fn test_is_cheat_error() {
+        let cheat = anyhow::Error::new(CheatError::new("p", Severity::Low, ["c"], "q", "m"));
+        assert!(is_cheat_error(&cheat));
+        assert!(!is_cheat_error(&anyhow::anyhow!("plain error")));
+    }
+}
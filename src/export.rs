@@ -0,0 +1,638 @@
+//! Rendering of [`TestReport`]s for CI systems.
+
+use std::fmt::Write as _;
+
+use crate::text::{format_cheats, rfc3339};
+use crate::{CheckInfo, CheckResult, StepResult, TestReport};
+
+impl TestReport {
+    /// Render as JUnit XML: one `<testsuite>` per step and one `<testcase>`
+    /// per check. Substeps get sibling `<testsuite>`s after their step's,
+    /// named by their path (`Step 2: Disk / probe`), since JUnit consumers
+    /// reject nested suites.
+    ///
+    /// A `Fail` gets a `<failure>` carrying the expected and actual values
+    /// and whatever cheat documentation was recorded with the check, a `Skip`
+    /// gets `<skipped>`, and a `Warn` passes with its message in
+    /// `<system-out>`. A check with a [`timestamp`](CheckInfo::timestamp)
+    /// carries it as the testcase's RFC 3339 `timestamp` attribute.
+    pub fn to_junit_xml(&self) -> String {
+        let summary = self.summary();
+        let mut out = String::new();
+        // Writing to a String cannot fail.
+        let _ = writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+        let _ = writeln!(
+            out,
+            r#"<testsuites tests="{}" failures="{}" skipped="{}">"#,
+            summary.total, summary.failed, summary.skipped
+        );
+        for step in self.steps() {
+            let suite = format!("Step {}: {}", step.step(), step.name());
+            for (prefix, part) in with_substeps(step) {
+                match prefix.strip_suffix(" / ") {
+                    Some(path) => write_testsuite(&mut out, part, &format!("{suite} / {path}")),
+                    None => write_testsuite(&mut out, part, &suite),
+                }
+            }
+        }
+        let _ = writeln!(out, "</testsuites>");
+        out
+    }
+
+    /// Render as Markdown for a PR comment: a table of per-step counts, then
+    /// a collapsed `<details>` block per failed check with its cheat
+    /// documentation.
+    ///
+    /// Each substep gets its own row under its step's, named by its path
+    /// (`Partition / probe`); the step's row counts its substeps' checks too.
+    pub fn to_markdown(&self) -> String {
+        let summary = self.summary();
+        let mut out = String::from("## Cheat-guarded test report\n\n");
+        out.push_str("| Step | Name | Passed | Failed | Skipped | Warned |\n");
+        out.push_str("|-----:|------|-------:|-------:|--------:|-------:|\n");
+        for step in self.steps() {
+            for (prefix, part) in with_substeps(step) {
+                let counts = part.summary();
+                let name = format!("{} / {}", step.name(), prefix);
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} | {} | {} | {} |",
+                    step.step(),
+                    markdown_cell(name.trim_end_matches(" / ")),
+                    counts.passed,
+                    counts.failed,
+                    counts.skipped,
+                    counts.warned
+                );
+            }
+        }
+        if summary.failed == 0 {
+            let _ = writeln!(out, "\n**All {} checks passed.**", summary.total);
+        } else {
+            let _ = writeln!(
+                out,
+                "\n**{} of {} checks failed.**",
+                summary.failed, summary.total
+            );
+        }
+        for step in self.steps() {
+            for (prefix, part) in with_substeps(step) {
+                for (index, (name, result)) in part.checks().iter().enumerate() {
+                    if let Some((expected, actual)) = result.as_fail() {
+                        let info = part.info(index).cloned().unwrap_or_default();
+                        let name = format!("{}{}", prefix, name);
+                        write_failure_details(&mut out, step, &name, expected, actual, &info);
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// Render as RFC 4180 CSV with one row per check and the columns
+    /// `step,check_name,result,severity,protects`.
+    ///
+    /// `result` is `pass`, `fail`, `skip`, or `warn`; `severity` and
+    /// `protects` are empty when they were not recorded with the check, as
+    /// for passes recorded by [`cheat_check!`](crate::cheat_check). A
+    /// substep's checks are filed under their step, named by their path
+    /// (`probe / vda`).
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("step,check_name,result,severity,protects\r\n");
+        for step in self.steps() {
+            for (prefix, part) in with_substeps(step) {
+                for (index, (name, result)) in part.checks().iter().enumerate() {
+                    let info = part.info(index).cloned().unwrap_or_default();
+                    let result = match result {
+                        CheckResult::Pass(_) => "pass",
+                        CheckResult::Fail { .. } => "fail",
+                        CheckResult::Skip(_) => "skip",
+                        CheckResult::Warn { .. } => "warn",
+                    };
+                    let _ = write!(
+                        out,
+                        "{},{},{},{},{}\r\n",
+                        step.step(),
+                        csv_field(&format!("{}{}", prefix, name)),
+                        result,
+                        info.severity.map_or("", |severity| severity.as_str()),
+                        csv_field(info.protects.as_deref().unwrap_or(""))
+                    );
+                }
+            }
+        }
+        out
+    }
+}
+
+/// `step` and every substep below it, depth first, each with the prefix that
+/// names its checks: `""` for `step` itself, `"probe / "` for its substep
+/// `probe`, `"probe / scan / "` for a substep of that, and so on.
+fn with_substeps(step: &StepResult) -> Vec<(String, &StepResult)> {
+    fn walk<'a>(prefix: &str, step: &'a StepResult, out: &mut Vec<(String, &'a StepResult)>) {
+        for substep in step.substeps() {
+            let prefix = format!("{}{} / ", prefix, substep.name());
+            out.push((prefix.clone(), substep));
+            walk(&prefix, substep, out);
+        }
+    }
+    let mut out = vec![(String::new(), step)];
+    walk("", step, &mut out);
+    out
+}
+
+/// `text` as a CSV field, quoted when it holds a comma, quote, or line break.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn write_failure_details(
+    out: &mut String,
+    step: &StepResult,
+    name: &str,
+    expected: &str,
+    actual: &str,
+    info: &CheckInfo,
+) {
+    let label = info.severity.map_or("FAILED", |severity| severity.as_str());
+    let _ = writeln!(
+        out,
+        "\n<details>\n<summary><b>{}</b> Step {}: {}</summary>\n",
+        label,
+        step.step(),
+        xml_escape(name)
+    );
+    if let Some(protects) = &info.protects {
+        let _ = writeln!(out, "- **Protects:** {}", xml_escape(protects));
+    }
+    if let Some(severity) = info.severity {
+        let _ = writeln!(out, "- **Severity:** {}", severity);
+    }
+    if !info.cheats.is_empty() {
+        let _ = writeln!(out, "- **Cheats:**");
+        for (i, cheat) in info.cheats.iter().enumerate() {
+            let _ = writeln!(out, "  {}. {}", i + 1, xml_escape(cheat));
+        }
+    }
+    if let Some(consequence) = &info.consequence {
+        let _ = writeln!(out, "- **Consequence:** {}", xml_escape(consequence));
+    }
+    let _ = writeln!(out, "\n**Expected:**\n\n{}", fenced(expected));
+    let _ = writeln!(out, "\n**Actual:**\n\n{}", fenced(actual));
+    let _ = writeln!(out, "\n</details>");
+}
+
+/// `text` as a table cell: escaped, with `|` and newlines neutralized.
+fn markdown_cell(text: &str) -> String {
+    xml_escape(text).replace('|', "\\|").replace('\n', " ")
+}
+
+/// `text` in a fenced code block whose fence is longer than any run of
+/// backticks inside it.
+fn fenced(text: &str) -> String {
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{fence}text\n{text}\n{fence}")
+}
+
+/// Write `step`'s own checks as a `<testsuite>` named `suite`.
+fn write_testsuite(out: &mut String, step: &StepResult, suite: &str) {
+    let summary = step.own_summary();
+    let pad = "  ";
+    let _ = writeln!(
+        out,
+        r#"{}<testsuite name="{}" tests="{}" failures="{}" skipped="{}">"#,
+        pad,
+        xml_escape(suite),
+        summary.total,
+        summary.failed,
+        summary.skipped
+    );
+    for (index, (name, result)) in step.checks().iter().enumerate() {
+        let info = step.info(index).cloned().unwrap_or_default();
+        let classname = info.category.as_deref().unwrap_or(suite);
+        let _ = write!(
+            out,
+            r#"{}  <testcase name="{}" classname="{}""#,
+            pad,
+            xml_escape(name),
+            xml_escape(classname)
+        );
+        if let Some(timestamp) = info.timestamp {
+            let _ = write!(out, r#" timestamp="{}""#, rfc3339(timestamp));
+        }
+        match result {
+            CheckResult::Pass(_) => {
+                let _ = writeln!(out, "/>");
+            }
+            CheckResult::Fail { expected, actual } => {
+                let message = format!("expected: {}, actual: {}", expected, actual);
+                let kind = info.severity.map_or("FAIL", |severity| severity.as_str());
+                let _ = writeln!(out, ">");
+                let _ = writeln!(
+                    out,
+                    r#"{}    <failure message="{}" type="{}">{}</failure>"#,
+                    pad,
+                    xml_escape(&message),
+                    kind,
+                    xml_escape(&failure_text(expected, actual, &info))
+                );
+                let _ = writeln!(out, "{}  </testcase>", pad);
+            }
+            CheckResult::Skip(reason) => {
+                let _ = writeln!(out, ">");
+                let _ = writeln!(
+                    out,
+                    r#"{}    <skipped message="{}"/>"#,
+                    pad,
+                    xml_escape(reason)
+                );
+                let _ = writeln!(out, "{}  </testcase>", pad);
+            }
+            CheckResult::Warn { message } => {
+                let _ = writeln!(out, ">");
+                let _ = writeln!(
+                    out,
+                    "{}    <system-out>WARN: {}</system-out>",
+                    pad,
+                    xml_escape(message)
+                );
+                let _ = writeln!(out, "{}  </testcase>", pad);
+            }
+        }
+    }
+    let _ = writeln!(out, "{}</testsuite>", pad);
+}
+
+/// The body of a `<failure>`: expected/actual, then the cheat documentation.
+fn failure_text(expected: &str, actual: &str, info: &CheckInfo) -> String {
+    let mut text = format!("expected: {}\nactual: {}", expected, actual);
+    if let Some(protects) = &info.protects {
+        let _ = write!(text, "\nprotects: {}", protects);
+    }
+    if let Some(severity) = info.severity {
+        let _ = write!(text, "\nseverity: {}", severity);
+    }
+    if !info.cheats.is_empty() {
+        let _ = write!(text, "\ncheats:\n{}", format_cheats(&info.cheats));
+    }
+    if let Some(consequence) = &info.consequence {
+        let _ = write!(text, "\nconsequence: {}", consequence);
+    }
+    text
+}
+
+/// `text` with the five XML special characters escaped, so it is safe both
+/// as element text and inside a double-quoted attribute.
+///
+/// Characters XML 1.0 forbids even as references, such as the ESC of ANSI
+/// colored command output, become U+FFFD so the document still parses.
+fn xml_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            '\0'..='\x1f' | '\u{fffe}' | '\u{ffff}' => out.push(char::REPLACEMENT_CHARACTER),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Severity;
+
+    fn report() -> TestReport {
+        let mut boot = StepResult::new(1, "Boot");
+        boot.add_check("kernel <6.1>", CheckResult::Pass("booted".into()));
+        boot.add_check("esp", CheckResult::Skip("BIOS & legacy boot".into()));
+        let mut disk = StepResult::new(2, "Partition \"Disk\"");
+        disk.add_check_with(
+            "vda2",
+            CheckResult::Fail {
+                expected: "vda2 exists".into(),
+                actual: "<none>".into(),
+            },
+            CheckInfo {
+                category: Some("disk".into()),
+                severity: Some(Severity::Critical),
+                protects: Some("Disk is partitioned".into()),
+                cheats: vec!["Accept any output".into()],
+                consequence: Some("Installation fails".into()),
+                duration: None,
+                timestamp: None,
+                stdout: None,
+                stderr: None,
+            },
+        );
+        disk.add_check(
+            "layout",
+            CheckResult::Warn {
+                message: "extra partition".into(),
+            },
+        );
+
+        let mut report = TestReport::new();
+        report.add_step(boot);
+        report.add_step(disk);
+        report
+    }
+
+    /// Check that `xml` is well formed: tags balance, no `<testsuite>` is
+    /// nested in another, and no text or attribute holds a raw `<` or an `&`
+    /// that does not start an escape.
+    fn assert_well_formed(xml: &str) -> Vec<String> {
+        if let Err(err) = roxmltree::Document::parse(xml) {
+            panic!("invalid XML: {err}\n{xml}");
+        }
+        let mut open = Vec::new();
+        let mut seen = Vec::new();
+        let mut rest = xml.trim_start_matches(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+        while let Some(start) = rest.find('<') {
+            assert_text(&rest[..start]);
+            let end = start + rest[start..].find('>').expect("unclosed tag");
+            let tag = &rest[start + 1..end];
+            assert!(!tag.contains('<'), "raw < inside {tag:?}");
+            assert_text(tag);
+            if let Some(name) = tag.strip_prefix('/') {
+                assert_eq!(open.pop().as_deref(), Some(name), "mismatched </{name}>");
+            } else {
+                let name = tag.split_whitespace().next().unwrap().trim_end_matches('/');
+                // The JUnit schema CI servers validate against has no nested suites.
+                assert!(
+                    name != "testsuite" || !open.iter().any(|tag| tag == "testsuite"),
+                    "nested <testsuite>"
+                );
+                seen.push(name.to_string());
+                if !tag.ends_with('/') {
+                    open.push(name.to_string());
+                }
+            }
+            rest = &rest[end + 1..];
+        }
+        assert_text(rest);
+        assert!(open.is_empty(), "unclosed {open:?}");
+        seen
+    }
+
+    /// `xml` without the testcase timestamps the `timestamps` feature adds.
+    fn unstamped(xml: &str) -> String {
+        let mut parts = xml.split(r#" timestamp=""#);
+        let mut out = parts.next().unwrap_or("").to_string();
+        for part in parts {
+            out.push_str(part.split_once('"').map_or(part, |(_, rest)| rest));
+        }
+        out
+    }
+
+    fn assert_text(text: &str) {
+        for (i, _) in text.match_indices('&') {
+            let entity = &text[i..];
+            assert!(
+                ["&amp;", "&lt;", "&gt;", "&quot;", "&apos;"]
+                    .iter()
+                    .any(|escape| entity.starts_with(escape)),
+                "unescaped & in {text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_junit_xml_is_well_formed() {
+        let xml = report().to_junit_xml();
+        let tags = assert_well_formed(&xml);
+        let count = |name: &str| tags.iter().filter(|tag| *tag == name).count();
+        assert_eq!(count("testsuite"), 2);
+        assert_eq!(count("testcase"), 4);
+        assert_eq!(count("failure"), 1);
+        assert_eq!(count("skipped"), 1);
+        assert!(xml.contains(r#"<testsuites tests="4" failures="1" skipped="1">"#));
+    }
+
+    #[test]
+    fn test_junit_xml_failure_carries_cheat_documentation() {
+        let xml = unstamped(&report().to_junit_xml());
+        assert!(xml.contains(
+            r#"<testsuite name="Step 2: Partition &quot;Disk&quot;" tests="2" failures="1" skipped="0">"#
+        ));
+        assert!(xml.contains(
+            "<testcase name=\"vda2\" classname=\"disk\">\n      \
+             <failure message=\"expected: vda2 exists, actual: &lt;none&gt;\" type=\"CRITICAL\">\
+             expected: vda2 exists\nactual: &lt;none&gt;\nprotects: Disk is partitioned\n\
+             severity: CRITICAL\ncheats:\n  1. Accept any output\n\
+             consequence: Installation fails</failure>"
+        ));
+        assert!(xml.contains(r#"<testcase name="kernel &lt;6.1&gt;" classname="Step 1: Boot"/>"#));
+        assert!(xml.contains(r#"<skipped message="BIOS &amp; legacy boot"/>"#));
+        assert!(xml.contains("<system-out>WARN: extra partition</system-out>"));
+    }
+
+    #[test]
+    fn test_markdown_has_a_row_per_step_and_details_per_failure() {
+        let mut report = report();
+        let mut mount = StepResult::new(3, "Mount | fstab");
+        mount.add_check(
+            "root",
+            CheckResult::Fail {
+                expected: "```/```".into(),
+                actual: "nothing\nmounted".into(),
+            },
+        );
+        report.add_step(mount);
+        let markdown = report.to_markdown();
+
+        let rows: Vec<&str> = markdown
+            .lines()
+            .filter(|line| line.starts_with("| ") && !line.starts_with("| Step"))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                "| 1 | Boot | 1 | 0 | 1 | 0 |",
+                "| 2 | Partition &quot;Disk&quot; | 0 | 1 | 0 | 1 |",
+                "| 3 | Mount \\| fstab | 0 | 1 | 0 | 0 |",
+            ]
+        );
+        assert!(markdown.contains("\n**2 of 5 checks failed.**\n"));
+        assert_eq!(markdown.matches("<details>").count(), 2);
+        assert_eq!(markdown.matches("</details>").count(), 2);
+        assert!(markdown.contains(
+            "<summary><b>CRITICAL</b> Step 2: vda2</summary>\n\n\
+             - **Protects:** Disk is partitioned\n\
+             - **Severity:** CRITICAL\n\
+             - **Cheats:**\n  1. Accept any output\n\
+             - **Consequence:** Installation fails\n\n\
+             **Expected:**\n\n```text\nvda2 exists\n```\n\n\
+             **Actual:**\n\n```text\n<none>\n```\n"
+        ));
+        assert!(markdown.contains("<summary><b>FAILED</b> Step 3: root</summary>"));
+        assert!(markdown.contains("````text\n```/```\n````"));
+    }
+
+    #[test]
+    fn test_substeps_render_as_paths() {
+        let mut disk = StepResult::new(2, "Disk");
+        disk.add_check("table", CheckResult::Pass("gpt".into()));
+        let mut probe = StepResult::new(2, "probe");
+        probe.add_check(
+            "vda",
+            CheckResult::Fail {
+                expected: "vda".into(),
+                actual: "none".into(),
+            },
+        );
+        disk.add_substep(probe);
+        let mut report = TestReport::new();
+        report.add_step(disk);
+
+        let xml = unstamped(&report.to_junit_xml());
+        let tags = assert_well_formed(&xml);
+        assert_eq!(tags.iter().filter(|tag| *tag == "testsuite").count(), 2);
+        assert!(xml.contains(
+            "  <testsuite name=\"Step 2: Disk\" tests=\"1\" failures=\"0\" skipped=\"0\">\n    \
+             <testcase name=\"table\" classname=\"Step 2: Disk\"/>\n  \
+             </testsuite>\n  \
+             <testsuite name=\"Step 2: Disk / probe\" tests=\"1\" failures=\"1\" skipped=\"0\">\n    \
+             <testcase name=\"vda\" classname=\"Step 2: Disk / probe\">\n"
+        ));
+        assert!(xml.contains(r#"<testsuites tests="2" failures="1" skipped="0">"#));
+
+        let markdown = report.to_markdown();
+        assert!(markdown
+            .contains("| 2 | Disk | 1 | 1 | 0 | 0 |\n| 2 | Disk / probe | 0 | 1 | 0 | 0 |\n"));
+        assert!(markdown.contains("<summary><b>FAILED</b> Step 2: probe / vda</summary>"));
+        assert!(report.to_csv().contains("\r\n2,probe / vda,fail,,\r\n"));
+    }
+
+    #[test]
+    fn test_junit_substeps_are_sibling_suites() {
+        let mut scan = StepResult::new(2, "scan");
+        scan.add_check("vdb", CheckResult::Skip("no second disk".into()));
+        let mut probe = StepResult::new(2, "probe");
+        probe.add_check("vda", CheckResult::Pass("vda".into()));
+        probe.add_substep(scan);
+        let mut disk = StepResult::new(2, "Disk");
+        disk.add_substep(probe);
+        let mut report = TestReport::new();
+        report.add_step(disk);
+
+        let xml = unstamped(&report.to_junit_xml());
+        assert_well_formed(&xml);
+        let suites: Vec<&str> = xml
+            .lines()
+            .filter_map(|line| line.strip_prefix("  <testsuite name=\""))
+            .map(|rest| &rest[..rest.find('"').unwrap()])
+            .collect();
+        assert_eq!(
+            suites,
+            [
+                "Step 2: Disk",
+                "Step 2: Disk / probe",
+                "Step 2: Disk / probe / scan"
+            ]
+        );
+        assert!(!xml.contains("    <testsuite"));
+    }
+
+    #[test]
+    fn test_junit_xml_replaces_control_characters() {
+        let mut console = StepResult::new(3, "Console");
+        console.add_check(
+            "login\u{1}",
+            CheckResult::Fail {
+                expected: "login prompt".into(),
+                actual: "\x1b[31mlogin failed\x1b[0m\0\u{ffff}\tretry\r\n".into(),
+            },
+        );
+        let mut report = TestReport::new();
+        report.add_step(console);
+
+        let xml = report.to_junit_xml();
+        assert_well_formed(&xml);
+        assert!(xml.contains("<testcase name=\"login\u{fffd}\""), "{xml}");
+        assert!(
+            xml.contains("\u{fffd}[31mlogin failed\u{fffd}[0m\u{fffd}\u{fffd}\tretry\r\n"),
+            "{xml}"
+        );
+        assert!(!xml.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_junit_xml_testcase_timestamp() {
+        let mut boot = StepResult::new(1, "Boot");
+        boot.add_check_with(
+            "kernel",
+            CheckResult::Pass("booted".into()),
+            CheckInfo {
+                timestamp: Some(std::time::UNIX_EPOCH + std::time::Duration::from_secs(86_400)),
+                ..CheckInfo::default()
+            },
+        );
+        let mut report = TestReport::new();
+        report.add_step(boot);
+        let xml = report.to_junit_xml();
+        assert_well_formed(&xml);
+        assert!(xml.contains(
+            r#"<testcase name="kernel" classname="Step 1: Boot" timestamp="1970-01-02T00:00:00.000Z"/>"#
+        ));
+    }
+
+    #[test]
+    fn test_markdown_all_passed() {
+        let mut step = StepResult::new(1, "Boot");
+        step.add_check("kernel", CheckResult::Pass("booted".into()));
+        let mut report = TestReport::new();
+        report.add_step(step);
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("**All 1 checks passed.**"));
+        assert!(!markdown.contains("<details>"));
+    }
+
+    #[test]
+    fn test_csv_rows_and_quoting() {
+        let mut report = report();
+        let mut mount = StepResult::new(3, "Mount");
+        mount.add_check_with(
+            "root, then /boot",
+            CheckResult::Pass("mounted".into()),
+            CheckInfo {
+                protects: Some("System \"boots\"".into()),
+                ..CheckInfo::default()
+            },
+        );
+        report.add_step(mount);
+
+        let csv = report.to_csv();
+        let rows: Vec<&str> = csv.split_terminator("\r\n").collect();
+        assert_eq!(
+            rows,
+            [
+                "step,check_name,result,severity,protects",
+                "1,kernel <6.1>,pass,,",
+                "1,esp,skip,,",
+                "2,vda2,fail,CRITICAL,Disk is partitioned",
+                "2,layout,warn,,",
+                "3,\"root, then /boot\",pass,,\"System \"\"boots\"\"\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_xml_escape() {
+        assert_eq!(
+            xml_escape(r#"<a href="x">&'"#),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&apos;"
+        );
+    }
+}
@@ -0,0 +1,218 @@
+//! Extension traits attaching cheat documentation to `Result` and `Option`.
+
+use std::fmt::Display;
+
+use crate::output::report_failure;
+use crate::severity::IntoSeverity;
+use crate::CheatError;
+
+/// Attach cheat documentation to the error of a `Result`, like
+/// `anyhow::Context::context`.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use leviso_cheat_guard::CheatContext;
+///
+/// let table = read_partition_table(&disk).cheat_context(
+///     "Disk is partitioned correctly",
+///     "CRITICAL",
+///     &["Treat an unreadable table as empty"],
+///     "Installation proceeds on a broken disk",
+///     "Could not read partition table",
+/// )?;
+/// ```
+pub trait CheatContext<T> {
+    /// On `Err`, wrap the error in a [`CheatError`] whose ERROR section is
+    /// `msg` followed by the original error. The original stays in the
+    /// `source()` chain. `Ok` passes through untouched.
+    ///
+    /// An unknown severity name wraps the original error in the parse error
+    /// instead, so the real failure is still the root cause.
+    fn cheat_context<M: Display>(
+        self,
+        protects: &str,
+        severity: impl IntoSeverity,
+        cheats: &[&str],
+        consequence: &str,
+        msg: M,
+    ) -> anyhow::Result<T>;
+}
+
+impl<T, E> CheatContext<T> for Result<T, E>
+where
+    E: Into<anyhow::Error>,
+{
+    fn cheat_context<M: Display>(
+        self,
+        protects: &str,
+        severity: impl IntoSeverity,
+        cheats: &[&str],
+        consequence: &str,
+        msg: M,
+    ) -> anyhow::Result<T> {
+        match self {
+            Ok(value) => Ok(value),
+            Err(err) => {
+                let err: anyhow::Error = err.into();
+                let severity = match severity.into_severity() {
+                    Ok(severity) => severity,
+                    Err(parse_err) => return Err(err.context(parse_err)),
+                };
+                let cheat = CheatError::new(
+                    protects,
+                    severity,
+                    cheats.iter().copied(),
+                    consequence,
+                    format!("{}: {:#}", msg, err),
+                );
+                report_failure(&cheat);
+                Err(err.context(cheat))
+            }
+        }
+    }
+}
+
+/// Turn a missing `Option` value into a cheat-guarded failure.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use leviso_cheat_guard::CheatOption;
+///
+/// let uuid = capture.get(1).cheat_ok_or(
+///     "Root filesystem is identified by UUID",
+///     "HIGH",
+///     &["Default to /dev/vda2 when the UUID is missing"],
+///     "System mounts the wrong root after a disk reorder",
+///     "blkid output had no UUID",
+/// )?;
+/// ```
+pub trait CheatOption<T> {
+    /// `Ok(v)` for `Some(v)`; for `None`, an error carrying a [`CheatError`]
+    /// with `msg` as its ERROR section.
+    fn cheat_ok_or<M: Display>(
+        self,
+        protects: &str,
+        severity: impl IntoSeverity,
+        cheats: &[&str],
+        consequence: &str,
+        msg: M,
+    ) -> anyhow::Result<T>;
+}
+
+impl<T> CheatOption<T> for Option<T> {
+    fn cheat_ok_or<M: Display>(
+        self,
+        protects: &str,
+        severity: impl IntoSeverity,
+        cheats: &[&str],
+        consequence: &str,
+        msg: M,
+    ) -> anyhow::Result<T> {
+        match self {
+            Some(value) => Ok(value),
+            None => {
+                let severity = severity.into_severity()?;
+                let cheat = CheatError::new(
+                    protects,
+                    severity,
+                    cheats.iter().copied(),
+                    consequence,
+                    msg.to_string(),
+                );
+                report_failure(&cheat);
+                Err(cheat.into())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Severity;
+
+    fn read_table(ok: bool) -> Result<u32, std::io::Error> {
+        if ok {
+            Ok(2)
+        } else {
+            Err(std::io::Error::other("device /dev/vda not found"))
+        }
+    }
+
+    #[test]
+    fn test_cheat_context_ok_passes_through() {
+        let value = read_table(true)
+            .cheat_context("p", "LOW", &["c"], "q", "unused")
+            .unwrap();
+        assert_eq!(value, 2);
+    }
+
+    #[test]
+    fn test_cheat_context_wraps_err_in_banner() {
+        let _globals = crate::test_util::lock_globals();
+        let err = read_table(false)
+            .cheat_context(
+                "Disk is partitioned",
+                Severity::Critical,
+                &["Treat an unreadable table as empty"],
+                "Broken disk",
+                "Could not read partition table",
+            )
+            .unwrap_err();
+
+        let msg = err.to_string();
+        assert!(msg.contains("CHEAT-GUARDED FAILURE"));
+        assert!(msg.contains("ERROR:\nCould not read partition table: device /dev/vda not found\n"));
+
+        let cheat = CheatError::from_anyhow(&err).unwrap();
+        assert_eq!(cheat.severity, Severity::Critical);
+        let io = err.root_cause().downcast_ref::<std::io::Error>().unwrap();
+        assert_eq!(io.to_string(), "device /dev/vda not found");
+    }
+
+    #[test]
+    fn test_cheat_context_unknown_severity_keeps_err() {
+        let err = read_table(false)
+            .cheat_context(
+                "p",
+                "CRTICAL",
+                &["c"],
+                "q",
+                "Could not read partition table",
+            )
+            .unwrap_err();
+
+        assert!(err.downcast_ref::<crate::ParseSeverityError>().is_some());
+        assert!(CheatError::from_anyhow(&err).is_none());
+        let io = err.root_cause().downcast_ref::<std::io::Error>().unwrap();
+        assert_eq!(io.to_string(), "device /dev/vda not found");
+    }
+
+    #[test]
+    fn test_cheat_ok_or_some() {
+        let value = Some("1234-ABCD")
+            .cheat_ok_or("p", "HIGH", &["c"], "q", "unused")
+            .unwrap();
+        assert_eq!(value, "1234-ABCD");
+    }
+
+    #[test]
+    fn test_cheat_ok_or_none() {
+        let err = None::<&str>
+            .cheat_ok_or(
+                "Root is identified by UUID",
+                "HIGH",
+                &["Default to /dev/vda2"],
+                "Wrong root mounted",
+                "blkid output had no UUID",
+            )
+            .unwrap_err();
+
+        let cheat = CheatError::from_anyhow(&err).unwrap();
+        assert_eq!(cheat.severity, Severity::High);
+        assert_eq!(cheat.cheats, ["Default to /dev/vda2"]);
+        assert_eq!(cheat.message, "blkid output had no UUID");
+    }
+}
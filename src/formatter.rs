@@ -0,0 +1,201 @@
+//! Pluggable rendering of [`CheatError`] messages.
+
+use std::sync::{Arc, RwLock};
+
+use crate::config::{self, OutputFormat};
+use crate::error::failure_title;
+use crate::CheatError;
+
+/// Renders a [`CheatError`] as the text its `Display` impl produces.
+///
+/// Install one with [`set_cheat_formatter`] to change how bail errors and
+/// downgraded bails look. The active formatter is consulted each time an
+/// error is displayed, not when it is created.
+pub trait CheatFormatter {
+    fn render(&self, err: &CheatError) -> String;
+}
+
+/// The multi-line `CHEAT-GUARDED FAILURE` banner.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HumanFormatter;
+
+impl CheatFormatter for HumanFormatter {
+    fn render(&self, err: &CheatError) -> String {
+        err.banner(&failure_title()).to_string()
+    }
+}
+
+/// A single-line JSON object; see [`CheatError::to_json`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonFormatter;
+
+impl CheatFormatter for JsonFormatter {
+    fn render(&self, err: &CheatError) -> String {
+        err.to_json()
+    }
+}
+
+/// A single `[SEVERITY] protects="..."` line; see [`CheatError::to_compact`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CompactFormatter;
+
+impl CheatFormatter for CompactFormatter {
+    fn render(&self, err: &CheatError) -> String {
+        err.to_compact()
+    }
+}
+
+type SharedFormatter = Arc<dyn CheatFormatter + Send + Sync>;
+
+/// Set by [`set_cheat_formatter`]; `None` means pick by `CHEAT_GUARD_FORMAT`.
+static FORMATTER: RwLock<Option<SharedFormatter>> = RwLock::new(None);
+
+/// Render every [`CheatError`] with `formatter`, overriding `CHEAT_GUARD_FORMAT`.
+pub fn set_cheat_formatter(formatter: Box<dyn CheatFormatter + Send + Sync>) {
+    *FORMATTER
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(formatter.into());
+}
+
+/// Undo [`set_cheat_formatter`], going back to the `CHEAT_GUARD_FORMAT` choice.
+pub fn reset_cheat_formatter() {
+    *FORMATTER
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+}
+
+/// Set by [`set_cheat_title`]; `None` means `CHEAT-GUARDED FAILURE`.
+static TITLE: RwLock<Option<String>> = RwLock::new(None);
+
+/// Title every failure banner with `title` instead of `CHEAT-GUARDED FAILURE`,
+/// unless the error sets its own with `title = ...`.
+pub fn set_cheat_title(title: impl Into<String>) {
+    *TITLE
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(title.into());
+}
+
+/// Undo [`set_cheat_title`], going back to `CHEAT-GUARDED FAILURE`.
+pub fn reset_cheat_title() {
+    *TITLE
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+}
+
+/// The title set with [`set_cheat_title`], if any.
+pub(crate) fn title() -> Option<String> {
+    TITLE
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+/// Render `err` with the active formatter.
+pub(crate) fn render(err: &CheatError) -> String {
+    render_styled(err, false)
+}
+
+/// [`render`], with the default human banner colored and linked as
+/// configured when `styled`, for printing it to stderr.
+pub(crate) fn render_styled(err: &CheatError, styled: bool) -> String {
+    // Clone out of the lock so a formatter may itself display a CheatError.
+    let custom = FORMATTER
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone();
+    match custom {
+        Some(formatter) => formatter.render(err),
+        None => match config::output_format() {
+            OutputFormat::Human => err.styled_banner(&failure_title(), styled).to_string(),
+            OutputFormat::Json => JsonFormatter.render(err),
+            OutputFormat::Compact => CompactFormatter.render(err),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::EnvGuard;
+    use crate::Severity;
+
+    struct Tagged;
+
+    impl CheatFormatter for Tagged {
+        fn render(&self, err: &CheatError) -> String {
+            format!("{}|{}|{}", err.severity, err.protects, err.cheats.join(","))
+        }
+    }
+
+    #[test]
+    fn test_custom_formatter_is_used() {
+        let _env = EnvGuard::set(&[(config::FORMAT_ENV, "json")]);
+        let err = CheatError::new("p", Severity::High, ["a", "b"], "q", "m");
+
+        set_cheat_formatter(Box::new(Tagged));
+        let custom = err.to_string();
+        reset_cheat_formatter();
+
+        assert_eq!(custom, "HIGH|p|a,b");
+        assert_eq!(err.to_string(), err.to_json());
+    }
+
+    #[cfg(feature = "anyhow")]
+    #[test]
+    fn test_custom_formatter_renders_bails() {
+        let _env = EnvGuard::unset(&[config::FORMAT_ENV, config::MIN_SEVERITY_ENV]);
+        set_cheat_formatter(Box::new(Tagged));
+        let result: anyhow::Result<()> = (|| {
+            crate::cheat_bail!(
+                protects = "Disk is partitioned",
+                severity = "CRITICAL",
+                cheats = ["Skip it"],
+                consequence = "q",
+                "boom"
+            );
+            Ok(())
+        })();
+        let message = result.unwrap_err().to_string();
+        reset_cheat_formatter();
+
+        assert_eq!(message, "CRITICAL|Disk is partitioned|Skip it");
+    }
+
+    #[test]
+    fn test_set_cheat_title() {
+        let _env = EnvGuard::unset(&[config::FORMAT_ENV]);
+        let err = CheatError::new("p", Severity::Low, ["c"], "q", "m");
+        let default = err.to_string();
+
+        set_cheat_title("SECURITY GUARD FAILURE");
+        let custom = err.to_string();
+        let own = err.clone().with_title("BOOT GUARD FAILURE").to_string();
+        let stable = err.render_stable();
+        let own_stable = err.clone().with_title("BOOT GUARD FAILURE").render_stable();
+        reset_cheat_title();
+
+        assert_eq!(stable, err.render_stable());
+        assert!(stable.contains("\n=== CHEAT-GUARDED FAILURE ===\n"));
+        assert!(own_stable.contains("\n=== BOOT GUARD FAILURE ===\n"));
+
+        assert!(default.contains("\n=== CHEAT-GUARDED FAILURE ===\n"));
+        assert_eq!(
+            custom,
+            default.replace("CHEAT-GUARDED FAILURE", "SECURITY GUARD FAILURE")
+        );
+        assert!(own.contains("\n=== BOOT GUARD FAILURE ===\n"));
+        assert_eq!(err.to_string(), default);
+    }
+
+    #[test]
+    fn test_builtin_formatters() {
+        let _env = EnvGuard::unset(&[config::FORMAT_ENV]);
+        let err = CheatError::new("p", Severity::Low, ["c"], "q", "m");
+        assert_eq!(
+            HumanFormatter.render(&err),
+            err.banner(crate::error::FAILURE_TITLE).to_string()
+        );
+        assert_eq!(JsonFormatter.render(&err), err.to_json());
+        assert_eq!(CompactFormatter.render(&err), err.to_compact());
+    }
+}
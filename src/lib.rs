@@ -17,6 +17,78 @@
 //! - [`cheat_bail!`] - Like `bail!()` but with cheat documentation
 //! - [`cheat_ensure!`] - Like `ensure!()` but with cheat documentation
 //! - [`cheat_check!`] - Check a condition and add to StepResult with cheat metadata
+//! - [`cheat_result!`] - Like `cheat_ensure!`, but evaluates to the `Result` instead of returning it
+//! - [`cheat_expect!`] - Like `Result::expect` but bails with cheat documentation
+//! - [`cheat_assert_eq!`] - Like `assert_eq!()` but bails with cheat documentation
+//! - [`cheat_assert_approx!`] - Bail with cheat documentation unless a float is within a relative tolerance
+//! - [`cheat_assert_within!`] - Bail with cheat documentation unless a value lies in an inclusive range
+//! - [`cheat_contains!`] - Bail with cheat documentation unless output contains a substring
+//! - [`cheat_exec_contains!`] - Run a command on a [`ConsoleExec`] and bail with cheat documentation unless its output contains a substring
+//! - `cheat_matches!` - Bail with cheat documentation unless text matches a regex (`regex` feature)
+//! - [`cheat_group!`] - Run several `cheat_check!`s without short-circuiting and count the failures
+//! - [`cheat_scope!`] - Open a [`CheatScope`] that prints how many of its checks passed when dropped
+//! - [`cheat_skip!`] - Record an inapplicable check on a StepResult, with cheat metadata as context
+//! - [`cheat_retry!`] - Retry a fallible operation, bailing with cheat documentation on exhaustion
+//! - [`cheat_timeout!`] - Run an operation with a deadline, bailing with cheat documentation if it is missed
+//! - [`cheat_warn!`] - Print cheat documentation for a suspicious but non-fatal condition
+//! - [`cheat_panic!`] - Panic with cheat documentation where there is no `Result` to bail into
+//! - [`cheat_todo!`] - Stub a check that is not written yet without losing its cheat documentation
+//! - [`cheat_profile!`] - Declare shared cheat documentation once, for `profile = NAME` in bail macros
+//! - [`cheat_doc!`] - Document a cheat for the catalog where there is no check to guard
+//! - [`severity!`] - Turn a severity name literal into a [`Severity`], rejecting unknown names at compile time
+//!
+//! [`prelude`] re-exports the macros and the common types for a single glob
+//! import.
+//!
+//! ## Environment
+//!
+//! - `CHEAT_GUARD_MIN_SEVERITY` - Severity threshold for [`cheat_bail!`] and
+//!   [`cheat_ensure!`]. Failures below it print a `DOWNGRADED:` banner to
+//!   stderr and execution continues instead of bailing. Unset or unparseable
+//!   means everything bails. [`cheat_check!`] is not affected.
+//!
+//! - `GITHUB_ACTIONS` - When `true`, failing [`cheat_check!`] calls also print
+//!   a GitHub Actions `::error file=...,line=...::` annotation.
+//! - `CHEAT_GUARD_TRACING_ONLY` - With the `tracing` feature, `1` suppresses
+//!   the stderr banners; failures are reported only as `tracing` events.
+//! - `CHEAT_GUARD_MAX_ACTUAL` - Byte limit for a failing [`cheat_check!`]'s
+//!   `actual`, default 1024. Longer values end in `… (N bytes truncated)`.
+//! - `CHEAT_GUARD_STRICT_TODO` - `1` makes [`cheat_todo!`] stubs bail instead
+//!   of printing a `CHEAT-GUARDED TODO` banner.
+//! - `CHEAT_GUARD_STRICT` - `1` makes [`cheat_warn!`] bail instead of printing
+//!   a warning, and ignores `CHEAT_GUARD_MIN_SEVERITY`, for release gating.
+//! - `CHEAT_GUARD_FORMAT` - `json` makes bail errors display as a single-line
+//!   JSON object (see [`CheatError::to_json`]), and `compact` as a single
+//!   `[SEVERITY] protects="..."` line (see [`CheatError::to_compact`]). Unset
+//!   or `human` keeps the banner.
+//! - `CHEAT_GUARD_WIDTH` - Border width of all banners, clamped to 20..=200.
+//!   Unset keeps 70 for bail banners and 60 for [`cheat_check!`] banners;
+//!   [`set_cheat_width`] overrides it from code.
+//! - `CHEAT_GUARD_COLOR` - `always`, `never`, or `auto` (default). `auto`
+//!   colors the severity and borders only when stderr is a terminal and
+//!   `NO_COLOR` is unset. Only banners printed to stderr are colored, never
+//!   a returned error's text or a [`set_cheat_writer`] sink.
+//! - `CHEAT_GUARD_LINKS` - `always`, `never`, or `auto` (default). Whether a
+//!   `doc_url` prints as a clickable OSC 8 hyperlink; `auto` follows
+//!   `CHEAT_GUARD_COLOR`.
+//! - `CHEAT_GUARD_STREAM` - `jsonl` makes every [`cheat_check!`], passing or
+//!   not, write one `{"name","result","severity","protects"}` JSON object per
+//!   line to the banner writer, in place of its `checking:` line and banner.
+//!
+//! - `CHEAT_GUARD_DEDUP_MS` - When above 0, a printed banner identical to one
+//!   shown within that many milliseconds (same protects, message, and
+//!   location) is held back, and the next banner that prints is preceded by
+//!   `(suppressed N duplicate cheat banners)`. Errors still bail as usual.
+//! - `CHEAT_GUARD_ESCALATE_AFTER` - When above 0, once one check (same
+//!   protects and location) has printed that many banners in a run, later
+//!   ones are printed one severity higher, after an `ESCALATED:` tag.
+//! - `CHEAT_GUARD_SLOW_MS` - A [`cheat_check!`] condition taking longer than
+//!   this many milliseconds (default 1000) gets a `(took 1.2s)` line after
+//!   its `checking:` line.
+//!
+//! Printed banners go to stderr unless redirected with [`set_cheat_writer`].
+//! [`set_cheat_formatter`] replaces the `CHEAT_GUARD_FORMAT` choice with any
+//! [`CheatFormatter`].
 //!
 //! ## Example
 //!
@@ -39,22 +111,201 @@
 //!     Ok(())
 //! }
 //! ```
+//!
+//! ## `no_std`
+//!
+//! With `default-features = false` the crate builds on `core` + `alloc`.
+//! Only [`CheatError`], [`CheatMeta`], and [`Severity`] remain: the macros
+//! and printed banners need the `std` feature. Render a banner with
+//! [`CheatError::write_banner`] into any `core::fmt::Write`.
+//!
+//! `features = ["std"]` without `anyhow` keeps the macros but drops the
+//! `anyhow` dependency: the bail macros then `return Err(CheatError)`, for
+//! functions returning `Result<T, CheatError>`. An unknown severity name,
+//! which has no `CheatError` to travel in, panics instead of bailing.
+//!
+//! `features = ["eyre"]`, also without `anyhow`, makes them return
+//! `eyre::Report` instead, for functions returning `eyre::Result`. The banner
+//! is the same; the `CheatError` is recovered with `downcast_ref`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "catalog")]
+mod catalog;
+#[cfg(feature = "std")]
+mod check;
+#[cfg(feature = "std")]
+mod config;
+#[cfg(feature = "std")]
+mod console;
+mod error;
+#[cfg(feature = "std")]
+mod export;
+#[cfg(feature = "anyhow")]
+mod ext;
+#[cfg(feature = "std")]
+mod formatter;
+mod meta;
+#[cfg(feature = "std")]
+mod ops;
+#[cfg(feature = "std")]
+mod output;
+pub mod prelude;
+#[cfg(feature = "std")]
+mod redact;
+#[cfg(feature = "std")]
+mod report;
+#[cfg(feature = "std")]
+mod scope;
+mod severity;
+#[cfg(feature = "std")]
+mod stats;
+#[cfg(feature = "std")]
+mod step;
+#[cfg(all(test, feature = "std"))]
+mod test_util;
+mod text;
 
 // Re-export proc-macros for convenience
 pub use leviso_cheat_test::{cheat_aware, cheat_canary, cheat_reviewed};
 
+#[cfg(feature = "catalog")]
+pub use catalog::{
+    catalog_json_string, cheat_catalog, dump_catalog_json, validate_catalog, CheatEntry,
+};
+#[cfg(feature = "std")]
+pub use check::CheckResult;
+#[cfg(feature = "std")]
+pub use config::{reset_cheat_width, set_cheat_width};
+#[cfg(feature = "std")]
+pub use console::ConsoleExec;
+#[cfg(feature = "anyhow")]
+pub use error::is_cheat_error;
+pub use error::{CheatError, CheatErrorBuilder, Location};
+#[cfg(feature = "anyhow")]
+pub use ext::{CheatContext, CheatOption};
+#[cfg(feature = "std")]
+pub use formatter::{
+    reset_cheat_formatter, reset_cheat_title, set_cheat_formatter, set_cheat_title, CheatFormatter,
+    CompactFormatter, HumanFormatter, JsonFormatter,
+};
+pub use meta::{CheatMeta, CheatMetaBuilder};
+#[cfg(feature = "std")]
+pub use output::{reset_cheat_writer, set_cheat_writer};
+#[cfg(feature = "regex")]
+pub use redact::redact_tokens;
+#[cfg(feature = "std")]
+pub use redact::{reset_cheat_redactor, set_cheat_redactor};
+#[cfg(feature = "std")]
+pub use report::TestReport;
+#[cfg(feature = "std")]
+pub use scope::CheatScope;
+pub use severity::{exit_code_for, IntoSeverity, ParseSeverityError, Severity};
+#[cfg(feature = "std")]
+pub use stats::{cheat_failure_counts, print_cheat_summary, reset_cheat_counts};
+#[cfg(feature = "std")]
+pub use step::{CheckInfo, StepResult, StepSummary};
+
+/// Support items for macro expansions. Not public API.
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::check::{CheckField, DebugCheckField, StrCheckField};
+    pub use crate::console::ConsoleExecExt;
+    #[cfg(feature = "regex")]
+    pub use crate::error::pattern_error;
+    pub use crate::error::{bail_error, severity_error, BailError};
+    pub use crate::ops::{
+        approx_error, retry, run_with_timeout, Ensure, APPROX_CHEAT, RETRY_CHEAT, TIMEOUT_CHEAT,
+    };
+    pub use crate::output::{
+        announce_check, announce_duration, report_bail_failure, report_check_failure,
+        report_failure, report_todo, report_warning, stream_check, streaming,
+    };
+    pub use crate::redact::redact;
+    pub use crate::severity::IntoSeverity;
+    pub use crate::step::check_timestamp;
+    pub use crate::text::{cheat_list, format_cheats, preview, truncate_actual};
+
+    /// What an empty `cheats = []` expands to, so it has a concrete type.
+    pub const NO_CHEATS: &[&str] = &[];
+    #[cfg(feature = "catalog")]
+    pub use inventory;
+    #[cfg(feature = "regex")]
+    pub use regex::Regex;
+}
+
 /// Bail with cheat-aware error message.
 ///
 /// Like `anyhow::bail!()` but includes cheat documentation in the error.
+/// The error is a [`CheatError`], so the fields can be recovered with
+/// `err.downcast_ref::<CheatError>()`. Without the `anyhow` feature it is
+/// wrapped in an `eyre::Report` under `eyre`, and returned as is otherwise.
+///
+/// The message after the fields is passed to `format!` as is, so positional
+/// arguments, named arguments (`"{name}", name = x`), and inline captures of
+/// variables in scope (`"{partition}"`) all work. Named arguments may reuse
+/// an option's name, such as `source`, once the message string has started.
+///
+/// A string `severity` is parsed with [`Severity::from_str`](std::str::FromStr);
+/// an unknown name bails with a [`ParseSeverityError`] instead of the banner.
+///
+/// The banner includes a `LOCATION:` line with the caller's `file:line:column`.
+///
+/// If the severity is below `CHEAT_GUARD_MIN_SEVERITY`, the banner is printed
+/// to stderr prefixed with `DOWNGRADED:` and execution continues after the
+/// macro instead of returning.
 ///
 /// # Arguments
 ///
 /// - `protects` - What user scenario this check protects
-/// - `severity` - "CRITICAL", "HIGH", "MEDIUM", or "LOW"
-/// - `cheats` - Array of ways this check could be cheated
+/// - `severity` - A [`Severity`], or one of "CRITICAL", "HIGH", "MEDIUM", "LOW"
+/// - `cheats` - Ways this check could be cheated: an inline array, or any
+///   expression iterating `AsRef<str>` items such as a `const &[&str]` or a
+///   `Vec<String>`. An empty `[]` is allowed while scaffolding: the banner
+///   reads `(none documented)` and a one-time reminder is printed
 /// - `consequence` - What users experience if cheated
+/// - `source` - Optional underlying error, kept as the error's `source()` and
+///   printed in a `CAUSE:` section
+/// - `remedy` - Optional fix to suggest, printed in a `SUGGESTED FIX:` section
+///   after the consequence
+/// - `doc_url` - Optional link to the check's documentation, printed as a
+///   `SEE:` line and kept as `doc_url` in the JSON format
+/// - `title` - Optional banner title replacing `CHEAT-GUARDED FAILURE`, such
+///   as `"SECURITY GUARD FAILURE"`; [`set_cheat_title`] changes the default
+/// - `meta = { "key" => value, ... }` - Optional extra context such as the
+///   device or command, printed as aligned `key: value` lines in a
+///   `METADATA:` section and kept as the JSON `metadata` object. Values are
+///   displayed with `{}`
 /// - Format string and args for the actual error message
 ///
+/// `protects` and `consequence` take any `Display` expression (a literal, a
+/// `String` or `&str` variable, a `format!(...)`) and are only borrowed.
+///
+/// The named fields may come in any order, each at most once, as long as the
+/// format string comes last. Leaving out a required field is a compile error
+/// naming it:
+///
+/// ```compile_fail
+/// # use leviso_cheat_guard::cheat_bail;
+/// fn check() -> anyhow::Result<()> {
+///     // error: missing `consequence = ...`
+///     cheat_bail!(
+///         severity = "CRITICAL",
+///         protects = "Disk partitioning works",
+///         cheats = ["Return Ok without checking"],
+///         "Partition not found"
+///     );
+/// }
+/// ```
+///
+/// Instead of the four documentation fields, `meta = ...` takes a
+/// [`CheatMeta`] defined once and shared between call sites, and
+/// `profile = NAME` takes one declared with [`cheat_profile!`]. Either may be
+/// followed by `extra_cheats = [...]` to append call-specific cheats.
+///
 /// # Example
 ///
 /// ```rust,ignore
@@ -68,51 +319,504 @@ pub use leviso_cheat_test::{cheat_aware, cheat_canary, cheat_reviewed};
 ///     );
 /// }
 /// ```
+#[cfg(feature = "std")]
 #[macro_export]
 macro_rules! cheat_bail {
+    (@raise (bail) [$($opts:tt)*], $err:expr) => {{
+        let err: $crate::CheatError = $err
+            .with_location($crate::Location::new(file!(), line!(), column!()))
+            $($opts)*;
+
+        if !$crate::__private::report_bail_failure(&err) {
+            return Err($crate::__private::bail_error(err));
+        }
+    }};
+    // For callers with no value to continue with: never downgraded.
+    (@raise (always) [$($opts:tt)*], $err:expr) => {{
+        let err: $crate::CheatError = $err
+            .with_location($crate::Location::new(file!(), line!(), column!()))
+            $($opts)*;
+
+        $crate::__private::report_failure(&err);
+        return Err($crate::__private::bail_error(err));
+    }};
+    (@raise (warn) [$($opts:tt)*], $err:expr) => {{
+        let err: $crate::CheatError = $err
+            .with_location($crate::Location::new(file!(), line!(), column!()))
+            $($opts)*;
+
+        if $crate::__private::report_warning(&err) {
+            return Err($crate::__private::bail_error(err));
+        }
+    }};
+    (@raise (todo) [$($opts:tt)*], $err:expr) => {{
+        let err: $crate::CheatError = $err
+            .with_location($crate::Location::new(file!(), line!(), column!()))
+            $($opts)*;
+
+        if $crate::__private::report_todo(&err) {
+            return Err($crate::__private::bail_error(err));
+        }
+    }};
+    (@raise (panic) [$($opts:tt)*], $err:expr) => {{
+        let err: $crate::CheatError = $err
+            .with_location($crate::Location::new(file!(), line!(), column!()))
+            $($opts)*;
+
+        $crate::__private::report_failure(&err);
+        panic!("{}", err)
+    }};
+    (@raise (ensure $failure:ident) [$($opts:tt)*], $err:expr) => {{
+        let err: $crate::CheatError = $err
+            .with_location($crate::Location::new(file!(), line!(), column!()))
+            $($opts)*;
+
+        match $failure.report(err) {
+            Ok(value) => value,
+            Err(err) => return Err(err),
+        }
+    }};
+    (@opts $sink:tt $base:tt [$($opts:tt)*], source = $source:expr, $($rest:tt)*) => {
+        $crate::cheat_bail!(@opts $sink $base [$($opts)* .with_source($source)], $($rest)*)
+    };
+    (@opts $sink:tt $base:tt [$($opts:tt)*], remedy = $remedy:expr, $($rest:tt)*) => {
+        $crate::cheat_bail!(@opts $sink $base [$($opts)* .with_remedy($remedy)], $($rest)*)
+    };
+    (@opts $sink:tt $base:tt [$($opts:tt)*], doc_url = $doc_url:expr, $($rest:tt)*) => {
+        $crate::cheat_bail!(@opts $sink $base [$($opts)* .with_doc_url($doc_url)], $($rest)*)
+    };
+    (@opts $sink:tt $base:tt [$($opts:tt)*], title = $title:expr, $($rest:tt)*) => {
+        $crate::cheat_bail!(@opts $sink $base [$($opts)* .with_title($title)], $($rest)*)
+    };
+    (
+        @opts $sink:tt $base:tt [$($opts:tt)*],
+        meta = { $($key:expr => $value:expr),* $(,)? }, $($rest:tt)*
+    ) => {
+        $crate::cheat_bail!(
+            @opts $sink $base [
+                $($opts)*
+                $(.with_metadata(ToString::to_string(&$key), ToString::to_string(&$value)))*
+            ],
+            $($rest)*
+        )
+    };
+    (@opts $sink:tt $base:tt [$($opts:tt)*], $($arg:tt)*) => {
+        $crate::cheat_bail!(@run $sink [$($opts)*] $base $($arg)*)
+    };
+    // Sinks that run an operation first and only bail, always, if it fails.
+    (@run (expect $result:expr) [$($opts:tt)*] $base:tt $($arg:tt)+) => {
+        match $result {
+            Ok(value) => value,
+            Err(cause) => $crate::cheat_bail!(
+                @run (always) [$($opts)*] $base
+                "{}: {:#}", format_args!($($arg)+), cause
+            ),
+        }
+    };
+    (@run (retry $attempts:expr, $delay_ms:expr) [$($opts:tt)*] $base:tt $op:expr $(,)?) => {
+        match $crate::__private::retry($attempts, $delay_ms, $op) {
+            Ok(value) => value,
+            Err((attempts, last_error)) => $crate::cheat_bail!(
+                @run (always) [$($opts)* .with_cheat($crate::__private::RETRY_CHEAT)] $base
+                "failed after {} attempts; last error: {}", attempts, last_error
+            ),
+        }
+    };
+    (@run (timeout $duration:expr) [$($opts:tt)*] $base:tt $op:expr $(,)?) => {
+        match $crate::__private::run_with_timeout($duration, $op) {
+            Ok(value) => value,
+            Err(deadline) => $crate::cheat_bail!(
+                @run (always) [$($opts)* .with_cheat($crate::__private::TIMEOUT_CHEAT)] $base
+                "operation did not finish within {:?}", deadline
+            ),
+        }
+    };
+    (@run $sink:tt [$($opts:tt)*] (meta $meta:expr) $($arg:tt)*) => {
+        $crate::cheat_bail!(
+            @raise $sink [$($opts)*],
+            $crate::CheatMeta::to_error(&$meta, format!($($arg)*))
+        )
+    };
+    (
+        @run $sink:tt [$($opts:tt)*]
+        ($protects:expr, $severity:expr, $cheats:expr, $consequence:expr) $($arg:tt)*
+    ) => {{
+        let severity: $crate::Severity = $crate::cheat_bail!(@severity $sink $severity);
+        $crate::cheat_bail!(
+            @raise $sink [$($opts)*],
+            $crate::CheatError::new(
+                ToString::to_string(&$protects),
+                severity,
+                $crate::__private::cheat_list($cheats),
+                ToString::to_string(&$consequence),
+                format!($($arg)*),
+            )
+        )
+    }};
+    // Without a `Result` to return, an unknown severity name can only panic.
+    (@severity (panic) $severity:expr) => {
+        $crate::__private::IntoSeverity::into_severity($severity)
+            .unwrap_or_else(|err| panic!("{}", err))
+    };
+    (@severity $sink:tt $severity:expr) => {
+        match $crate::__private::IntoSeverity::into_severity($severity) {
+            Ok(severity) => severity,
+            Err(err) => return Err($crate::__private::severity_error(err)),
+        }
+    };
+    // Key/value metadata is collected with the options, which come after
+    // `meta = CheatMeta` or `profile = ...`; move it past the next field.
+    (@sink $sink:tt meta = { $($tags:tt)* }, $field:ident = [$($items:tt)*], $($rest:tt)*) => {
+        $crate::cheat_bail!(@sink $sink $field = [$($items)*], meta = { $($tags)* }, $($rest)*)
+    };
+    (@sink $sink:tt meta = { $($tags:tt)* }, $field:ident = $value:expr, $($rest:tt)*) => {
+        $crate::cheat_bail!(@sink $sink $field = $value, meta = { $($tags)* }, $($rest)*)
+    };
+    (@sink $sink:tt profile = $profile:expr, $($rest:tt)*) => {
+        $crate::cheat_bail!(@sink $sink meta = *$profile, $($rest)*)
+    };
+    (@sink $sink:tt meta = $meta:expr, extra_cheats = [$($extra:expr),* $(,)?], $($rest:tt)*) => {
+        $crate::cheat_bail!(
+            @sink $sink
+            meta = $crate::CheatMeta::with_extra_cheats(&$meta, [$($extra),*]),
+            $($rest)*
+        )
+    };
+    (@sink $sink:tt meta = $meta:expr, $($rest:tt)*) => {
+        $crate::cheat_bail!(@opts $sink (meta $meta) [], $($rest)*)
+    };
+    (@sink $sink:tt $($rest:tt)*) => {
+        $crate::cheat_bail!(@fields $sink [] [] [] [] [] $($rest)*)
+    };
+    (@fields $sink:tt [] $s:tt $c:tt $q:tt $o:tt protects = $value:expr, $($rest:tt)*) => {
+        $crate::cheat_bail!(@fields $sink [($value)] $s $c $q $o $($rest)*)
+    };
+    (@fields $sink:tt [$p:tt] $s:tt $c:tt $q:tt $o:tt protects = $($rest:tt)*) => {
+        compile_error!("`protects` is given more than once")
+    };
+    (@fields $sink:tt $p:tt [] $c:tt $q:tt $o:tt severity = $value:expr, $($rest:tt)*) => {
+        $crate::cheat_bail!(@fields $sink $p [($value)] $c $q $o $($rest)*)
+    };
+    (@fields $sink:tt $p:tt [$s:tt] $c:tt $q:tt $o:tt severity = $($rest:tt)*) => {
+        compile_error!("`severity` is given more than once")
+    };
+    (@fields $sink:tt $p:tt $s:tt [] $q:tt $o:tt cheats = [], $($rest:tt)*) => {
+        $crate::cheat_bail!(@fields $sink $p $s [($crate::__private::NO_CHEATS)] $q $o $($rest)*)
+    };
+    (@fields $sink:tt $p:tt $s:tt [] $q:tt $o:tt cheats = $value:expr, $($rest:tt)*) => {
+        $crate::cheat_bail!(@fields $sink $p $s [($value)] $q $o $($rest)*)
+    };
+    (@fields $sink:tt $p:tt $s:tt [$c:tt] $q:tt $o:tt cheats = $($rest:tt)*) => {
+        compile_error!("`cheats` is given more than once")
+    };
+    (@fields $sink:tt $p:tt $s:tt $c:tt [] $o:tt consequence = $value:expr, $($rest:tt)*) => {
+        $crate::cheat_bail!(@fields $sink $p $s $c [($value)] $o $($rest)*)
+    };
+    (@fields $sink:tt $p:tt $s:tt $c:tt [$q:tt] $o:tt consequence = $($rest:tt)*) => {
+        compile_error!("`consequence` is given more than once")
+    };
+    (@fields $sink:tt $p:tt $s:tt $c:tt $q:tt [$($o:tt)*] source = $value:expr, $($rest:tt)*) => {
+        $crate::cheat_bail!(@fields $sink $p $s $c $q [$($o)* .with_source($value)] $($rest)*)
+    };
+    (@fields $sink:tt $p:tt $s:tt $c:tt $q:tt [$($o:tt)*] remedy = $value:expr, $($rest:tt)*) => {
+        $crate::cheat_bail!(@fields $sink $p $s $c $q [$($o)* .with_remedy($value)] $($rest)*)
+    };
+    (@fields $sink:tt $p:tt $s:tt $c:tt $q:tt [$($o:tt)*] doc_url = $value:expr, $($rest:tt)*) => {
+        $crate::cheat_bail!(@fields $sink $p $s $c $q [$($o)* .with_doc_url($value)] $($rest)*)
+    };
+    (@fields $sink:tt $p:tt $s:tt $c:tt $q:tt [$($o:tt)*] title = $value:expr, $($rest:tt)*) => {
+        $crate::cheat_bail!(@fields $sink $p $s $c $q [$($o)* .with_title($value)] $($rest)*)
+    };
+    (
+        @fields $sink:tt $p:tt $s:tt $c:tt $q:tt [$($o:tt)*]
+        meta = { $($key:expr => $value:expr),* $(,)? }, $($rest:tt)*
+    ) => {
+        $crate::cheat_bail!(
+            @fields $sink $p $s $c $q [
+                $($o)*
+                $(.with_metadata(ToString::to_string(&$key), ToString::to_string(&$value)))*
+            ]
+            $($rest)*
+        )
+    };
+    (
+        @fields $sink:tt
+        [($protects:expr)] [($severity:expr)] [($cheats:expr)] [($consequence:expr)]
+        [$($opts:tt)*] $($arg:tt)*
+    ) => {
+        $crate::cheat_bail!(
+            @opts $sink ($protects, $severity, $cheats, $consequence) [$($opts)*],
+            $($arg)*
+        )
+    };
+    (@fields $sink:tt [] $s:tt $c:tt $q:tt $o:tt $($rest:tt)*) => {
+        compile_error!("missing `protects = ...`")
+    };
+    (@fields $sink:tt $p:tt [] $c:tt $q:tt $o:tt $($rest:tt)*) => {
+        compile_error!("missing `severity = ...`")
+    };
+    (@fields $sink:tt $p:tt $s:tt [] $q:tt $o:tt $($rest:tt)*) => {
+        compile_error!("missing `cheats = ...`")
+    };
+    (@fields $sink:tt $p:tt $s:tt $c:tt [] $o:tt $($rest:tt)*) => {
+        compile_error!("missing `consequence = ...`")
+    };
+    ($($arg:tt)*) => {
+        $crate::cheat_bail!(@sink (bail) $($arg)*)
+    };
+}
+
+/// Declare a named, lazily built [`CheatMeta`] for use as `profile = NAME`.
+///
+/// Expands to a `static NAME: LazyLock<CheatMeta>`, so it can live at module
+/// level and be shared by every check in a subsystem. An unknown severity
+/// name panics the first time the profile is used.
+///
+/// With the `catalog` feature, every profile is also listed by
+/// `cheat_catalog()`. An optional `id = "DISK-001"` right after the name gives
+/// the entry a stable ID; `validate_catalog()` reports any ID used twice.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// cheat_profile!(
+///     pub DISK_PARTITION,
+///     protects = "Disk is partitioned correctly",
+///     severity = "CRITICAL",
+///     cheats = ["Accept any sfdisk output", "Skip verification"],
+///     consequence = "No partitions, installation fails"
+/// );
+///
+/// cheat_bail!(profile = DISK_PARTITION, "vda1 not found");
+/// cheat_bail!(
+///     profile = DISK_PARTITION,
+///     extra_cheats = ["Check only the first partition"],
+///     "vda2 not found"
+/// );
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! cheat_profile {
     (
+        $(#[$attr:meta])*
+        $vis:vis $name:ident,
+        $(id = $id:expr,)?
         protects = $protects:expr,
         severity = $severity:expr,
-        cheats = [$($cheat:expr),+ $(,)?],
-        consequence = $consequence:expr,
-        $($arg:tt)*
-    ) => {{
-        let cheats_list: &[&str] = &[$($cheat),+];
-        let cheats_formatted: String = cheats_list
-            .iter()
-            .enumerate()
-            .map(|(i, c)| format!("  {}. {}", i + 1, c))
-            .collect::<Vec<_>>()
-            .join("\n");
-
-        let error_msg = format!($($arg)*);
-
-        anyhow::bail!(
-            "\n{border}\n\
-             === CHEAT-GUARDED FAILURE ===\n\
-             {border}\n\n\
-             PROTECTS: {protects}\n\
-             SEVERITY: {severity}\n\n\
-             CHEAT VECTORS:\n\
-             {cheats}\n\n\
-             USER CONSEQUENCE:\n\
-             {consequence}\n\n\
-             ERROR:\n\
-             {error}\n\
-             {border}\n",
-            border = "=".repeat(70),
+        cheats = [],
+        $($rest:tt)*
+    ) => {
+        $crate::cheat_profile!(
+            $(#[$attr])*
+            $vis $name,
+            $(id = $id,)?
             protects = $protects,
             severity = $severity,
-            cheats = cheats_formatted,
-            consequence = $consequence,
-            error = error_msg
+            cheats = $crate::__private::NO_CHEATS,
+            $($rest)*
         );
-    }};
+    };
+    (
+        $(#[$attr:meta])*
+        $vis:vis $name:ident,
+        $(id = $id:expr,)?
+        protects = $protects:expr,
+        severity = $severity:expr,
+        cheats = $cheats:expr,
+        consequence = $consequence:expr $(,)?
+    ) => {
+        $(#[$attr])*
+        $vis static $name: ::std::sync::LazyLock<$crate::CheatMeta> =
+            ::std::sync::LazyLock::new(|| {
+                $crate::CheatMeta::builder()
+                    .protects(ToString::to_string(&$protects))
+                    .severity($severity)
+                    .cheats($cheats)
+                    .consequence(ToString::to_string(&$consequence))
+                    .build()
+            });
+        $crate::__cheat_register!([$($id)?], || &*$name);
+    };
+}
+
+/// Document a cheat for the catalog without guarding any condition.
+///
+/// For code that is protected by something other than a pass/fail check,
+/// such as a type invariant or a review rule. With the `catalog` feature the
+/// fields are listed by `cheat_catalog()`, like a [`cheat_profile!`]; without
+/// it the macro expands to nothing. Usable at module level or as a statement.
+/// The fields may not refer to local variables, and an unknown severity name
+/// panics when the catalog is first read. `cheats` is a list or any
+/// expression yielding strings, such as a shared `const` slice.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// cheat_doc!(
+///     id = "DISK-002",
+///     protects = "Partition table is GPT",
+///     severity = "HIGH",
+///     cheats = ["Construct DiskLayout without DiskLayout::gpt"],
+///     consequence = "UEFI firmware cannot find the ESP"
+/// );
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! cheat_doc {
+    (
+        $(id = $id:expr,)?
+        protects = $protects:expr,
+        severity = $severity:expr,
+        cheats = [],
+        $($rest:tt)*
+    ) => {
+        $crate::cheat_doc!(
+            $(id = $id,)?
+            protects = $protects,
+            severity = $severity,
+            cheats = $crate::__private::NO_CHEATS,
+            $($rest)*
+        );
+    };
+    (
+        $(id = $id:expr,)?
+        protects = $protects:expr,
+        severity = $severity:expr,
+        cheats = $cheats:expr,
+        consequence = $consequence:expr $(,)?
+    ) => {
+        $crate::__cheat_register!([$($id)?], || {
+            static META: ::std::sync::LazyLock<$crate::CheatMeta> =
+                ::std::sync::LazyLock::new(|| {
+                    $crate::CheatMeta::builder()
+                        .protects(ToString::to_string(&$protects))
+                        .severity($severity)
+                        .cheats($cheats)
+                        .consequence(ToString::to_string(&$consequence))
+                        .build()
+                });
+            &*META
+        });
+    };
+}
+
+/// Add a [`CheatEntry`] built by `$meta` to the catalog.
+#[cfg(feature = "catalog")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cheat_register {
+    ([], $meta:expr) => {
+        $crate::__cheat_register!(@submit ::core::option::Option::None, $meta);
+    };
+    ([$id:expr], $meta:expr) => {
+        $crate::__cheat_register!(@submit ::core::option::Option::Some($id), $meta);
+    };
+    (@submit $id:expr, $meta:expr) => {
+        $crate::__private::inventory::submit! {
+            $crate::CheatEntry::new(
+                $id,
+                $meta,
+                $crate::Location::new(file!(), line!(), column!()),
+            )
+        }
+    };
+}
+
+/// Without the `catalog` feature there is nothing to register.
+#[cfg(all(feature = "std", not(feature = "catalog")))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cheat_register {
+    ($id:tt, $meta:expr) => {};
+}
+
+/// Resolve a severity name literal to its [`Severity`] at compile time.
+///
+/// `severity!("CRITICAL")` is `Severity::Critical`. Only the upper, lower,
+/// and title case spellings (`"CRITICAL"`, `"critical"`, `"Critical"`) are
+/// accepted; any other name, including a mixed-case one like `"cRiTiCaL"`
+/// that [`Severity::from_str`](core::str::FromStr::from_str) and string
+/// `severity` fields accept since they ignore case, is a compile error. For
+/// a typo such as `"CRTICAL"` that is the point: the build fails instead of
+/// the runtime error or panic a string `severity` gets, so call sites can
+/// move off string literals one at a time.
+///
+/// Works without the `std` feature.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// cheat_ensure!(
+///     has_vda1,
+///     protects = "Disk is partitioned",
+///     severity = severity!("CRITICAL"),
+///     cheats = ["Accept any output"],
+///     consequence = "Installation fails",
+///     "vda1 not found"
+/// );
+/// ```
+#[macro_export]
+macro_rules! severity {
+    ("CRITICAL") => {
+        $crate::Severity::Critical
+    };
+    ("critical") => {
+        $crate::Severity::Critical
+    };
+    ("Critical") => {
+        $crate::Severity::Critical
+    };
+    ("HIGH") => {
+        $crate::Severity::High
+    };
+    ("high") => {
+        $crate::Severity::High
+    };
+    ("High") => {
+        $crate::Severity::High
+    };
+    ("MEDIUM") => {
+        $crate::Severity::Medium
+    };
+    ("medium") => {
+        $crate::Severity::Medium
+    };
+    ("Medium") => {
+        $crate::Severity::Medium
+    };
+    ("LOW") => {
+        $crate::Severity::Low
+    };
+    ("low") => {
+        $crate::Severity::Low
+    };
+    ("Low") => {
+        $crate::Severity::Low
+    };
+    ($other:literal) => {
+        compile_error!(concat!(
+            "invalid cheat severity ",
+            stringify!($other),
+            ": expected one of CRITICAL, HIGH, MEDIUM, LOW in upper, lower, or title case"
+        ))
+    };
 }
 
 /// Ensure a condition with cheat-aware error message.
 ///
 /// Like `anyhow::ensure!()` but includes cheat documentation if the condition is false.
+/// Accepts the same optional `source`, `remedy`, `doc_url`, `title`, and `meta = { ... }`,
+/// and the `meta = ...` and `profile = ...` forms of [`cheat_bail!`]. The named
+/// fields may come in any order.
+///
+/// The condition may also be an `Option<T>` or a `Result<T, E>` with
+/// `E: Display`; the macro then evaluates to the `T`. `None` bails with the
+/// message as given, and `Err(err)` with the message followed by `: {err:#}`.
+/// These always bail, since there is no value to continue with under
+/// `CHEAT_GUARD_MIN_SEVERITY`.
+///
+/// Everything after the condition is evaluated only if it fails, so the
+/// cheats, consequence, and message arguments cost nothing when it holds.
 ///
 /// # Example
 ///
@@ -125,120 +829,1267 @@ macro_rules! cheat_bail {
 ///     consequence = "Installation fails",
 ///     "Partition {} not found", "vda1"
 /// );
+///
+/// let root = cheat_ensure!(
+///     find_partition(&table, "root"),
+///     meta = DISK_PARTITION,
+///     "no root partition in the table"
+/// );
 /// ```
+#[cfg(feature = "std")]
 #[macro_export]
 macro_rules! cheat_ensure {
+    ($cond:expr, $($rest:tt)*) => {
+        match $crate::__private::Ensure::ensure($cond) {
+            Ok(value) => value,
+            Err(failure) => $crate::cheat_bail!(@sink (ensure failure) $($rest)*),
+        }
+    };
+}
+
+/// Like [`cheat_ensure!`], but evaluates to the `Result` instead of returning it.
+///
+/// `Ok(())` if the condition holds, otherwise `Err` carrying the banner, of
+/// the type the bail macros return (`anyhow::Error` by default). Nothing
+/// returns early, so several results can be collected and reported together.
+/// The arguments are those of `cheat_ensure!`, and a `?` or `return` inside
+/// them leaves only this macro. A failure below `CHEAT_GUARD_MIN_SEVERITY`
+/// prints its `DOWNGRADED:` banner and yields `Ok(())`.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let results = [
+///     cheat_result!(
+///         output.contains("vda1"),
+///         meta = DISK_PARTITION,
+///         "vda1 not found"
+///     ),
+///     cheat_result!(
+///         output.contains("vda2"),
+///         meta = DISK_PARTITION,
+///         "vda2 not found"
+///     ),
+/// ];
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! cheat_result {
+    ($cond:expr, $($rest:tt)*) => {
+        (|| -> ::core::result::Result<(), $crate::__private::BailError> {
+            $crate::cheat_ensure!($cond, $($rest)*);
+            Ok(())
+        })()
+    };
+}
+
+/// Unwrap a `Result` or bail with cheat documentation, like `Result::expect`.
+///
+/// Evaluates `$result` once. `Ok(value)` yields the value; `Err(err)` bails
+/// with the banner, whose ERROR section is the formatted context followed by
+/// the error's `Display` (`{:#}`, so `anyhow` chains are kept). Like
+/// [`cheat_retry!`], it always bails: there is no value to continue with, so
+/// `CHEAT_GUARD_MIN_SEVERITY` does not apply.
+///
+/// After `$result` it takes the same arguments as [`cheat_bail!`]: the fields
+/// in any order with their options, or `meta =`/`profile =`, then the context
+/// message.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let table = cheat_expect!(
+///     read_partition_table(&disk),
+///     protects = "Disk is partitioned correctly",
+///     severity = "CRITICAL",
+///     cheats = ["Treat an unreadable table as empty"],
+///     consequence = "Installation proceeds on a broken disk",
+///     "could not read the partition table of {}", disk
+/// );
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! cheat_expect {
+    ($result:expr, $($rest:tt)+) => {
+        $crate::cheat_bail!(@sink (expect $result) $($rest)+)
+    };
+}
+
+/// Bail with cheat-aware error message unless two values are equal.
+///
+/// Both operands are evaluated exactly once and must be `PartialEq + Debug`.
+/// On mismatch the ERROR section reads `expected: {left:?}` / `actual: {right:?}`.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// cheat_assert_eq!(
+///     partitions.len(),
+///     2,
+///     protects = "Both partitions were created",
+///     severity = "CRITICAL",
+///     cheats = ["Compare against partitions.len() itself", "Check >= 1 instead"],
+///     consequence = "Missing partition causes mount failure"
+/// );
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! cheat_assert_eq {
+    (
+        $left:expr,
+        $right:expr,
+        protects = $protects:expr,
+        severity = $severity:expr,
+        cheats = [],
+        $($rest:tt)*
+    ) => {
+        $crate::cheat_assert_eq!(
+            $left,
+            $right,
+            protects = $protects,
+            severity = $severity,
+            cheats = $crate::__private::NO_CHEATS,
+            $($rest)*
+        )
+    };
+    (
+        $left:expr,
+        $right:expr,
+        protects = $protects:expr,
+        severity = $severity:expr,
+        cheats = $cheats:expr,
+        consequence = $consequence:expr $(,)?
+    ) => {{
+        match (&$left, &$right) {
+            (left, right) => {
+                if !(*left == *right) {
+                    $crate::cheat_bail!(
+                        protects = $protects,
+                        severity = $severity,
+                        cheats = $cheats,
+                        consequence = $consequence,
+                        "expected: {:?}\nactual: {:?}",
+                        left,
+                        right
+                    );
+                }
+            }
+        }
+    }};
+}
+
+/// Bail with cheat-aware error message unless `actual` is within a relative
+/// `tolerance` of `expected`.
+///
+/// Both values are converted with `Into<f64>`, so `f32`, `f64`, and the
+/// smaller integer types work. It bails when
+/// `|actual - expected| / |expected| > tolerance` (an `expected` of zero only
+/// accepts zero; NaN never passes), and the ERROR section reports both values,
+/// the tolerance, and the relative error. The cheat "Widen the tolerance until
+/// it passes" is always appended.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// cheat_assert_approx!(
+///     root.size_mb as f64,
+///     requested_mb as f64,
+///     tolerance = 0.01,
+///     protects = "Root partition has the requested size",
+///     severity = "HIGH",
+///     cheats = ["Compare against the size sfdisk reported back"],
+///     consequence = "Root fills up long before the user expects"
+/// );
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! cheat_assert_approx {
+    (
+        $actual:expr,
+        $expected:expr,
+        tolerance = $tolerance:expr,
+        protects = $protects:expr,
+        severity = $severity:expr,
+        cheats = [],
+        $($rest:tt)*
+    ) => {
+        $crate::cheat_assert_approx!(
+            $actual,
+            $expected,
+            tolerance = $tolerance,
+            protects = $protects,
+            severity = $severity,
+            cheats = $crate::__private::NO_CHEATS,
+            $($rest)*
+        )
+    };
     (
-        $cond:expr,
+        $actual:expr,
+        $expected:expr,
+        tolerance = $tolerance:expr,
         protects = $protects:expr,
         severity = $severity:expr,
-        cheats = [$($cheat:expr),+ $(,)?],
-        consequence = $consequence:expr,
-        $($arg:tt)*
+        cheats = $cheats:expr,
+        consequence = $consequence:expr $(,)?
     ) => {{
-        if !($cond) {
+        let actual: f64 = ::core::convert::Into::into($actual);
+        let expected: f64 = ::core::convert::Into::into($expected);
+        let tolerance: f64 = $tolerance;
+        if let Some(error) = $crate::__private::approx_error(actual, expected, tolerance) {
             $crate::cheat_bail!(
                 protects = $protects,
                 severity = $severity,
-                cheats = [$($cheat),+],
+                cheats = $crate::__private::cheat_list($cheats)
+                    .into_iter()
+                    .chain([String::from($crate::__private::APPROX_CHEAT)]),
                 consequence = $consequence,
-                $($arg)*
+                "expected: {} (relative tolerance {})\nactual: {}\nrelative error: {}",
+                expected,
+                tolerance,
+                actual,
+                error
             );
         }
     }};
 }
 
-/// Check a condition and record result with cheat metadata.
+/// Bail with cheat-aware error message unless `value` lies in an inclusive range.
 ///
-/// This is for the install-tests `StepResult` pattern. It checks a condition,
-/// adds a CheckResult to the StepResult, and documents the cheat vectors.
+/// `value` can be any `PartialOrd + Debug` type and the range a
+/// `RangeInclusive` of the same type; both are evaluated exactly once. The
+/// ERROR section shows the range, the value, and which bound it crossed.
 ///
 /// # Example
 ///
 /// ```rust,ignore
-/// let mut result = StepResult::new(4, "Partition Disk");
-///
-/// cheat_check!(
-///     result,
-///     name = "Partition table created",
-///     condition = output.contains("vda1"),
-///     protects = "Disk has correct partitions",
-///     severity = "CRITICAL",
-///     cheats = ["Accept any output", "Skip verification"],
-///     consequence = "No partitions, installation fails",
-///     expected = "Partition vda1 exists",
-///     actual = format!("sfdisk output: {}", output)
+/// cheat_assert_within!(
+///     free_memory_gib,
+///     2..=4,
+///     protects = "Live ISO leaves room for the installer",
+///     severity = "MEDIUM",
+///     cheats = ["Widen the range to whatever the VM has"],
+///     consequence = "Installer is OOM-killed on small machines"
 /// );
 /// ```
+#[cfg(feature = "std")]
 #[macro_export]
-macro_rules! cheat_check {
+macro_rules! cheat_assert_within {
     (
-        $result:expr,
-        name = $name:expr,
-        condition = $cond:expr,
+        $value:expr,
+        $range:expr,
+        protects = $protects:expr,
+        severity = $severity:expr,
+        cheats = [],
+        $($rest:tt)*
+    ) => {
+        $crate::cheat_assert_within!(
+            $value,
+            $range,
+            protects = $protects,
+            severity = $severity,
+            cheats = $crate::__private::NO_CHEATS,
+            $($rest)*
+        )
+    };
+    (
+        $value:expr,
+        $range:expr,
         protects = $protects:expr,
         severity = $severity:expr,
-        cheats = [$($cheat:expr),+ $(,)?],
-        consequence = $consequence:expr,
-        expected = $expected:expr,
-        actual = $actual:expr
+        cheats = $cheats:expr,
+        consequence = $consequence:expr $(,)?
     ) => {{
-        let cheats_list: &[&str] = &[$($cheat),+];
-        let _cheats_formatted: String = cheats_list
-            .iter()
-            .enumerate()
-            .map(|(i, c)| format!("  {}. {}", i + 1, c))
-            .collect::<Vec<_>>()
-            .join("\n");
-
-        // Print what this check protects (visible in test output)
-        println!("    checking: {} (protects: {})", $name, $protects);
-
-        if $cond {
-            $result.add_check($name, $crate::CheckResult::Pass($expected.to_string()));
-        } else {
-            // Print cheat vectors on failure
-            eprintln!("\n{}", "=".repeat(60));
-            eprintln!("CHEAT-GUARDED CHECK FAILED: {}", $name);
-            eprintln!("{}", "=".repeat(60));
-            eprintln!("PROTECTS: {}", $protects);
-            eprintln!("SEVERITY: {}", $severity);
-            eprintln!("CHEATS:");
-            eprintln!("{}", _cheats_formatted);
-            eprintln!("CONSEQUENCE: {}", $consequence);
-            eprintln!("{}", "=".repeat(60));
-
-            $result.add_check($name, $crate::CheckResult::Fail {
-                expected: $expected.to_string(),
-                actual: $actual.to_string(),
-            });
+        match (&$value, &$range) {
+            (value, range) => {
+                let range: &::core::ops::RangeInclusive<_> = range;
+                if !range.contains(value) {
+                    let crossed = if value < range.start() {
+                        format!("below minimum {:?}", range.start())
+                    } else if value > range.end() {
+                        format!("above maximum {:?}", range.end())
+                    } else {
+                        String::from("not comparable to the bounds")
+                    };
+                    $crate::cheat_bail!(
+                        protects = $protects,
+                        severity = $severity,
+                        cheats = $cheats,
+                        consequence = $consequence,
+                        "range: {:?}\nvalue: {:?} ({})",
+                        range,
+                        value,
+                        crossed
+                    );
+                }
+            }
         }
     }};
 }
 
-/// CheckResult for use with cheat_check! macro.
-/// Mirrors the install-tests CheckResult enum.
-#[derive(Debug, Clone)]
-pub enum CheckResult {
-    Pass(String),
-    Fail { expected: String, actual: String },
+/// Bail with cheat-aware error message unless `haystack` contains `needle`.
+///
+/// Both arguments are `impl AsRef<str>`. The ERROR section names the needle
+/// and shows the first 200 characters of the haystack.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let output = console.exec("lsblk", timeout)?;
+/// cheat_contains!(
+///     output,
+///     "vda1",
+///     protects = "Disk is partitioned correctly",
+///     severity = "CRITICAL",
+///     cheats = ["Accept exit code without verification"],
+///     consequence = "No partitions, installation fails silently"
+/// );
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! cheat_contains {
+    (
+        $haystack:expr,
+        $needle:expr,
+        protects = $protects:expr,
+        severity = $severity:expr,
+        cheats = [],
+        $($rest:tt)*
+    ) => {
+        $crate::cheat_contains!(
+            $haystack,
+            $needle,
+            protects = $protects,
+            severity = $severity,
+            cheats = $crate::__private::NO_CHEATS,
+            $($rest)*
+        )
+    };
+    (
+        $haystack:expr,
+        $needle:expr,
+        protects = $protects:expr,
+        severity = $severity:expr,
+        cheats = $cheats:expr,
+        consequence = $consequence:expr $(,)?
+    ) => {{
+        match (&$haystack, &$needle) {
+            (haystack, needle) => {
+                let haystack: &str = ::core::convert::AsRef::<str>::as_ref(haystack);
+                let needle: &str = ::core::convert::AsRef::<str>::as_ref(needle);
+                if !haystack.contains(needle) {
+                    $crate::cheat_bail!(
+                        protects = $protects,
+                        severity = $severity,
+                        cheats = $cheats,
+                        consequence = $consequence,
+                        "{:?} not found in: {}",
+                        needle,
+                        $crate::__private::preview(haystack, 200)
+                    );
+                }
+            }
+        }
+    }};
 }
 
-impl CheckResult {
-    pub fn passed(&self) -> bool {
-        matches!(self, CheckResult::Pass(_))
-    }
+/// Run a command on a [`ConsoleExec`] and bail with cheat-aware error message
+/// unless its output contains `needle`.
+///
+/// Takes the console, the command, the timeout, and the needle, then the same
+/// fields as [`cheat_contains!`]. Evaluates to the captured output. On failure
+/// the banner names the needle and the command, records the command as
+/// `command` metadata, and shows the full output as STDOUT. A command that
+/// cannot be run at all bails with the console's error instead; like
+/// [`cheat_expect!`], that bail ignores `CHEAT_GUARD_MIN_SEVERITY`, since
+/// there is no output to continue with.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let output = cheat_exec_contains!(
+///     console,
+///     "sfdisk /dev/vda",
+///     timeout,
+///     "vda1",
+///     protects = "Disk is partitioned correctly",
+///     severity = "CRITICAL",
+///     cheats = ["Accept exit code without verification"],
+///     consequence = "No partitions, installation fails silently"
+/// );
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! cheat_exec_contains {
+    (
+        $console:expr,
+        $command:expr,
+        $timeout:expr,
+        $needle:expr,
+        protects = $protects:expr,
+        severity = $severity:expr,
+        cheats = [],
+        $($rest:tt)*
+    ) => {
+        $crate::cheat_exec_contains!(
+            $console,
+            $command,
+            $timeout,
+            $needle,
+            protects = $protects,
+            severity = $severity,
+            cheats = $crate::__private::NO_CHEATS,
+            $($rest)*
+        )
+    };
+    (
+        $console:expr,
+        $command:expr,
+        $timeout:expr,
+        $needle:expr,
+        protects = $protects:expr,
+        severity = $severity:expr,
+        cheats = $cheats:expr,
+        consequence = $consequence:expr $(,)?
+    ) => {{
+        match (&$command, &$needle) {
+            (command, needle) => {
+                let command: &str = ::core::convert::AsRef::<str>::as_ref(command);
+                let needle: &str = ::core::convert::AsRef::<str>::as_ref(needle);
+                use $crate::__private::ConsoleExecExt as _;
+                match $console.cheat_guard_exec(command, $timeout) {
+                    Ok(output) => {
+                        if !output.contains(needle) {
+                            $crate::cheat_bail!(
+                                @opts (bail) ($protects, $severity, $cheats, $consequence)
+                                [
+                                    .with_metadata("command", command)
+                                    .with_stdout(::std::clone::Clone::clone(&output))
+                                ],
+                                "{:?} not found in the output of `{}`",
+                                needle,
+                                command
+                            );
+                        }
+                        output
+                    }
+                    Err(err) => $crate::cheat_bail!(
+                        @opts (always) ($protects, $severity, $cheats, $consequence)
+                        [.with_metadata("command", command)],
+                        "`{}` failed: {:#}",
+                        command,
+                        err
+                    ),
+                }
+            }
+        }
+    }};
 }
 
-#[cfg(test)]
-mod tests {
-    use anyhow::Result;
-
-    #[test]
-    fn test_cheat_ensure_passes() -> Result<()> {
-        cheat_ensure!(
-            true,
-            protects = "Test passes",
+/// Bail with cheat-aware error message unless `text` matches a regex.
+///
+/// Requires the `regex` feature. The ERROR section shows the pattern and the
+/// first 200 characters of the text. A pattern that fails to compile bails
+/// with a plain `invalid cheat_matches! pattern` error instead of the banner,
+/// so a broken regex is not mistaken for a cheat failure.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// cheat_matches!(
+///     output,
+///     r"vda\d+",
+///     protects = "Disk is partitioned correctly",
+///     severity = "CRITICAL",
+///     cheats = ["Match any output with .*"],
+///     consequence = "No partitions, installation fails silently"
+/// );
+/// ```
+#[cfg(feature = "regex")]
+#[macro_export]
+macro_rules! cheat_matches {
+    (
+        $text:expr,
+        $pattern:expr,
+        protects = $protects:expr,
+        severity = $severity:expr,
+        cheats = [],
+        $($rest:tt)*
+    ) => {
+        $crate::cheat_matches!(
+            $text,
+            $pattern,
+            protects = $protects,
+            severity = $severity,
+            cheats = $crate::__private::NO_CHEATS,
+            $($rest)*
+        )
+    };
+    (
+        $text:expr,
+        $pattern:expr,
+        protects = $protects:expr,
+        severity = $severity:expr,
+        cheats = $cheats:expr,
+        consequence = $consequence:expr $(,)?
+    ) => {{
+        match (&$text, &$pattern) {
+            (text, pattern) => {
+                let text: &str = ::core::convert::AsRef::<str>::as_ref(text);
+                let pattern: &str = ::core::convert::AsRef::<str>::as_ref(pattern);
+                let regex = match $crate::__private::Regex::new(pattern) {
+                    Ok(regex) => regex,
+                    Err(err) => return Err($crate::__private::pattern_error(pattern, err)),
+                };
+                if !regex.is_match(text) {
+                    $crate::cheat_bail!(
+                        protects = $protects,
+                        severity = $severity,
+                        cheats = $cheats,
+                        consequence = $consequence,
+                        "pattern {:?} did not match: {}",
+                        pattern,
+                        $crate::__private::preview(text, 200)
+                    );
+                }
+            }
+        }
+    }};
+}
+
+/// Retry a fallible operation and bail with cheat documentation if every
+/// attempt fails.
+///
+/// Runs the closure (`FnMut() -> Result<T, E>` with `E: Display`) up to
+/// `attempts` times, sleeping `delay_ms` between tries, and evaluates to the
+/// first `Ok` value. On exhaustion it bails with the banner, reporting the
+/// attempt count and last error. The cheat "Increase attempts to force a
+/// pass" is always appended, since bumping the count until a flaky operation
+/// passes by luck is the classic way to cheat a retry. Exhaustion always
+/// bails; `CHEAT_GUARD_MIN_SEVERITY` does not apply because there is no value
+/// to continue with.
+///
+/// Between `delay_ms` and the closure go the same fields and options as in
+/// [`cheat_bail!`], or `meta =`/`profile =`.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let output = cheat_retry!(
+///     attempts = 3,
+///     delay_ms = 500,
+///     protects = "Network comes up after boot",
+///     severity = "HIGH",
+///     cheats = ["Treat a timeout as success"],
+///     consequence = "Installed system has no network",
+///     || console.exec("ping -c1 10.0.2.2", timeout)
+/// );
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! cheat_retry {
+    (attempts = $attempts:expr, delay_ms = $delay_ms:expr, $($rest:tt)+) => {
+        $crate::cheat_bail!(@sink (retry $attempts, $delay_ms) $($rest)+)
+    };
+}
+
+/// Run a blocking operation with a deadline and bail with cheat
+/// documentation if it does not finish in time.
+///
+/// The closure (`FnOnce() -> T + Send + 'static`) runs on a worker thread and
+/// the macro evaluates to its return value. If `duration` elapses first, it
+/// bails with the banner reporting the deadline; the worker is abandoned, not
+/// killed. The cheat "Raise the timeout instead of fixing slowness" is always
+/// appended. Like [`cheat_retry!`], a missed deadline always bails.
+///
+/// Between `duration` and the closure go the same fields and options as in
+/// [`cheat_bail!`], or `meta =`/`profile =`.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let output = cheat_timeout!(
+///     duration = Duration::from_secs(30),
+///     protects = "Installer finishes in reasonable time",
+///     severity = "MEDIUM",
+///     cheats = ["Skip the slow step"],
+///     consequence = "Users wait minutes for a hung install",
+///     move || run_pacstrap(&target)
+/// );
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! cheat_timeout {
+    (duration = $duration:expr, $($rest:tt)+) => {
+        $crate::cheat_bail!(@sink (timeout $duration) $($rest)+)
+    };
+}
+
+/// Print a cheat-aware warning without failing.
+///
+/// Takes the same arguments as [`cheat_bail!`] but prints a
+/// `CHEAT-GUARDED WARNING` banner to stderr and continues. With
+/// `CHEAT_GUARD_STRICT=1` it bails with the [`CheatError`] instead, like
+/// [`cheat_todo!`] under `CHEAT_GUARD_STRICT_TODO`, so use it in a function
+/// returning `anyhow::Result`; an unknown severity name bails with the parse
+/// error, as in [`cheat_bail!`].
+///
+/// # Example
+///
+/// ```rust,ignore
+/// if layout.has_extra_partitions() {
+///     cheat_warn!(
+///         protects = "Disk layout matches the installer plan",
+///         severity = "LOW",
+///         cheats = ["Ignore unexpected partitions"],
+///         consequence = "Leftover partitions confuse the bootloader",
+///         "Found {} extra partitions", layout.extra()
+///     );
+/// }
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! cheat_warn {
+    ($($arg:tt)*) => {
+        $crate::cheat_bail!(@sink (warn) $($arg)*)
+    };
+}
+
+/// Mark a check that is not written yet, keeping its cheat documentation.
+///
+/// Takes the same arguments as [`cheat_bail!`], with the message saying what
+/// is still missing. In debug builds it prints a `CHEAT-GUARDED TODO` banner
+/// and continues, so a stubbed step still runs. With
+/// `CHEAT_GUARD_STRICT_TODO=1` it bails with a [`CheatError`] instead, so CI
+/// can refuse to ship stubs. Use it in a function returning `anyhow::Result`;
+/// an unknown severity name bails with the parse error, as in [`cheat_bail!`].
+///
+/// # Example
+///
+/// ```rust,ignore
+/// cheat_todo!(
+///     protects = "Root filesystem is mounted read-write",
+///     severity = "HIGH",
+///     cheats = ["Check the mount exists but not its flags"],
+///     consequence = "Users cannot save files after install",
+///     "Verify mount flags once the console exposes /proc/mounts"
+/// );
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! cheat_todo {
+    ($($arg:tt)*) => {
+        $crate::cheat_bail!(@sink (todo) $($arg)*)
+    };
+}
+
+/// Panic with cheat documentation, for code that has no `Result` to bail into.
+///
+/// Takes the same arguments as [`cheat_bail!`]. The failure is reported like a
+/// bail (counts, `tracing`, `log`) and the panic message is the rendered
+/// [`CheatError`], so it carries the full banner. `CHEAT_GUARD_MIN_SEVERITY`
+/// does not apply, and an unknown severity name panics with the parse error.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// impl Step for Partition {
+///     fn verify(&mut self) {
+///         if !self.output.contains("vda1") {
+///             cheat_panic!(
+///                 protects = "Disk is partitioned correctly",
+///                 severity = "CRITICAL",
+///                 cheats = ["Accept exit code without verification"],
+///                 consequence = "No partitions, installation fails silently",
+///                 "vda1 not found after sfdisk"
+///             );
+///         }
+///     }
+/// }
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! cheat_panic {
+    ($($arg:tt)*) => {
+        $crate::cheat_bail!(@sink (panic) $($arg)*)
+    };
+}
+
+/// Check a condition and record result with cheat metadata.
+///
+/// This is for the install-tests [`StepResult`] pattern. It checks a condition,
+/// adds a CheckResult to the StepResult, and documents the cheat vectors.
+///
+/// Evaluates to the condition's `bool`, so it can drive further logic.
+///
+/// The condition is timed; the duration is kept in the recorded
+/// [`CheckInfo`], and a condition slower than `CHEAT_GUARD_SLOW_MS` prints
+/// `(took 1.2s)` under its `checking:` line.
+///
+/// After `result`, the named fields may come in any order, each once; a
+/// missing one is a compile error naming it.
+///
+/// With `warn = true`, a failing condition records a `CheckResult::Warn`
+/// instead of a `Fail`; the banner is still printed. An optional
+/// `remedy = "..."` adds a `SUGGESTED FIX:` line to the banner,
+/// `doc_url = "..."` a `SEE:` line, and `meta = { "key" => value, ... }` a
+/// `METADATA:` section. `stdout = ...` and `stderr = ...` attach a command's
+/// captured output, kept on the check's [`CheckInfo`] and printed in its own
+/// `STDOUT:`/`STDERR:` section, separate from `actual`.
+///
+/// `category = "disk"` files the check under a subsystem, for
+/// [`TestReport::category_summary`].
+///
+/// With `CHEAT_GUARD_STREAM=jsonl`, every check instead writes one JSON object
+/// (`name`, `result` of `pass`/`fail`/`warn`, `severity`, `protects`) per line
+/// to the [banner writer](crate::set_cheat_writer); `severity` is then also
+/// evaluated for passing checks.
+///
+/// When `GITHUB_ACTIONS=true`, a failing check also prints an `::error`
+/// (or `::warning`) workflow command so it shows inline on the PR diff.
+///
+/// `expected` and `actual` are stored as-is when they are strings (anything
+/// `AsRef<str>`) and as their `Debug` form otherwise, so numbers and parsed
+/// structs can be passed directly.
+///
+/// Only `name`, `condition`, `protects`, and `expected` are evaluated when the
+/// condition holds; `cheats`, `consequence`, `actual`, and the options are
+/// evaluated only on failure, so an expensive `format!` in `actual` costs
+/// nothing on the passing path. A pass keeps only its timing and `category`
+/// in its [`CheckInfo`]; the cheat documentation is recorded for failures.
+///
+/// There is no error channel here, so an unknown string `severity` panics
+/// when the check fails.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let mut result = StepResult::new(4, "Partition Disk");
+///
+/// let partitioned = cheat_check!(
+///     result,
+///     name = "Partition table created",
+///     condition = output.contains("vda1"),
+///     protects = "Disk has correct partitions",
+///     severity = "CRITICAL",
+///     cheats = ["Accept any output", "Skip verification"],
+///     consequence = "No partitions, installation fails",
+///     expected = "Partition vda1 exists",
+///     actual = format!("sfdisk output: {}", output)
+/// );
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! cheat_check {
+    (@add $result:expr, $category:tt, $name:expr, $check:expr, $info:expr) => {
+        $result.add_check_with(
+            $name,
+            $check,
+            $crate::CheckInfo {
+                category: $crate::cheat_check!(@category $category),
+                ..$info
+            },
+        )
+    };
+    (@category []) => {
+        None
+    };
+    (@category [($category:expr)]) => {
+        Some(ToString::to_string(&$category))
+    };
+    (
+        @opts (
+            $result:expr,
+            $name:expr,
+            $cond:expr,
+            $protects:expr,
+            $severity:expr,
+            $cheats:expr,
+            $consequence:expr,
+            $expected:expr,
+            $actual:expr
+        )
+        [$warn:expr] $category:tt [$($opts:tt)*]
+    ) => {{
+        #[allow(unused_imports)]
+        use $crate::__private::{DebugCheckField as _, StrCheckField as _};
+
+        // Print what this check protects (visible in test output)
+        $crate::__private::announce_check(&$name, &$protects);
+
+        let started = ::std::time::Instant::now();
+        let passed: bool = $cond;
+        let elapsed = started.elapsed();
+        let timestamp = $crate::__private::check_timestamp();
+        $crate::__private::announce_duration(elapsed);
+        if passed {
+            if $crate::__private::streaming() {
+                let severity = $crate::__private::IntoSeverity::into_severity($severity).ok();
+                $crate::__private::stream_check(&$name, "pass", severity, &$protects, timestamp);
+            }
+            $crate::cheat_check!(
+                @add $result, $category, $name,
+                $crate::CheckResult::Pass((&$crate::__private::CheckField(&$expected)).to_check_field()),
+                $crate::CheckInfo {
+                    duration: Some(elapsed),
+                    timestamp,
+                    ..Default::default()
+                }
+            );
+        } else {
+            let warn: bool = $warn;
+            let severity: $crate::Severity =
+                $crate::__private::IntoSeverity::into_severity($severity)
+                    .unwrap_or_else(|err| panic!("{}", err));
+            let expected: String = (&$crate::__private::CheckField(&$expected)).to_check_field();
+            let actual: String = $crate::__private::truncate_actual(
+                $crate::__private::redact((&$crate::__private::CheckField(&$actual)).to_check_field()),
+            );
+            let err = $crate::CheatError::new(
+                ToString::to_string(&$protects),
+                severity,
+                $crate::__private::cheat_list($cheats),
+                ToString::to_string(&$consequence),
+                format!("expected: {}, actual: {}", expected, actual),
+            )
+            .with_location($crate::Location::new(file!(), line!(), column!()))
+            $($opts)*;
+
+            let category: Option<String> = $crate::cheat_check!(@category $category);
+            $crate::__private::report_check_failure(
+                &$name,
+                &err,
+                warn,
+                &expected,
+                &actual,
+                category.as_deref(),
+                timestamp,
+            );
+
+            let info = $crate::CheckInfo {
+                category,
+                duration: Some(elapsed),
+                timestamp,
+                ..$crate::CheckInfo::from(&err)
+            };
+            let check = if warn {
+                $crate::CheckResult::Warn {
+                    message: err.message,
+                }
+            } else {
+                $crate::CheckResult::Fail { expected, actual }
+            };
+            $result.add_check_with($name, check, info);
+        }
+        passed
+    }};
+    (
+        @fields $r:tt [] $k:tt $p:tt $s:tt $c:tt $q:tt $e:tt $a:tt $w:tt $g:tt $o:tt
+        name = $value:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::cheat_check!(@fields $r [($value)] $k $p $s $c $q $e $a $w $g $o $($($rest)*)?)
+    };
+    (
+        @fields $r:tt [$n:tt] $k:tt $p:tt $s:tt $c:tt $q:tt $e:tt $a:tt $w:tt $g:tt $o:tt
+        name = $($rest:tt)*
+    ) => {
+        compile_error!("`name` is given more than once")
+    };
+    (
+        @fields $r:tt $n:tt [] $p:tt $s:tt $c:tt $q:tt $e:tt $a:tt $w:tt $g:tt $o:tt
+        condition = $value:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::cheat_check!(@fields $r $n [($value)] $p $s $c $q $e $a $w $g $o $($($rest)*)?)
+    };
+    (
+        @fields $r:tt $n:tt [$k:tt] $p:tt $s:tt $c:tt $q:tt $e:tt $a:tt $w:tt $g:tt $o:tt
+        condition = $($rest:tt)*
+    ) => {
+        compile_error!("`condition` is given more than once")
+    };
+    (
+        @fields $r:tt $n:tt $k:tt [] $s:tt $c:tt $q:tt $e:tt $a:tt $w:tt $g:tt $o:tt
+        protects = $value:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::cheat_check!(@fields $r $n $k [($value)] $s $c $q $e $a $w $g $o $($($rest)*)?)
+    };
+    (
+        @fields $r:tt $n:tt $k:tt [$p:tt] $s:tt $c:tt $q:tt $e:tt $a:tt $w:tt $g:tt $o:tt
+        protects = $($rest:tt)*
+    ) => {
+        compile_error!("`protects` is given more than once")
+    };
+    (
+        @fields $r:tt $n:tt $k:tt $p:tt [] $c:tt $q:tt $e:tt $a:tt $w:tt $g:tt $o:tt
+        severity = $value:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::cheat_check!(@fields $r $n $k $p [($value)] $c $q $e $a $w $g $o $($($rest)*)?)
+    };
+    (
+        @fields $r:tt $n:tt $k:tt $p:tt [$s:tt] $c:tt $q:tt $e:tt $a:tt $w:tt $g:tt $o:tt
+        severity = $($rest:tt)*
+    ) => {
+        compile_error!("`severity` is given more than once")
+    };
+    (
+        @fields $r:tt $n:tt $k:tt $p:tt $s:tt [] $q:tt $e:tt $a:tt $w:tt $g:tt $o:tt
+        cheats = [] $(, $($rest:tt)*)?
+    ) => {
+        $crate::cheat_check!(
+            @fields $r $n $k $p $s [($crate::__private::NO_CHEATS)] $q $e $a $w $g $o $($($rest)*)?
+        )
+    };
+    (
+        @fields $r:tt $n:tt $k:tt $p:tt $s:tt [] $q:tt $e:tt $a:tt $w:tt $g:tt $o:tt
+        cheats = $value:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::cheat_check!(@fields $r $n $k $p $s [($value)] $q $e $a $w $g $o $($($rest)*)?)
+    };
+    (
+        @fields $r:tt $n:tt $k:tt $p:tt $s:tt [$c:tt] $q:tt $e:tt $a:tt $w:tt $g:tt $o:tt
+        cheats = $($rest:tt)*
+    ) => {
+        compile_error!("`cheats` is given more than once")
+    };
+    (
+        @fields $r:tt $n:tt $k:tt $p:tt $s:tt $c:tt [] $e:tt $a:tt $w:tt $g:tt $o:tt
+        consequence = $value:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::cheat_check!(@fields $r $n $k $p $s $c [($value)] $e $a $w $g $o $($($rest)*)?)
+    };
+    (
+        @fields $r:tt $n:tt $k:tt $p:tt $s:tt $c:tt [$q:tt] $e:tt $a:tt $w:tt $g:tt $o:tt
+        consequence = $($rest:tt)*
+    ) => {
+        compile_error!("`consequence` is given more than once")
+    };
+    (
+        @fields $r:tt $n:tt $k:tt $p:tt $s:tt $c:tt $q:tt [] $a:tt $w:tt $g:tt $o:tt
+        expected = $value:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::cheat_check!(@fields $r $n $k $p $s $c $q [($value)] $a $w $g $o $($($rest)*)?)
+    };
+    (
+        @fields $r:tt $n:tt $k:tt $p:tt $s:tt $c:tt $q:tt [$e:tt] $a:tt $w:tt $g:tt $o:tt
+        expected = $($rest:tt)*
+    ) => {
+        compile_error!("`expected` is given more than once")
+    };
+    (
+        @fields $r:tt $n:tt $k:tt $p:tt $s:tt $c:tt $q:tt $e:tt [] $w:tt $g:tt $o:tt
+        actual = $value:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::cheat_check!(@fields $r $n $k $p $s $c $q $e [($value)] $w $g $o $($($rest)*)?)
+    };
+    (
+        @fields $r:tt $n:tt $k:tt $p:tt $s:tt $c:tt $q:tt $e:tt [$a:tt] $w:tt $g:tt $o:tt
+        actual = $($rest:tt)*
+    ) => {
+        compile_error!("`actual` is given more than once")
+    };
+    (
+        @fields $r:tt $n:tt $k:tt $p:tt $s:tt $c:tt $q:tt $e:tt $a:tt [] $g:tt $o:tt
+        warn = $value:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::cheat_check!(@fields $r $n $k $p $s $c $q $e $a [($value)] $g $o $($($rest)*)?)
+    };
+    (
+        @fields $r:tt $n:tt $k:tt $p:tt $s:tt $c:tt $q:tt $e:tt $a:tt [$w:tt] $g:tt $o:tt
+        warn = $($rest:tt)*
+    ) => {
+        compile_error!("`warn` is given more than once")
+    };
+    (
+        @fields $r:tt $n:tt $k:tt $p:tt $s:tt $c:tt $q:tt $e:tt $a:tt $w:tt [] $o:tt
+        category = $value:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::cheat_check!(@fields $r $n $k $p $s $c $q $e $a $w [($value)] $o $($($rest)*)?)
+    };
+    (
+        @fields $r:tt $n:tt $k:tt $p:tt $s:tt $c:tt $q:tt $e:tt $a:tt $w:tt [$g:tt] $o:tt
+        category = $($rest:tt)*
+    ) => {
+        compile_error!("`category` is given more than once")
+    };
+    (
+        @fields $r:tt $n:tt $k:tt $p:tt $s:tt $c:tt $q:tt $e:tt $a:tt $w:tt $g:tt [$($o:tt)*]
+        remedy = $value:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::cheat_check!(
+            @fields $r $n $k $p $s $c $q $e $a $w $g [$($o)* .with_remedy($value)] $($($rest)*)?
+        )
+    };
+    (
+        @fields $r:tt $n:tt $k:tt $p:tt $s:tt $c:tt $q:tt $e:tt $a:tt $w:tt $g:tt [$($o:tt)*]
+        doc_url = $value:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::cheat_check!(
+            @fields $r $n $k $p $s $c $q $e $a $w $g [$($o)* .with_doc_url($value)] $($($rest)*)?
+        )
+    };
+    (
+        @fields $r:tt $n:tt $k:tt $p:tt $s:tt $c:tt $q:tt $e:tt $a:tt $w:tt $g:tt [$($o:tt)*]
+        stdout = $value:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::cheat_check!(
+            @fields $r $n $k $p $s $c $q $e $a $w $g [$($o)* .with_stdout($value)] $($($rest)*)?
+        )
+    };
+    (
+        @fields $r:tt $n:tt $k:tt $p:tt $s:tt $c:tt $q:tt $e:tt $a:tt $w:tt $g:tt [$($o:tt)*]
+        stderr = $value:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::cheat_check!(
+            @fields $r $n $k $p $s $c $q $e $a $w $g [$($o)* .with_stderr($value)] $($($rest)*)?
+        )
+    };
+    (
+        @fields $r:tt $n:tt $k:tt $p:tt $s:tt $c:tt $q:tt $e:tt $a:tt $w:tt $g:tt [$($o:tt)*]
+        meta = { $($key:expr => $value:expr),* $(,)? } $(, $($rest:tt)*)?
+    ) => {
+        $crate::cheat_check!(
+            @fields $r $n $k $p $s $c $q $e $a $w $g [
+                $($o)*
+                $(.with_metadata(ToString::to_string(&$key), ToString::to_string(&$value)))*
+            ]
+            $($($rest)*)?
+        )
+    };
+    (
+        @fields ($result:expr) [($name:expr)] [($cond:expr)] [($protects:expr)] [($severity:expr)]
+        [($cheats:expr)] [($consequence:expr)] [($expected:expr)] [($actual:expr)] []
+        $category:tt [$($opts:tt)*]
+    ) => {
+        $crate::cheat_check!(
+            @opts (
+                $result,
+                $name,
+                $cond,
+                $protects,
+                $severity,
+                $cheats,
+                $consequence,
+                $expected,
+                $actual
+            )
+            [false] $category [$($opts)*]
+        )
+    };
+    (
+        @fields ($result:expr) [($name:expr)] [($cond:expr)] [($protects:expr)] [($severity:expr)]
+        [($cheats:expr)] [($consequence:expr)] [($expected:expr)] [($actual:expr)] [($warn:expr)]
+        $category:tt [$($opts:tt)*]
+    ) => {
+        $crate::cheat_check!(
+            @opts (
+                $result,
+                $name,
+                $cond,
+                $protects,
+                $severity,
+                $cheats,
+                $consequence,
+                $expected,
+                $actual
+            )
+            [$warn] $category [$($opts)*]
+        )
+    };
+    (@fields $r:tt [] $k:tt $p:tt $s:tt $c:tt $q:tt $e:tt $a:tt $w:tt $g:tt $o:tt $($rest:tt)*) => {
+        compile_error!("missing `name = ...`")
+    };
+    (@fields $r:tt $n:tt [] $p:tt $s:tt $c:tt $q:tt $e:tt $a:tt $w:tt $g:tt $o:tt $($rest:tt)*) => {
+        compile_error!("missing `condition = ...`")
+    };
+    (@fields $r:tt $n:tt $k:tt [] $s:tt $c:tt $q:tt $e:tt $a:tt $w:tt $g:tt $o:tt $($rest:tt)*) => {
+        compile_error!("missing `protects = ...`")
+    };
+    (@fields $r:tt $n:tt $k:tt $p:tt [] $c:tt $q:tt $e:tt $a:tt $w:tt $g:tt $o:tt $($rest:tt)*) => {
+        compile_error!("missing `severity = ...`")
+    };
+    (@fields $r:tt $n:tt $k:tt $p:tt $s:tt [] $q:tt $e:tt $a:tt $w:tt $g:tt $o:tt $($rest:tt)*) => {
+        compile_error!("missing `cheats = ...`")
+    };
+    (@fields $r:tt $n:tt $k:tt $p:tt $s:tt $c:tt [] $e:tt $a:tt $w:tt $g:tt $o:tt $($rest:tt)*) => {
+        compile_error!("missing `consequence = ...`")
+    };
+    (@fields $r:tt $n:tt $k:tt $p:tt $s:tt $c:tt $q:tt [] $a:tt $w:tt $g:tt $o:tt $($rest:tt)*) => {
+        compile_error!("missing `expected = ...`")
+    };
+    (@fields $r:tt $n:tt $k:tt $p:tt $s:tt $c:tt $q:tt $e:tt [] $w:tt $g:tt $o:tt $($rest:tt)*) => {
+        compile_error!("missing `actual = ...`")
+    };
+    ($result:expr, $($rest:tt)*) => {
+        $crate::cheat_check!(@fields ($result) [] [] [] [] [] [] [] [] [] [] [] $($rest)*)
+    };
+}
+
+/// Run several [`cheat_check!`]s against one [`StepResult`].
+///
+/// Each braced item holds the fields of one `cheat_check!` after `result`.
+/// Every check runs, so one failure does not hide the others, and the macro
+/// evaluates to the number of them that recorded a `CheckResult::Fail`.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let failures = cheat_group!(result, [
+///     {
+///         name = "vda1 exists",
+///         condition = output.contains("vda1"),
+///         protects = "Boot partition is created",
+///         severity = "CRITICAL",
+///         cheats = ["Accept any output"],
+///         consequence = "Nothing to install the bootloader into",
+///         expected = "vda1",
+///         actual = &output,
+///     },
+///     {
+///         name = "vda2 exists",
+///         condition = output.contains("vda2"),
+///         protects = "Root partition is created",
+///         severity = "CRITICAL",
+///         cheats = ["Check only the first partition"],
+///         consequence = "Nowhere to install the system",
+///         expected = "vda2",
+///         actual = &output,
+///     },
+/// ]);
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! cheat_group {
+    ($result:expr, [$({ $($check:tt)* }),* $(,)?]) => {{
+        let failed_before = $result.summary().failed;
+        $(
+            $crate::cheat_check!($result, $($check)*);
+        )*
+        $result.summary().failed - failed_before
+    }};
+}
+
+/// Open a [`CheatScope`] named `$name`.
+///
+/// Feed it each `CheckResult` with `record`; when it goes out of scope it
+/// prints `scope 'NAME': P/T checks passed`.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let mut scope = cheat_scope!("Partition Disk");
+/// for (_, check) in step.checks() {
+///     scope.record(check);
+/// }
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! cheat_scope {
+    ($name:expr $(,)?) => {
+        $crate::CheatScope::new($name)
+    };
+}
+
+/// Record a skipped check with cheat metadata.
+///
+/// For checks that do not apply in this environment (e.g. UEFI checks on a
+/// BIOS machine). Adds a `CheckResult::Skip` carrying `reason` and prints the
+/// cheat metadata to stdout, since skipping is itself a way to cheat.
+/// A trailing `category = "..."` files it as [`cheat_check!`] does.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// if !system.is_uefi() {
+///     cheat_skip!(
+///         result,
+///         name = "EFI system partition mounted",
+///         reason = "BIOS boot, no ESP expected",
+///         protects = "Bootloader can be installed",
+///         severity = "CRITICAL",
+///         cheats = ["Report BIOS on a UEFI machine to skip the check"],
+///         consequence = "System does not boot"
+///     );
+/// }
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! cheat_skip {
+    (
+        $result:expr,
+        name = $name:expr,
+        reason = $reason:expr,
+        protects = $protects:expr,
+        severity = $severity:expr,
+        cheats = [],
+        $($rest:tt)*
+    ) => {
+        $crate::cheat_skip!(
+            $result,
+            name = $name,
+            reason = $reason,
+            protects = $protects,
+            severity = $severity,
+            cheats = $crate::__private::NO_CHEATS,
+            $($rest)*
+        )
+    };
+    (
+        $result:expr,
+        name = $name:expr,
+        reason = $reason:expr,
+        protects = $protects:expr,
+        severity = $severity:expr,
+        cheats = $cheats:expr,
+        consequence = $consequence:expr
+        $(, category = $category:expr)? $(,)?
+    ) => {{
+        let severity: $crate::Severity = $crate::__private::IntoSeverity::into_severity($severity)
+            .unwrap_or_else(|err| panic!("{}", err));
+        let cheats_list: Vec<String> = $crate::__private::cheat_list($cheats);
+        let reason: String = ToString::to_string(&$reason);
+
+        println!("    skipping: {} (protects: {})", $name, $protects);
+        println!("      reason: {}", reason);
+        println!("      severity: {}", severity);
+        for (i, c) in cheats_list.iter().enumerate() {
+            println!("      cheat {}: {}", i + 1, c);
+        }
+        println!("      consequence: {}", $consequence);
+
+        $crate::cheat_check!(
+            @add $result, [$(($category))?], $name,
+            $crate::CheckResult::Skip(reason),
+            $crate::CheckInfo {
+                severity: Some(severity),
+                protects: Some(ToString::to_string(&$protects)),
+                cheats: cheats_list,
+                consequence: Some(ToString::to_string(&$consequence)),
+                ..Default::default()
+            }
+        );
+    }};
+}
+
+#[cfg(all(test, feature = "anyhow"))]
+mod tests {
+    use crate::test_util::{lock_globals, EnvGuard, SharedBuf};
+    use crate::{CheckResult, StepResult};
+    use anyhow::Result;
+
+    /// Plain human banners for failures of any severity, so tests can match
+    /// the captured check banner.
+    fn check_banner_env() -> EnvGuard {
+        EnvGuard::set(&[
+            (crate::config::COLOR_ENV, "never"),
+            (crate::config::FORMAT_ENV, "human"),
+            (crate::config::MIN_SEVERITY_ENV, "LOW"),
+        ])
+    }
+
+    #[test]
+    fn test_cheat_ensure_passes() -> Result<()> {
+        cheat_ensure!(
+            true,
+            protects = "Test passes",
             severity = "LOW",
             cheats = ["None"],
             consequence = "Test fails",
@@ -247,8 +2098,209 @@ mod tests {
         Ok(())
     }
 
+    fn bail_message(result: Result<()>) -> String {
+        crate::CheatError::from_anyhow(&result.unwrap_err())
+            .unwrap()
+            .message
+            .clone()
+    }
+
+    #[test]
+    fn test_cheat_bail_title() {
+        let _env = EnvGuard::unset(&[crate::config::FORMAT_ENV, crate::config::MIN_SEVERITY_ENV]);
+        let check = |titled: bool| -> Result<()> {
+            if titled {
+                cheat_ensure!(
+                    false,
+                    title = "SECURITY GUARD FAILURE",
+                    protects = "Secrets stay on disk",
+                    severity = "HIGH",
+                    cheats = ["Log the key"],
+                    consequence = "Keys leak",
+                    "key printed"
+                );
+            }
+            cheat_bail!(
+                protects = "Secrets stay on disk",
+                severity = "HIGH",
+                cheats = ["Log the key"],
+                consequence = "Keys leak",
+                "key printed"
+            );
+            Ok(())
+        };
+
+        let titled = check(true).unwrap_err().to_string();
+        let plain = check(false).unwrap_err().to_string();
+        assert!(
+            titled.contains("\n=== SECURITY GUARD FAILURE ===\n"),
+            "{titled}"
+        );
+        assert!(
+            plain.contains("\n=== CHEAT-GUARDED FAILURE ===\n"),
+            "{plain}"
+        );
+        assert_eq!(
+            titled
+                .replace("SECURITY GUARD", "CHEAT-GUARDED")
+                .lines()
+                .filter(|l| !l.starts_with("LOCATION"))
+                .collect::<Vec<_>>(),
+            plain
+                .lines()
+                .filter(|l| !l.starts_with("LOCATION"))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_builder_matches_cheat_bail() {
+        let _globals = lock_globals();
+        let result: Result<()> = (|| {
+            cheat_bail!(
+                protects = "Disk is partitioned",
+                severity = "HIGH",
+                cheats = ["Accept any output", "Skip verification"],
+                consequence = "Installation fails",
+                remedy = "Run sfdisk by hand",
+                doc_url = "https://example.com/disk",
+                meta = { "disk" => "/dev/vda" },
+                "{} not found",
+                "vda1"
+            );
+            Ok(())
+        })();
+        let from_macro = result.unwrap_err();
+        let location = crate::CheatError::from_anyhow(&from_macro)
+            .unwrap()
+            .location
+            .unwrap();
+
+        let defs = [("Accept any output",), ("Skip verification",)];
+        let mut builder = crate::CheatError::builder()
+            .protects("Disk is partitioned")
+            .severity("HIGH")
+            .consequence("Installation fails");
+        for (cheat,) in defs {
+            builder = builder.cheat(cheat);
+        }
+        let built = builder
+            .remedy("Run sfdisk by hand")
+            .doc_url("https://example.com/disk")
+            .metadata("disk", "/dev/vda")
+            .message(format!("{} not found", "vda1"))
+            .location(location)
+            .build();
+
+        assert_eq!(built.to_string(), from_macro.to_string());
+        assert_eq!(
+            anyhow::Error::from(built).to_string(),
+            from_macro.to_string()
+        );
+    }
+
+    #[test]
+    fn test_cheat_bail_format_args() {
+        let _globals = lock_globals();
+        let partition = "vda1";
+        let positional: Result<()> = (|| {
+            cheat_bail!(
+                protects = "p",
+                severity = "HIGH",
+                cheats = ["c"],
+                consequence = "q",
+                "Partition {} not found on {}",
+                partition,
+                "vda"
+            );
+            Ok(())
+        })();
+        let inline: Result<()> = (|| {
+            cheat_bail!(
+                protects = "p",
+                severity = "HIGH",
+                cheats = ["c"],
+                consequence = "q",
+                "Partition {partition} not found on {disk:>4}",
+                disk = "vda"
+            );
+            Ok(())
+        })();
+        // Named arguments that share a name with a bail option stay format arguments.
+        let named: Result<()> = (|| {
+            cheat_bail!(
+                profile = DISK_PARTITION,
+                remedy = "Rerun sfdisk",
+                "Partition {name} not found ({source}, {remedy})",
+                name = partition,
+                source = "sfdisk",
+                remedy = 2
+            );
+            Ok(())
+        })();
+
+        assert_eq!(bail_message(positional), "Partition vda1 not found on vda");
+        assert_eq!(bail_message(inline), "Partition vda1 not found on  vda");
+        assert_eq!(bail_message(named), "Partition vda1 not found (sfdisk, 2)");
+    }
+
+    #[test]
+    fn test_cheat_result_is_a_value() {
+        let _globals = lock_globals();
+        let output = "vda1";
+        let results = [
+            cheat_result!(
+                output.contains("vda1"),
+                protects = "Boot partition is created",
+                severity = "LOW",
+                cheats = ["Accept any output"],
+                consequence = "Nothing to install the bootloader into",
+                "vda1 not found in {output:?}"
+            ),
+            cheat_result!(
+                output.contains("vda2"),
+                protects = "Root partition is created",
+                severity = "LOW",
+                cheats = ["Check only the first partition"],
+                consequence = "Nowhere to install the system",
+                "vda2 not found in {output:?}"
+            ),
+        ];
+
+        assert!(results[0].is_ok());
+        let err = results[1].as_ref().unwrap_err();
+        let cheat = crate::CheatError::from_anyhow(err).unwrap();
+        assert_eq!(cheat.protects, "Root partition is created");
+        assert_eq!(cheat.message, "vda2 not found in \"vda1\"");
+        assert!(err.to_string().contains("=== CHEAT-GUARDED FAILURE ==="));
+    }
+
+    #[test]
+    fn test_cheat_result_does_not_return_early() {
+        fn count_failures() -> usize {
+            let mut failures = 0;
+            for n in 0..3 {
+                let result = cheat_result!(
+                    n != 1,
+                    protects = "Every disk is found",
+                    severity = crate::Severity::Low,
+                    cheats = [],
+                    consequence = "A disk is skipped",
+                    "disk {} missing",
+                    n
+                );
+                failures += usize::from(result.is_err());
+            }
+            failures
+        }
+
+        let _globals = lock_globals();
+        assert_eq!(count_failures(), 1);
+    }
+
     #[test]
     fn test_cheat_bail_format() {
+        let _env = EnvGuard::set(&[(crate::config::COLOR_ENV, "never")]);
         let result: Result<()> = (|| {
             cheat_bail!(
                 protects = "Test scenario",
@@ -258,6 +2310,7 @@ mod tests {
                 "Error: {} not found",
                 "thing"
             );
+            Ok(())
         })();
 
         let err = result.unwrap_err();
@@ -268,4 +2321,2033 @@ mod tests {
         assert!(msg.contains("2. Cheat 2"));
         assert!(msg.contains("Error: thing not found"));
     }
+
+    fn disk_meta() -> crate::CheatMeta {
+        crate::CheatMeta::builder()
+            .protects("Disk is partitioned")
+            .severity("CRITICAL")
+            .cheat("Accept any output")
+            .cheat("Skip verification")
+            .consequence("Installation fails")
+            .build()
+    }
+
+    /// The bail error of `result`, with its location cleared so banners from
+    /// different call sites compare equal.
+    fn unlocated(result: Result<()>) -> crate::CheatError {
+        let err = result.unwrap_err();
+        let mut cheat = err.downcast_ref::<crate::CheatError>().unwrap().clone();
+        assert!(cheat.location.is_some());
+        cheat.location = None;
+        cheat
+    }
+
+    #[test]
+    fn test_cheat_bail_meta_matches_inline() {
+        let _env = EnvGuard::unset(&[crate::config::FORMAT_ENV, crate::config::MIN_SEVERITY_ENV]);
+        let inline: Result<()> = (|| {
+            cheat_bail!(
+                protects = "Disk is partitioned",
+                severity = "CRITICAL",
+                cheats = ["Accept any output", "Skip verification"],
+                consequence = "Installation fails",
+                "{} not found",
+                "vda1"
+            );
+            Ok(())
+        })();
+        let meta = disk_meta();
+        let with_meta: Result<()> = (|| {
+            cheat_bail!(meta = meta, "{} not found", "vda1");
+            Ok(())
+        })();
+
+        assert_eq!(
+            unlocated(inline).to_string(),
+            unlocated(with_meta).to_string()
+        );
+    }
+
+    #[test]
+    fn test_cheat_ensure_meta_matches_inline() {
+        let _env = EnvGuard::unset(&[crate::config::FORMAT_ENV, crate::config::MIN_SEVERITY_ENV]);
+        let meta = disk_meta();
+        let inline: Result<()> = (|| {
+            cheat_ensure!(
+                false,
+                protects = "Disk is partitioned",
+                severity = "CRITICAL",
+                cheats = ["Accept any output", "Skip verification"],
+                consequence = "Installation fails",
+                "vda1 not found"
+            );
+            Ok(())
+        })();
+        let with_meta: Result<()> = (|| {
+            cheat_ensure!(true, meta = &meta, "never shown");
+            cheat_ensure!(false, meta = &meta, "vda1 not found");
+            Ok(())
+        })();
+
+        assert_eq!(
+            unlocated(inline).to_string(),
+            unlocated(with_meta).to_string()
+        );
+    }
+
+    cheat_profile!(
+        DISK_PARTITION,
+        protects = "Disk is partitioned",
+        severity = "CRITICAL",
+        cheats = ["Accept any output", "Skip verification"],
+        consequence = "Installation fails",
+    );
+
+    #[test]
+    fn test_cheat_profile_shared_by_call_sites() {
+        let _env = EnvGuard::unset(&[crate::config::MIN_SEVERITY_ENV]);
+        let plain: Result<()> = (|| {
+            cheat_bail!(profile = DISK_PARTITION, "vda1 not found");
+            Ok(())
+        })();
+        let extended: Result<()> = (|| {
+            cheat_ensure!(
+                false,
+                profile = DISK_PARTITION,
+                extra_cheats = ["Check only the first partition"],
+                "{} not found",
+                "vda2"
+            );
+            Ok(())
+        })();
+
+        let plain = unlocated(plain);
+        assert_eq!(plain.cheats, ["Accept any output", "Skip verification"]);
+        assert_eq!(plain.message, "vda1 not found");
+        let extended = unlocated(extended);
+        let repeated: Result<()> = (|| {
+            cheat_bail!(
+                profile = DISK_PARTITION,
+                extra_cheats = ["Skip verification "],
+                "vda3 not found"
+            );
+            Ok(())
+        })();
+        assert_eq!(unlocated(repeated).cheats, plain.cheats);
+        assert_eq!(
+            extended.cheats,
+            [
+                "Accept any output",
+                "Skip verification",
+                "Check only the first partition"
+            ]
+        );
+        assert_eq!(extended.message, "vda2 not found");
+        assert!(extended
+            .to_string()
+            .contains("  3. Check only the first partition"));
+        assert_eq!(DISK_PARTITION.cheats.len(), 2);
+    }
+
+    #[test]
+    fn test_cheat_bail_meta_with_source() {
+        let _globals = lock_globals();
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "no such device");
+        let result: Result<()> = (|| {
+            cheat_bail!(meta = disk_meta(), source = io, "sfdisk failed");
+            Ok(())
+        })();
+
+        let err = result.unwrap_err();
+        let cheat = crate::CheatError::from_anyhow(&err).unwrap();
+        assert_eq!(
+            std::error::Error::source(cheat).unwrap().to_string(),
+            "no such device"
+        );
+    }
+
+    const DISK_CHEATS: &[&str] = &["Accept any output", "Skip verification"];
+
+    #[test]
+    fn test_cheat_bail_accepts_const_slice() {
+        let _env = EnvGuard::unset(&[crate::config::FORMAT_ENV, crate::config::MIN_SEVERITY_ENV]);
+        let inline: Result<()> = (|| {
+            cheat_bail!(
+                protects = "Disk is partitioned",
+                severity = "HIGH",
+                cheats = ["Accept any output", "Skip verification"],
+                consequence = "Installation fails",
+                "vda1 not found"
+            );
+            Ok(())
+        })();
+        let from_const: Result<()> = (|| {
+            cheat_bail!(
+                protects = "Disk is partitioned",
+                severity = "HIGH",
+                cheats = DISK_CHEATS,
+                consequence = "Installation fails",
+                "vda1 not found"
+            );
+            Ok(())
+        })();
+
+        let from_const = unlocated(from_const);
+        assert!(from_const
+            .to_string()
+            .contains("CHEAT VECTORS:\n  1. Accept any output\n  2. Skip verification\n"));
+        assert_eq!(unlocated(inline).to_string(), from_const.to_string());
+    }
+
+    fn stubbed_step() -> Result<u32> {
+        cheat_todo!(
+            protects = "Root filesystem is mounted read-write",
+            severity = "HIGH",
+            cheats = ["Check the mount exists but not its flags"],
+            consequence = "Users cannot save files after install",
+            "Verify mount flags"
+        );
+        Ok(7)
+    }
+
+    #[test]
+    fn test_cheat_todo_lenient_continues() {
+        let _env = EnvGuard::unset(&[crate::config::STRICT_TODO_ENV]);
+        assert_eq!(stubbed_step().unwrap(), 7);
+    }
+
+    #[test]
+    fn test_cheat_todo_strict_bails() {
+        let _env = EnvGuard::set(&[(crate::config::STRICT_TODO_ENV, "1")]);
+        let err = stubbed_step().unwrap_err();
+        let cheat = crate::CheatError::from_anyhow(&err).unwrap();
+        assert_eq!(cheat.message, "Verify mount flags");
+        assert_eq!(cheat.severity, crate::Severity::High);
+    }
+
+    fn partition_size(actual_mb: f64) -> Result<()> {
+        cheat_assert_approx!(
+            actual_mb,
+            1000.0,
+            tolerance = 0.01,
+            protects = "Root partition has the requested size",
+            severity = "HIGH",
+            cheats = ["Compare against the size sfdisk reported back"],
+            consequence = "Root fills up early"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_cheat_assert_approx_within_tolerance() {
+        let _globals = lock_globals();
+        assert!(partition_size(1009.5).is_ok());
+        assert!(partition_size(990.0).is_ok());
+    }
+
+    #[test]
+    fn test_cheat_assert_approx_out_of_tolerance() {
+        let _env = EnvGuard::unset(&[crate::config::MIN_SEVERITY_ENV]);
+        let err = partition_size(1050.0).unwrap_err();
+        let cheat = crate::CheatError::from_anyhow(&err).unwrap();
+        assert_eq!(
+            cheat.message,
+            "expected: 1000 (relative tolerance 0.01)\nactual: 1050\nrelative error: 0.05"
+        );
+        assert_eq!(cheat.cheats.last().unwrap(), crate::ops::APPROX_CHEAT);
+        assert!(partition_size(f64::NAN).is_err());
+    }
+
+    fn free_memory(gib: u32) -> Result<()> {
+        cheat_assert_within!(
+            gib,
+            2..=4,
+            protects = "Live ISO leaves room for the installer",
+            severity = "MEDIUM",
+            cheats = ["Widen the range to whatever the VM has"],
+            consequence = "Installer is OOM-killed"
+        );
+        Ok(())
+    }
+
+    fn within_message(result: Result<()>) -> String {
+        crate::CheatError::from_anyhow(&result.unwrap_err())
+            .unwrap()
+            .message
+            .clone()
+    }
+
+    #[test]
+    fn test_cheat_assert_within_in_range() {
+        let _globals = lock_globals();
+        assert!(free_memory(2).is_ok());
+        assert!(free_memory(4).is_ok());
+    }
+
+    #[test]
+    fn test_cheat_assert_within_below_min() {
+        let _env = EnvGuard::unset(&[crate::config::MIN_SEVERITY_ENV]);
+        assert_eq!(
+            within_message(free_memory(1)),
+            "range: 2..=4\nvalue: 1 (below minimum 2)"
+        );
+    }
+
+    #[test]
+    fn test_cheat_assert_within_above_max() {
+        let _env = EnvGuard::unset(&[crate::config::MIN_SEVERITY_ENV]);
+        assert_eq!(
+            within_message(free_memory(8)),
+            "range: 2..=4\nvalue: 8 (above maximum 4)"
+        );
+    }
+
+    #[test]
+    fn test_cheat_bail_empty_cheats() {
+        let _env = EnvGuard::unset(&[crate::config::FORMAT_ENV, crate::config::MIN_SEVERITY_ENV]);
+        let result: Result<()> = (|| {
+            cheat_ensure!(
+                false,
+                protects = "Scaffolded bail",
+                severity = "HIGH",
+                cheats = [],
+                consequence = "Installation fails",
+                "not written yet"
+            );
+            Ok(())
+        })();
+
+        let cheat = unlocated(result);
+        assert!(cheat.cheats.is_empty());
+        assert!(cheat
+            .to_string()
+            .contains("\nCHEAT VECTORS:\n  (none documented)\n\nUSER CONSEQUENCE:\n"));
+    }
+
+    #[test]
+    fn test_cheat_check_empty_cheats_records_failure() {
+        let _globals = lock_globals();
+        let mut result = StepResult::new(1, "Test");
+        cheat_check!(
+            result,
+            name = "Scaffolded check",
+            condition = false,
+            protects = "Something",
+            severity = "LOW",
+            cheats = [],
+            consequence = "Nothing yet",
+            expected = "a",
+            actual = "b"
+        );
+        assert!(!result.all_passed());
+    }
+
+    #[test]
+    fn test_cheat_check_truncates_actual() {
+        let _env = EnvGuard::set(&[(crate::config::MAX_ACTUAL_ENV, "11")]);
+        let output = "sfdisk: 🚫🚫🚫 no partition table";
+        let mut result = StepResult::new(1, "Test");
+        cheat_check!(
+            result,
+            name = "Partition table created",
+            condition = false,
+            protects = "Disk is partitioned",
+            severity = "HIGH",
+            cheats = ["Accept any output"],
+            consequence = "Installation fails",
+            expected = "vda1",
+            actual = output
+        );
+
+        // "sfdisk: " is 8 bytes and each emoji is 4, so the cut lands before the first emoji.
+        let removed = output.len() - 8;
+        match &result.checks()[0].1 {
+            CheckResult::Fail { actual, .. } => {
+                assert_eq!(
+                    actual,
+                    &format!("sfdisk: \u{2026} ({} bytes truncated)", removed)
+                )
+            }
+            other => panic!("expected Fail, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_cheat_check_redacts_actual() {
+        let _globals = lock_globals();
+        crate::set_cheat_redactor(|text| text.replace("secret123", "***"));
+        let mut result = StepResult::new(1, "Test");
+        cheat_check!(
+            result,
+            name = "User can log in",
+            condition = false,
+            protects = "Login works",
+            severity = "HIGH",
+            cheats = ["Accept any output"],
+            consequence = "Users are locked out",
+            expected = "login ok",
+            actual = "su: wrong password secret123"
+        );
+        crate::reset_cheat_redactor();
+
+        match &result.checks()[0].1 {
+            CheckResult::Fail { actual, .. } => assert_eq!(actual, "su: wrong password ***"),
+            other => panic!("expected Fail, got {:?}", other),
+        }
+    }
+
+    #[derive(Debug)]
+    struct Partition {
+        name: &'static str,
+        size_mb: u32,
+    }
+
+    #[test]
+    fn test_cheat_check_debug_fields() {
+        let _globals = lock_globals();
+        let mut result = StepResult::new(1, "Test");
+        let count = 1;
+        let root = Partition {
+            name: "vda2",
+            size_mb: 512,
+        };
+        cheat_check!(
+            result,
+            name = "Partition count",
+            condition = count == 2,
+            protects = "Disk is partitioned",
+            severity = "HIGH",
+            cheats = ["Count any block device"],
+            consequence = "Installation fails",
+            expected = 2,
+            actual = count
+        );
+        cheat_check!(
+            result,
+            name = "Root partition",
+            condition = root.name == "vda2" && root.size_mb >= 8192,
+            protects = "Disk is partitioned",
+            severity = "HIGH",
+            cheats = ["Accept any size"],
+            consequence = "Root fills up",
+            expected = String::from("vda2 >= 8192 MB"),
+            actual = Partition {
+                name: "vda2",
+                size_mb: 512
+            }
+        );
+
+        let fails: Vec<_> = result
+            .checks()
+            .iter()
+            .map(|(_, check)| match check {
+                CheckResult::Fail { expected, actual } => (expected.as_str(), actual.as_str()),
+                other => panic!("expected Fail, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(fails[0], ("2", "1"));
+        assert_eq!(
+            fails[1],
+            (
+                "vda2 >= 8192 MB",
+                "Partition { name: \"vda2\", size_mb: 512 }"
+            )
+        );
+    }
+
+    #[test]
+    fn test_cheat_check_accepts_runtime_vec() {
+        let _globals = lock_globals();
+        let cheats: Vec<String> = (1..=3).map(|n| format!("Skip partition {}", n)).collect();
+        let mut result = StepResult::new(1, "Test");
+        cheat_check!(
+            result,
+            name = "Partitions exist",
+            condition = false,
+            protects = "Disk is partitioned",
+            severity = "HIGH",
+            cheats = &cheats,
+            consequence = "Installation fails",
+            expected = "3 partitions",
+            actual = "none"
+        );
+        let retried: Result<u32> = (|| {
+            Ok(cheat_retry!(
+                attempts = 1,
+                delay_ms = 0,
+                protects = "Disk is partitioned",
+                severity = "HIGH",
+                cheats = cheats,
+                consequence = "Installation fails",
+                || Err::<u32, _>("no disk")
+            ))
+        })();
+
+        assert!(!result.checks()[0].1.passed());
+        let cheat = unlocated(retried.map(drop));
+        assert_eq!(cheat.cheats.len(), 4);
+        assert_eq!(cheat.cheats[2], "Skip partition 3");
+        assert_eq!(cheat.cheats[3], crate::ops::RETRY_CHEAT);
+    }
+
+    #[test]
+    fn test_cheat_bail_error_is_cheat_error() {
+        let _globals = lock_globals();
+        let result: Result<()> = (|| {
+            cheat_bail!(
+                protects = "Test scenario",
+                severity = "MEDIUM",
+                cheats = ["Cheat 1", "Cheat 2"],
+                consequence = "Bad things happen",
+                "Error: {} not found",
+                "thing"
+            );
+            Ok(())
+        })();
+
+        let err = result.unwrap_err();
+        let cheat = err.downcast_ref::<crate::CheatError>().unwrap();
+        assert_eq!(cheat.protects, "Test scenario");
+        assert_eq!(cheat.severity, crate::Severity::Medium);
+        assert_eq!(cheat.cheats, ["Cheat 1", "Cheat 2"]);
+        assert_eq!(cheat.consequence, "Bad things happen");
+        assert_eq!(cheat.message, "Error: thing not found");
+        assert_eq!(err.to_string(), cheat.to_string());
+    }
+
+    #[test]
+    fn test_cheat_error_recovered_from_anyhow() {
+        let result: Result<()> = (|| {
+            cheat_bail!(
+                protects = "Test scenario",
+                severity = crate::Severity::Critical,
+                cheats = ["Skip verification", "Accept any output"],
+                consequence = "Bad things happen",
+                "boom"
+            );
+            Ok(())
+        })();
+
+        let err = result.unwrap_err();
+        assert!(crate::is_cheat_error(&err));
+        let cheat = crate::CheatError::from_anyhow(&err).unwrap();
+        assert_eq!(cheat.severity, crate::Severity::Critical);
+        assert_eq!(cheat.cheats, ["Skip verification", "Accept any output"]);
+    }
+
+    #[test]
+    fn test_cheat_error_into_anyhow_keeps_structure() {
+        let _env = EnvGuard::unset(&[crate::config::FORMAT_ENV]);
+        let cheat = crate::CheatError::new(
+            "Disk is partitioned",
+            crate::Severity::High,
+            ["Accept any output"],
+            "Installation fails",
+            "vda1 not found",
+        )
+        .with_remedy("Rerun sfdisk");
+        let banner = cheat.to_string();
+
+        let err = anyhow::Error::from(cheat.clone());
+        assert_eq!(err.to_string(), banner);
+        let back = err.downcast_ref::<crate::CheatError>().unwrap();
+        assert_eq!(back.protects, cheat.protects);
+        assert_eq!(back.severity, cheat.severity);
+        assert_eq!(back.remedy.as_deref(), Some("Rerun sfdisk"));
+
+        let propagated: Result<()> = (|| Err(cheat)?)();
+        assert!(crate::is_cheat_error(&propagated.unwrap_err()));
+    }
+
+    #[test]
+    fn test_cheat_bail_accepts_severity_enum() {
+        let _env = EnvGuard::set(&[(crate::config::COLOR_ENV, "never")]);
+        let result: Result<()> = (|| {
+            cheat_bail!(
+                protects = "Test scenario",
+                severity = crate::Severity::High,
+                cheats = ["Cheat 1"],
+                consequence = "Bad things happen",
+                "boom"
+            );
+            Ok(())
+        })();
+
+        let msg = result.unwrap_err().to_string();
+        assert!(msg.contains("\nSEVERITY: HIGH\n"));
+    }
+
+    #[test]
+    fn test_cheat_bail_rejects_unknown_severity() {
+        let result: Result<()> = (|| {
+            cheat_bail!(
+                protects = "Test scenario",
+                severity = "CRITCAL",
+                cheats = ["Cheat 1"],
+                consequence = "Bad things happen",
+                "boom"
+            );
+            Ok(())
+        })();
+
+        let err = result.unwrap_err();
+        assert!(err.downcast_ref::<crate::ParseSeverityError>().is_some());
+        assert!(!err.to_string().contains("CHEAT-GUARDED FAILURE"));
+    }
+
+    #[test]
+    fn test_cheat_bail_downgrades_below_min_severity() {
+        let _env = EnvGuard::set(&[(crate::config::MIN_SEVERITY_ENV, "HIGH")]);
+
+        let low: Result<()> = (|| {
+            cheat_bail!(
+                protects = "Test scenario",
+                severity = "LOW",
+                cheats = ["Cheat 1"],
+                consequence = "Minor annoyance",
+                "low failure"
+            );
+            Ok(())
+        })();
+        assert!(low.is_ok());
+
+        let high: Result<()> = (|| {
+            cheat_ensure!(
+                false,
+                protects = "Test scenario",
+                severity = "HIGH",
+                cheats = ["Cheat 1"],
+                consequence = "Bad things happen",
+                "high failure"
+            );
+            Ok(())
+        })();
+        assert!(high.unwrap_err().to_string().contains("high failure"));
+    }
+
+    fn warn_probe() -> Result<u32> {
+        cheat_warn!(
+            protects = "Disk layout is standard",
+            severity = "LOW",
+            cheats = ["Ignore extra partitions"],
+            consequence = "Odd layout goes unnoticed",
+            "found {} extra partitions",
+            2
+        );
+        Ok(7)
+    }
+
+    #[test]
+    fn test_cheat_warn_continues() {
+        let _env = EnvGuard::unset(&[crate::config::STRICT_ENV]);
+        assert_eq!(warn_probe().unwrap(), 7);
+    }
+
+    #[test]
+    fn test_cheat_warn_bails_under_strict() {
+        let _env = EnvGuard::set(&[
+            (crate::config::STRICT_ENV, "1"),
+            (crate::config::FORMAT_ENV, "human"),
+        ]);
+        let err = warn_probe().unwrap_err();
+
+        let cheat = crate::CheatError::from_anyhow(&err).unwrap();
+        assert_eq!(cheat.message, "found 2 extra partitions");
+        assert!(err.to_string().contains("=== CHEAT-GUARDED FAILURE ==="));
+    }
+
+    #[test]
+    fn test_strict_bails_below_min_severity() {
+        let _env = EnvGuard::set(&[
+            (crate::config::MIN_SEVERITY_ENV, "HIGH"),
+            (crate::config::STRICT_ENV, "true"),
+        ]);
+        let result: Result<()> = (|| {
+            cheat_bail!(
+                protects = "p",
+                severity = "LOW",
+                cheats = ["c"],
+                consequence = "q",
+                "low failure"
+            );
+            Ok(())
+        })();
+        assert!(result.unwrap_err().to_string().contains("low failure"));
+    }
+
+    #[test]
+    fn test_cheat_assert_eq_passes() -> Result<()> {
+        cheat_assert_eq!(
+            2 + 2,
+            4,
+            protects = "Arithmetic works",
+            severity = "LOW",
+            cheats = ["Compare a value with itself"],
+            consequence = "Wrong sums"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_cheat_assert_eq_bails_with_both_values() {
+        let _globals = lock_globals();
+        let mut calls = 0;
+        let result: Result<()> = (|| {
+            cheat_assert_eq!(
+                {
+                    calls += 1;
+                    vec!["vda1"]
+                },
+                vec!["vda1", "vda2"],
+                protects = "Both partitions were created",
+                severity = "CRITICAL",
+                cheats = ["Check >= 1 instead"],
+                consequence = "Missing partition"
+            );
+            Ok(())
+        })();
+
+        let msg = result.unwrap_err().to_string();
+        assert!(msg.contains("ERROR:\nexpected: [\"vda1\"]\nactual: [\"vda1\", \"vda2\"]\n"));
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_cheat_contains_present() -> Result<()> {
+        let output = String::from("vda1 vda2");
+        cheat_contains!(
+            output,
+            "vda1",
+            protects = "Disk is partitioned",
+            severity = "CRITICAL",
+            cheats = ["Skip partition check"],
+            consequence = "No partitions"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_cheat_contains_absent() {
+        let _globals = lock_globals();
+        let result: Result<()> = (|| {
+            cheat_contains!(
+                "vda disk",
+                String::from("vda1"),
+                protects = "Disk is partitioned",
+                severity = "CRITICAL",
+                cheats = ["Skip partition check"],
+                consequence = "No partitions"
+            );
+            Ok(())
+        })();
+
+        let msg = result.unwrap_err().to_string();
+        assert!(msg.contains("ERROR:\n\"vda1\" not found in: vda disk\n"));
+    }
+
+    #[test]
+    fn test_cheat_contains_truncates_long_haystack() {
+        let haystack = "x".repeat(500);
+        let result: Result<()> = (|| {
+            cheat_contains!(
+                haystack,
+                "vda1",
+                protects = "Disk is partitioned",
+                severity = "CRITICAL",
+                cheats = ["Skip partition check"],
+                consequence = "No partitions"
+            );
+            Ok(())
+        })();
+
+        let err = result.unwrap_err();
+        let cheat = crate::CheatError::from_anyhow(&err).unwrap();
+        let expected = format!("\"vda1\" not found in: {}...", "x".repeat(200));
+        assert_eq!(cheat.message, expected);
+    }
+
+    #[cfg(feature = "regex")]
+    fn check_partition_pattern(text: &str, pattern: &str) -> Result<()> {
+        cheat_matches!(
+            text,
+            pattern,
+            protects = "Disk is partitioned",
+            severity = "CRITICAL",
+            cheats = ["Match any output with .*"],
+            consequence = "No partitions"
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_cheat_matches_match() {
+        assert!(check_partition_pattern("vda1 vda2", r"vda\d+").is_ok());
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_cheat_matches_non_match() {
+        let err = check_partition_pattern("vda disk", r"vda\d+").unwrap_err();
+        let cheat = crate::CheatError::from_anyhow(&err).unwrap();
+        assert_eq!(
+            cheat.message,
+            "pattern \"vda\\\\d+\" did not match: vda disk"
+        );
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_cheat_matches_invalid_pattern() {
+        let err = check_partition_pattern("vda1", r"vda(\d+").unwrap_err();
+        assert!(!crate::is_cheat_error(&err));
+        assert!(err
+            .to_string()
+            .starts_with("invalid cheat_matches! pattern \"vda(\\\\d+\""));
+    }
+
+    #[test]
+    fn test_cheat_check_returns_condition() {
+        let mut step = StepResult::new(1, "Test step");
+        let mut evaluations = 0;
+
+        let passed = cheat_check!(
+            step,
+            name = "Partition table created",
+            condition = {
+                evaluations += 1;
+                true
+            },
+            protects = "Disk has correct partitions",
+            severity = "CRITICAL",
+            cheats = ["Accept any output"],
+            consequence = "No partitions",
+            expected = "vda1 exists",
+            actual = "vda1"
+        );
+        let failed = cheat_check!(
+            step,
+            name = "Swap created",
+            condition = {
+                evaluations += 1;
+                false
+            },
+            protects = "Swap is available",
+            severity = "LOW",
+            cheats = ["Skip swapon check"],
+            consequence = "No swap",
+            expected = "swap active",
+            actual = "no swap"
+        );
+
+        assert!(passed);
+        assert!(!failed);
+        assert_eq!(evaluations, 2);
+        assert!(step.checks()[0].1.passed());
+        assert!(!step.checks()[1].1.passed());
+    }
+
+    #[test]
+    fn test_cheat_skip_records_skip() {
+        let mut step = StepResult::new(1, "Test step");
+        cheat_skip!(
+            step,
+            name = "EFI partition mounted",
+            reason = "BIOS boot",
+            protects = "Bootloader can be installed",
+            severity = "CRITICAL",
+            cheats = ["Report BIOS on a UEFI machine"],
+            consequence = "System does not boot"
+        );
+
+        let (name, result) = &step.checks()[0];
+        assert_eq!(name, "EFI partition mounted");
+        assert!(matches!(result, CheckResult::Skip(reason) if reason == "BIOS boot"));
+        assert!(result.passed());
+    }
+
+    #[test]
+    fn test_cheat_check_warn_records_warning() {
+        let mut step = StepResult::new(1, "Test step");
+        let passed = cheat_check!(
+            step,
+            name = "Standard disk layout",
+            condition = false,
+            protects = "Disk layout matches the plan",
+            severity = "LOW",
+            cheats = ["Ignore extra partitions"],
+            consequence = "Odd layout goes unnoticed",
+            expected = "2 partitions",
+            actual = "3 partitions",
+            warn = true
+        );
+
+        assert!(!passed);
+        let result = &step.checks()[0].1;
+        assert!(result.passed());
+        assert!(result.warned());
+        assert!(matches!(
+            result,
+            CheckResult::Warn { message } if message == "expected: 2 partitions, actual: 3 partitions"
+        ));
+    }
+
+    #[test]
+    fn test_cheat_bail_reports_caller_location() {
+        let _globals = lock_globals();
+        let result: Result<()> = (|| {
+            cheat_bail!(
+                protects = "Test scenario",
+                severity = "CRITICAL",
+                cheats = ["Cheat 1"],
+                consequence = "Bad things happen",
+                "boom"
+            );
+            Ok(())
+        })();
+        let expected_line = line!() - 9;
+
+        let err = result.unwrap_err();
+        let location = crate::CheatError::from_anyhow(&err)
+            .unwrap()
+            .location
+            .unwrap();
+        assert_eq!(location.file, file!());
+        assert_eq!(location.line, expected_line);
+        assert!(err
+            .to_string()
+            .contains(&format!("\nLOCATION: src/lib.rs:{}:", expected_line)));
+    }
+
+    #[test]
+    fn test_cheat_bail_json_format() {
+        let _env = EnvGuard::set(&[(crate::config::FORMAT_ENV, "json")]);
+        let result: Result<()> = (|| {
+            cheat_bail!(
+                protects = "Test scenario",
+                severity = "HIGH",
+                cheats = ["Cheat 1", "Cheat 2"],
+                consequence = "Bad things happen",
+                "Error: {} not found",
+                "thing"
+            );
+            Ok(())
+        })();
+
+        let msg = result.unwrap_err().to_string();
+        let json: serde_json::Value = serde_json::from_str(&msg).unwrap();
+        assert_eq!(json["protects"], "Test scenario");
+        assert_eq!(json["severity"], "HIGH");
+        assert_eq!(json["cheats"], serde_json::json!(["Cheat 1", "Cheat 2"]));
+        assert_eq!(json["consequence"], "Bad things happen");
+        assert_eq!(json["error"], "Error: thing not found");
+        assert!(json["location"]
+            .as_str()
+            .unwrap()
+            .starts_with("src/lib.rs:"));
+        assert!(json["doc_url"].is_null());
+    }
+
+    #[test]
+    fn test_cheat_bail_doc_url() {
+        let _env = EnvGuard::set(&[
+            (crate::config::FORMAT_ENV, "human"),
+            (crate::config::COLOR_ENV, "never"),
+        ]);
+        let documented: Result<()> = (|| {
+            cheat_bail!(
+                meta = disk_meta(),
+                doc_url = "https://wiki.levitate.dev/cheats/disk",
+                "sfdisk failed"
+            );
+            Ok(())
+        })();
+        let undocumented: Result<()> = (|| {
+            cheat_bail!(meta = disk_meta(), "sfdisk failed");
+            Ok(())
+        })();
+
+        let err = documented.unwrap_err();
+        let cheat = crate::CheatError::from_anyhow(&err).unwrap();
+        assert!(err
+            .to_string()
+            .contains("\nSEE: https://wiki.levitate.dev/cheats/disk\n"));
+        let json: serde_json::Value = serde_json::from_str(&cheat.to_json()).unwrap();
+        assert_eq!(json["doc_url"], "https://wiki.levitate.dev/cheats/disk");
+
+        assert!(!undocumented.unwrap_err().to_string().contains("SEE:"));
+    }
+
+    #[test]
+    fn test_cheat_check_pass_skips_cheat_formatting() {
+        let calls = || crate::text::FORMAT_CHEATS_CALLS.with(|calls| calls.get());
+        let mut step = StepResult::new(1, "Test step");
+        let before = calls();
+
+        cheat_check!(
+            step,
+            name = "Many cheats",
+            condition = true,
+            protects = "Formatting is lazy",
+            severity = "LOW",
+            cheats = ["c1", "c2", "c3", "c4", "c5", "c6", "c7", "c8", "c9", "c10"],
+            consequence = "Wasted allocations",
+            expected = "pass",
+            actual = "pass"
+        );
+        assert_eq!(calls(), before);
+        // A pass records no cheat documentation, so nothing is copied for it.
+        let info = step.info(0).unwrap();
+        assert_eq!(info.protects, None);
+        assert_eq!(info.severity, None);
+        assert!(info.cheats.is_empty());
+
+        cheat_check!(
+            step,
+            name = "Many cheats",
+            condition = false,
+            protects = "Formatting is lazy",
+            severity = "LOW",
+            cheats = ["c1", "c2", "c3", "c4", "c5", "c6", "c7", "c8", "c9", "c10"],
+            consequence = "Wasted allocations",
+            expected = "pass",
+            actual = "fail"
+        );
+        assert!(calls() > before);
+    }
+
+    #[test]
+    fn test_cheat_check_pass_skips_failure_fields() {
+        let _globals = lock_globals();
+        let evaluated = std::cell::Cell::new(0);
+        let expensive = |text: &str| {
+            evaluated.set(evaluated.get() + 1);
+            text.to_string()
+        };
+        let mut step = StepResult::new(1, "Test step");
+
+        for condition in [true, false] {
+            cheat_check!(
+                step,
+                name = "Lazy fields",
+                condition = condition,
+                protects = "Passing checks are cheap",
+                severity = "LOW",
+                cheats = [expensive("Skip the check")],
+                consequence = expensive("Slow test runs"),
+                expected = "pass",
+                actual = expensive("sfdisk output")
+            );
+            if condition {
+                assert_eq!(evaluated.get(), 0);
+            }
+        }
+        assert_eq!(evaluated.get(), 3);
+    }
+
+    #[test]
+    fn test_cheat_ensure_pass_skips_failure_fields() {
+        let _globals = lock_globals();
+        let evaluated = std::cell::Cell::new(0);
+        let expensive = |text: &str| {
+            evaluated.set(evaluated.get() + 1);
+            text.to_string()
+        };
+        let ensure = |condition: bool| -> Result<()> {
+            cheat_ensure!(
+                condition,
+                protects = "Passing checks are cheap",
+                severity = "LOW",
+                cheats = [expensive("Skip the check")],
+                consequence = expensive("Slow test runs"),
+                "{}",
+                expensive("message")
+            );
+            Ok(())
+        };
+
+        assert!(ensure(true).is_ok());
+        assert_eq!(evaluated.get(), 0);
+        assert!(ensure(false).is_err());
+        assert_eq!(evaluated.get(), 3);
+    }
+
+    #[test]
+    fn test_cheat_check_times_slow_condition() {
+        let _env = EnvGuard::set(&[(crate::config::SLOW_ENV, "10")]);
+        let mut step = StepResult::new(1, "Test step");
+
+        cheat_check!(
+            step,
+            name = "Slow check",
+            condition = {
+                std::thread::sleep(std::time::Duration::from_millis(30));
+                true
+            },
+            protects = "Slow checks are visible",
+            severity = "LOW",
+            cheats = ["Make the check too slow to run"],
+            consequence = "Check is effectively skipped",
+            expected = "pass",
+            actual = "pass"
+        );
+
+        let elapsed = step.duration(0).expect("check was timed");
+        assert!(elapsed >= std::time::Duration::from_millis(30));
+        assert_eq!(
+            step.durations().collect::<Vec<_>>(),
+            [("Slow check", elapsed)]
+        );
+        let note = crate::output::slow_note(elapsed).expect("slow check is annotated");
+        assert!(note.trim_start().starts_with("(took "), "{note}");
+        assert_eq!(
+            crate::output::slow_note(std::time::Duration::from_millis(5)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_cheat_check_records_into_step_result() {
+        let mut result = StepResult::new(4, "Partition Disk");
+        let output = "vda1";
+
+        cheat_check!(
+            result,
+            name = "Partition table created",
+            condition = output.contains("vda1"),
+            protects = "Disk has correct partitions",
+            severity = "CRITICAL",
+            cheats = ["Accept any output"],
+            consequence = "No partitions",
+            expected = "Partition vda1 exists",
+            actual = format!("sfdisk output: {}", output)
+        );
+        cheat_check!(
+            result,
+            name = "Swap partition created",
+            condition = output.contains("vda2"),
+            protects = "Swap is available",
+            severity = "LOW",
+            cheats = ["Skip swap check"],
+            consequence = "No swap",
+            expected = "Partition vda2 exists",
+            actual = format!("sfdisk output: {}", output)
+        );
+
+        let checks = result.checks();
+        assert_eq!(checks.len(), 2);
+        assert_eq!(checks[0].0, "Partition table created");
+        assert!(matches!(&checks[0].1, CheckResult::Pass(m) if m == "Partition vda1 exists"));
+        assert_eq!(checks[1].0, "Swap partition created");
+        assert!(matches!(
+            &checks[1].1,
+            CheckResult::Fail { expected, actual }
+                if expected == "Partition vda2 exists" && actual == "sfdisk output: vda1"
+        ));
+    }
+
+    #[test]
+    fn test_cheat_panic_carries_banner() {
+        let _env = EnvGuard::set(&[
+            (crate::config::FORMAT_ENV, "human"),
+            (crate::config::COLOR_ENV, "never"),
+        ]);
+        let payload = std::panic::catch_unwind(|| {
+            cheat_panic!(
+                protects = "Disk is partitioned correctly",
+                severity = "CRITICAL",
+                cheats = ["Accept exit code without verification"],
+                consequence = "No partitions, installation fails silently",
+                "{} not found after sfdisk",
+                "vda1"
+            )
+        })
+        .unwrap_err();
+
+        let message = payload.downcast_ref::<String>().unwrap();
+        assert!(message.contains("=== CHEAT-GUARDED FAILURE ==="));
+        assert!(message.contains("PROTECTS: Disk is partitioned correctly\n"));
+        assert!(message.contains("SEVERITY: CRITICAL\n"));
+        assert!(message.contains("ERROR:\nvda1 not found after sfdisk\n"));
+    }
+
+    fn find_partition(table: &[&'static str], name: &str) -> Option<&'static str> {
+        table
+            .iter()
+            .copied()
+            .find(|partition| partition.ends_with(name))
+    }
+
+    fn root_partition(table: &[&'static str]) -> Result<&'static str> {
+        let root = cheat_ensure!(
+            find_partition(table, "root"),
+            meta = disk_meta(),
+            "no root partition in {:?}",
+            table
+        );
+        Ok(root)
+    }
+
+    fn partition_count(listing: std::io::Result<usize>) -> Result<usize> {
+        let count = cheat_ensure!(
+            listing,
+            protects = "Disk is partitioned correctly",
+            severity = "HIGH",
+            cheats = ["Treat an unreadable table as empty"],
+            consequence = "Installation proceeds on a broken disk",
+            "could not list partitions"
+        );
+        Ok(count)
+    }
+
+    #[test]
+    fn test_cheat_ensure_bool_arm_unchanged() {
+        let result: Result<()> = (|| {
+            let value: () = cheat_ensure!(
+                1 + 1 == 2,
+                protects = "Arithmetic works",
+                severity = "LOW",
+                cheats = ["Compare a value with itself"],
+                consequence = "Nothing adds up",
+                "math is broken"
+            );
+            Ok(value)
+        })();
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_cheat_ensure_option_returns_value_or_bails() {
+        let _globals = lock_globals();
+        assert_eq!(
+            root_partition(&["vda1-boot", "vda2-root"]).unwrap(),
+            "vda2-root"
+        );
+
+        let err = root_partition(&["vda1-boot"]).unwrap_err();
+        let cheat = crate::CheatError::from_anyhow(&err).unwrap();
+        assert_eq!(cheat.message, "no root partition in [\"vda1-boot\"]");
+        assert_eq!(cheat.protects, "Disk is partitioned");
+    }
+
+    #[test]
+    fn test_cheat_ensure_result_returns_value_or_bails() {
+        let _globals = lock_globals();
+        assert_eq!(partition_count(Ok(2)).unwrap(), 2);
+
+        let err = partition_count(Err(std::io::Error::other("EIO"))).unwrap_err();
+        let cheat = crate::CheatError::from_anyhow(&err).unwrap();
+        assert_eq!(cheat.message, "could not list partitions: EIO");
+        assert!(cheat.location.is_some());
+    }
+
+    #[test]
+    fn test_cheat_ensure_option_bails_even_when_downgraded() {
+        let _env = EnvGuard::set(&[(crate::config::MIN_SEVERITY_ENV, "CRITICAL")]);
+        assert!(partition_count(Err(std::io::Error::other("EIO"))).is_err());
+    }
+
+    fn read_table(table: std::io::Result<u32>) -> Result<u32> {
+        let partitions = cheat_expect!(
+            table,
+            protects = "Disk is partitioned correctly",
+            severity = "CRITICAL",
+            cheats = ["Treat an unreadable table as empty"],
+            consequence = "Installation proceeds on a broken disk",
+            "could not read the partition table of {}",
+            "/dev/vda"
+        );
+        Ok(partitions + 1)
+    }
+
+    #[test]
+    fn test_cheat_expect_ok_returns_value() {
+        assert_eq!(read_table(Ok(2)).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_cheat_expect_err_bails_with_inner_error() {
+        let _globals = lock_globals();
+        let mut evaluations = 0;
+        let result: Result<u32> = (|| {
+            Ok(cheat_expect!(
+                {
+                    evaluations += 1;
+                    Err::<u32, _>(std::io::Error::other("no such device"))
+                },
+                protects = "Disk is partitioned correctly",
+                severity = "HIGH",
+                cheats = [],
+                consequence = "Installation proceeds on a broken disk",
+                "could not read the partition table"
+            ))
+        })();
+
+        assert_eq!(evaluations, 1);
+        let err = result.unwrap_err();
+        let cheat = crate::CheatError::from_anyhow(&err).unwrap();
+        assert_eq!(
+            cheat.message,
+            "could not read the partition table: no such device"
+        );
+        assert!(err
+            .to_string()
+            .contains("ERROR:\ncould not read the partition table: no such device\n"));
+        assert!(read_table(Err(std::io::Error::other("EIO")))
+            .unwrap_err()
+            .to_string()
+            .contains("could not read the partition table of /dev/vda: EIO"));
+    }
+
+    fn flaky(succeed_on: u32) -> Result<u32> {
+        let mut calls = 0;
+        let value = cheat_retry!(
+            attempts = 3,
+            delay_ms = 0,
+            protects = "Device settles",
+            severity = "HIGH",
+            cheats = ["Treat a timeout as success"],
+            consequence = "Device missing after install",
+            || {
+                calls += 1;
+                if calls >= succeed_on {
+                    Ok(calls)
+                } else {
+                    Err(anyhow::anyhow!("attempt {} failed", calls))
+                }
+            }
+        );
+        Ok(value)
+    }
+
+    #[test]
+    fn test_cheat_retry_first_try() {
+        assert_eq!(flaky(1).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_cheat_retry_third_try() {
+        assert_eq!(flaky(3).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_cheat_retry_exhausted() {
+        let err = flaky(4).unwrap_err();
+        let cheat = crate::CheatError::from_anyhow(&err).unwrap();
+        assert_eq!(
+            cheat.message,
+            "failed after 3 attempts; last error: attempt 3 failed"
+        );
+        assert_eq!(
+            cheat.cheats,
+            [
+                "Treat a timeout as success",
+                "Increase attempts to force a pass"
+            ]
+        );
+    }
+
+    fn sleepy(sleep_ms: u64) -> Result<&'static str> {
+        let value = cheat_timeout!(
+            duration = std::time::Duration::from_millis(100),
+            protects = "Operation is fast",
+            severity = "MEDIUM",
+            cheats = ["Skip the slow step"],
+            consequence = "Users wait forever",
+            move || {
+                std::thread::sleep(std::time::Duration::from_millis(sleep_ms));
+                "done"
+            }
+        );
+        Ok(value)
+    }
+
+    #[test]
+    fn test_cheat_timeout_returns_value() {
+        assert_eq!(sleepy(0).unwrap(), "done");
+    }
+
+    #[test]
+    fn test_cheat_timeout_bails_when_slow() {
+        let err = sleepy(2_000).unwrap_err();
+        let cheat = crate::CheatError::from_anyhow(&err).unwrap();
+        assert_eq!(cheat.message, "operation did not finish within 100ms");
+        assert_eq!(
+            cheat.cheats.last().unwrap(),
+            "Raise the timeout instead of fixing slowness"
+        );
+    }
+
+    #[test]
+    fn test_wrapper_macros_take_bail_fields() {
+        let expected: Result<u32> = (|| {
+            Ok(cheat_expect!(
+                Err::<u32, _>(std::io::Error::other("EIO")),
+                consequence = "Installation proceeds on a broken disk",
+                severity = "HIGH",
+                remedy = "Check the disk is attached",
+                protects = "Disk is partitioned correctly",
+                meta = { "device" => "/dev/vda" },
+                cheats = ["Treat an unreadable table as empty"],
+                "could not read the partition table"
+            ))
+        })();
+        let retried: Result<u32> = (|| {
+            Ok(cheat_retry!(
+                attempts = 2,
+                delay_ms = 0,
+                profile = DISK_PARTITION,
+                doc_url = "https://example.com/disk",
+                || Err::<u32, _>("busy")
+            ))
+        })();
+        let timed: Result<()> = (|| {
+            cheat_timeout!(
+                duration = std::time::Duration::from_millis(10),
+                meta = disk_meta(),
+                title = "SLOW GUARD FAILURE",
+                move || std::thread::sleep(std::time::Duration::from_millis(2_000))
+            );
+            Ok(())
+        })();
+
+        let err = expected.unwrap_err();
+        let cheat = crate::CheatError::from_anyhow(&err).unwrap();
+        assert_eq!(cheat.remedy.as_deref(), Some("Check the disk is attached"));
+        assert_eq!(cheat.metadata, [("device".into(), "/dev/vda".into())]);
+        assert_eq!(cheat.message, "could not read the partition table: EIO");
+
+        let err = retried.unwrap_err();
+        let cheat = crate::CheatError::from_anyhow(&err).unwrap();
+        assert_eq!(cheat.protects, "Disk is partitioned");
+        assert_eq!(cheat.doc_url.as_deref(), Some("https://example.com/disk"));
+        assert_eq!(
+            cheat.cheats,
+            [
+                "Accept any output",
+                "Skip verification",
+                "Increase attempts to force a pass"
+            ]
+        );
+
+        let err = timed.unwrap_err();
+        let cheat = crate::CheatError::from_anyhow(&err).unwrap();
+        assert_eq!(cheat.title.as_deref(), Some("SLOW GUARD FAILURE"));
+        assert_eq!(
+            cheat.cheats.last().map(String::as_str),
+            Some(crate::__private::TIMEOUT_CHEAT)
+        );
+    }
+
+    #[test]
+    fn test_cheat_todo_reports_unknown_severity() {
+        let _env = EnvGuard::unset(&[crate::config::STRICT_TODO_ENV]);
+        let result: Result<()> = (|| {
+            cheat_todo!(
+                protects = "p",
+                severity = "CRTICAL",
+                cheats = ["c"],
+                consequence = "q",
+                "not written"
+            );
+            Ok(())
+        })();
+        let err = result.unwrap_err();
+        assert!(err.downcast_ref::<crate::ParseSeverityError>().is_some());
+    }
+
+    #[test]
+    fn test_cheat_bail_keeps_source() {
+        let result: Result<()> = (|| {
+            let io = std::io::Error::new(std::io::ErrorKind::NotFound, "/dev/vda missing");
+            cheat_ensure!(
+                false,
+                protects = "Disk is present",
+                severity = "CRITICAL",
+                cheats = ["Ignore open errors"],
+                consequence = "Nothing gets installed",
+                source = io,
+                "Could not open disk"
+            );
+            Ok(())
+        })();
+
+        let err = result.unwrap_err();
+        let source = err.source().unwrap();
+        let io = source.downcast_ref::<std::io::Error>().unwrap();
+        assert_eq!(io.kind(), std::io::ErrorKind::NotFound);
+
+        let _globals = lock_globals();
+        assert!(err
+            .to_string()
+            .contains("ERROR:\nCould not open disk\n\nCAUSE:\n/dev/vda missing\n"));
+    }
+
+    #[test]
+    fn test_cheat_bail_remedy() {
+        let _globals = lock_globals();
+        let io = std::io::Error::other("device busy");
+        let with_remedy: Result<()> = (|| {
+            cheat_ensure!(
+                false,
+                protects = "Disk is partitioned",
+                severity = "CRITICAL",
+                cheats = ["Skip verification"],
+                consequence = "Installation fails",
+                remedy = "run sfdisk --reset and retry",
+                source = io,
+                "sfdisk failed"
+            );
+            Ok(())
+        })();
+        let without: Result<()> = (|| {
+            cheat_bail!(meta = disk_meta(), "sfdisk failed");
+            Ok(())
+        })();
+
+        let err = with_remedy.unwrap_err();
+        let cheat = crate::CheatError::from_anyhow(&err).unwrap();
+        assert_eq!(
+            cheat.remedy.as_deref(),
+            Some("run sfdisk --reset and retry")
+        );
+        assert!(cheat.source.is_some());
+        assert!(err.to_string().contains(
+            "USER CONSEQUENCE:\nInstallation fails\n\n\
+             SUGGESTED FIX:\nrun sfdisk --reset and retry\n\nERROR:\n"
+        ));
+
+        let err = without.unwrap_err();
+        assert!(!err.to_string().contains("SUGGESTED FIX:"));
+        assert!(crate::CheatError::from_anyhow(&err)
+            .unwrap()
+            .remedy
+            .is_none());
+    }
+
+    #[test]
+    fn test_cheat_check_accepts_remedy() {
+        let _env = check_banner_env();
+        let buf = SharedBuf::default();
+        crate::set_cheat_writer(Box::new(buf.clone()));
+        let mut step = StepResult::new(1, "Test step");
+        let passed = cheat_check!(
+            step,
+            name = "Partition table created",
+            condition = false,
+            protects = "Disk has correct partitions",
+            severity = "LOW",
+            cheats = ["Accept any output"],
+            consequence = "Installation fails",
+            expected = "vda1",
+            actual = "nothing",
+            remedy = "run sfdisk --reset and retry",
+            warn = true,
+            doc_url = "https://wiki.levitate.dev/cheats/disk",
+        );
+        crate::reset_cheat_writer();
+
+        assert!(!passed);
+        assert!(step.checks()[0].1.warned());
+        let captured = buf.contents();
+        assert!(
+            captured.contains(
+                "CONSEQUENCE: Installation fails\nSUGGESTED FIX: run sfdisk --reset and retry\n"
+            ),
+            "{captured}"
+        );
+    }
+
+    #[test]
+    fn test_cheat_bail_fields_in_any_order() {
+        let _globals = lock_globals();
+        let documented: Result<()> = (|| {
+            cheat_bail!(
+                protects = "Disk is partitioned",
+                severity = "HIGH",
+                cheats = ["Accept any output", "Skip verification"],
+                consequence = "Installation fails",
+                remedy = "Rerun sfdisk",
+                "{} not found",
+                "vda1"
+            );
+            Ok(())
+        })();
+        let shuffled: Result<()> = (|| {
+            cheat_ensure!(
+                false,
+                remedy = "Rerun sfdisk",
+                consequence = "Installation fails",
+                cheats = ["Accept any output", "Skip verification"],
+                severity = "HIGH",
+                protects = "Disk is partitioned",
+                "{} not found",
+                "vda1"
+            );
+            Ok(())
+        })();
+
+        let bailed: Result<()> = (|| {
+            cheat_bail!(
+                severity = "HIGH",
+                cheats = ["Accept any output", "Skip verification"],
+                remedy = "Rerun sfdisk",
+                protects = "Disk is partitioned",
+                consequence = "Installation fails",
+                "{} not found",
+                "vda1"
+            );
+            Ok(())
+        })();
+
+        let banner = unlocated(documented).to_string();
+        assert!(banner.contains("PROTECTS: Disk is partitioned\n"));
+        assert_eq!(unlocated(shuffled).to_string(), banner);
+        assert_eq!(unlocated(bailed).to_string(), banner);
+    }
+
+    #[test]
+    fn test_cheat_check_fields_in_any_order() {
+        let _globals = lock_globals();
+        let mut documented = StepResult::new(1, "Test step");
+        cheat_check!(
+            documented,
+            name = "Partition table created",
+            condition = false,
+            protects = "Disk has correct partitions",
+            severity = "LOW",
+            cheats = ["Accept any output"],
+            consequence = "Installation fails",
+            expected = "vda1",
+            actual = "nothing",
+        );
+        let mut shuffled = StepResult::new(1, "Test step");
+        cheat_check!(
+            shuffled,
+            actual = "nothing",
+            expected = "vda1",
+            severity = "LOW",
+            condition = false,
+            consequence = "Installation fails",
+            name = "Partition table created",
+            cheats = ["Accept any output"],
+            protects = "Disk has correct partitions"
+        );
+
+        assert_eq!(documented.checks(), shuffled.checks());
+    }
+
+    #[test]
+    fn test_cheat_bail_computed_protects_and_consequence() {
+        let _globals = lock_globals();
+        let disk = String::from("vda");
+        let protects = format!("Disk {} is partitioned", disk);
+        let consequence: &str = "Installation fails";
+        let owned: Result<()> = (|| {
+            cheat_bail!(
+                protects = protects,
+                severity = "HIGH",
+                cheats = ["Skip verification"],
+                consequence = consequence,
+                "{}1 not found",
+                disk
+            );
+            Ok(())
+        })();
+        let borrowed: Result<()> = (|| {
+            cheat_ensure!(
+                disk.is_empty(),
+                protects = &protects,
+                severity = "HIGH",
+                cheats = ["Skip verification"],
+                consequence = consequence.to_string(),
+                "{}1 not found",
+                disk
+            );
+            Ok(())
+        })();
+        let formatted: Result<()> = (|| {
+            cheat_bail!(
+                protects = format!("Disk {} is partitioned", disk),
+                severity = "HIGH",
+                cheats = ["Skip verification"],
+                consequence = format!("{} {}", "Installation", "fails"),
+                "{}1 not found",
+                disk
+            );
+            Ok(())
+        })();
+
+        let owned = unlocated(owned);
+        assert_eq!(owned.protects, "Disk vda is partitioned");
+        assert_eq!(owned.consequence, "Installation fails");
+        assert_eq!(unlocated(borrowed).to_string(), owned.to_string());
+        assert_eq!(unlocated(formatted).to_string(), owned.to_string());
+    }
+
+    #[test]
+    fn test_cheat_check_computed_protects_and_consequence() {
+        let _globals = lock_globals();
+        let disk = String::from("vda");
+        let protects = format!("Disk {} is partitioned", disk);
+        let consequence: &str = "Installation fails";
+        let mut step = StepResult::new(1, "Test step");
+        cheat_check!(
+            step,
+            name = "Owned",
+            condition = false,
+            protects = protects,
+            severity = "LOW",
+            cheats = ["Accept any output"],
+            consequence = consequence,
+            expected = "vda1",
+            actual = "nothing",
+            warn = true
+        );
+        cheat_check!(
+            step,
+            name = "Formatted",
+            condition = false,
+            protects = format!("Disk {} is partitioned", disk.as_str()),
+            severity = "LOW",
+            cheats = ["Accept any output"],
+            consequence = format!("{} {}", "Installation", "fails"),
+            expected = "vda1",
+            actual = "nothing",
+            warn = true
+        );
+
+        let checks = step.checks();
+        assert_eq!(checks[0].1, checks[1].1);
+        // The macro only borrowed the local.
+        assert_eq!(protects, "Disk vda is partitioned");
+    }
+
+    #[test]
+    fn test_cheat_bail_metadata() {
+        let _env = EnvGuard::unset(&[crate::config::FORMAT_ENV, crate::config::MIN_SEVERITY_ENV]);
+        let device = "/dev/vda";
+        let tagged: Result<()> = (|| {
+            cheat_bail!(
+                meta = { "device" => device, "cmd" => "sfdisk", "attempt" => 2 },
+                protects = "Disk is partitioned",
+                severity = "HIGH",
+                cheats = ["Skip verification"],
+                consequence = "Installation fails",
+                "sfdisk failed"
+            );
+            Ok(())
+        })();
+        let shared: Result<()> = (|| {
+            cheat_ensure!(
+                false,
+                meta = { "device" => device },
+                meta = disk_meta(),
+                "sfdisk failed"
+            );
+            Ok(())
+        })();
+
+        let tagged = unlocated(tagged);
+        assert_eq!(
+            tagged.metadata,
+            [
+                ("device".to_string(), "/dev/vda".to_string()),
+                ("cmd".to_string(), "sfdisk".to_string()),
+                ("attempt".to_string(), "2".to_string()),
+            ]
+        );
+        assert!(tagged.to_string().contains(
+            "METADATA:\n  device:  /dev/vda\n  cmd:     sfdisk\n  attempt: 2\n\nERROR:\n"
+        ));
+        let json: serde_json::Value = serde_json::from_str(&tagged.to_json()).unwrap();
+        assert_eq!(
+            json["metadata"],
+            serde_json::json!({"device": "/dev/vda", "cmd": "sfdisk", "attempt": "2"})
+        );
+
+        let shared = unlocated(shared);
+        assert_eq!(shared.protects, "Disk is partitioned");
+        assert_eq!(
+            shared.metadata,
+            [("device".to_string(), "/dev/vda".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_cheat_check_metadata() {
+        let _env = check_banner_env();
+        let buf = SharedBuf::default();
+        crate::set_cheat_writer(Box::new(buf.clone()));
+        let mut step = StepResult::new(1, "Test step");
+        let passed = cheat_check!(
+            step,
+            name = "Partition table created",
+            condition = false,
+            meta = { "device" => "/dev/vda", "cmd" => "sfdisk" },
+            protects = "Disk has correct partitions",
+            severity = "LOW",
+            cheats = ["Accept any output"],
+            consequence = "Installation fails",
+            expected = "vda1",
+            actual = "nothing",
+        );
+        crate::reset_cheat_writer();
+
+        assert!(!passed);
+        let captured = buf.contents();
+        assert!(
+            captured.contains(
+                "CONSEQUENCE: Installation fails\nMETADATA:\n  device: /dev/vda\n  cmd:    sfdisk\n"
+            ),
+            "{captured}"
+        );
+    }
+
+    #[test]
+    fn test_cheat_group_runs_every_check() {
+        let _globals = lock_globals();
+        let mut step = StepResult::new(4, "Partition Disk");
+        let output = "vda1 vda3";
+        let failures = cheat_group!(step, [
+            {
+                name = "vda1 exists",
+                condition = output.contains("vda1"),
+                protects = "Boot partition is created",
+                severity = "LOW",
+                cheats = ["Accept any output"],
+                consequence = "Nothing to install the bootloader into",
+                expected = "vda1",
+                actual = output,
+            },
+            {
+                name = "vda2 exists",
+                condition = output.contains("vda2"),
+                protects = "Root partition is created",
+                severity = "LOW",
+                cheats = ["Check only the first partition"],
+                consequence = "Nowhere to install the system",
+                expected = "vda2",
+                actual = output,
+            },
+            {
+                name = "vda3 exists",
+                condition = output.contains("vda3"),
+                protects = "Swap partition is created",
+                severity = "LOW",
+                cheats = ["Accept any output"],
+                consequence = "Out of memory under load",
+                expected = "vda3",
+                actual = output,
+            },
+        ]);
+
+        assert_eq!(failures, 1);
+        let names: Vec<&str> = step
+            .checks()
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(names, ["vda1 exists", "vda2 exists", "vda3 exists"]);
+        assert!(!step.checks()[1].1.passed());
+    }
+
+    #[test]
+    fn test_cheat_check_captured_output() {
+        let _globals = lock_globals();
+        let mut step = StepResult::new(4, "Partition Disk");
+        let stdout = String::from("Device     Start\n/dev/vda1   2048\n");
+        cheat_check!(
+            step,
+            name = "vda2 exists",
+            condition = stdout.contains("vda2"),
+            protects = "Root partition is created",
+            severity = "LOW",
+            cheats = ["Check only the first partition"],
+            consequence = "Nowhere to install the system",
+            expected = "vda2",
+            actual = "only vda1",
+            stdout = &stdout,
+            stderr = "sfdisk: partition 2 skipped",
+        );
+
+        assert_eq!(step.checks()[0].1.as_fail(), Some(("vda2", "only vda1")));
+        let info = step.info(0).unwrap();
+        assert_eq!(info.stdout.as_deref(), Some(stdout.as_str()));
+        assert_eq!(info.stderr.as_deref(), Some("sfdisk: partition 2 skipped"));
+    }
+
+    #[test]
+    fn test_cheat_check_category_summary() {
+        let _globals = lock_globals();
+        let mut step = StepResult::new(1, "Install");
+        cheat_check!(
+            step,
+            name = "Partition table created",
+            category = "disk",
+            condition = true,
+            protects = "Disk has correct partitions",
+            severity = "LOW",
+            cheats = ["Accept any output"],
+            consequence = "Installation fails",
+            expected = "vda1",
+            actual = "vda1",
+        );
+        cheat_check!(
+            step,
+            name = "Swap created",
+            condition = false,
+            protects = "Swap is usable",
+            severity = "LOW",
+            cheats = ["Accept any output"],
+            consequence = "Out of memory under load",
+            expected = "vda2",
+            actual = "nothing",
+            category = "disk"
+        );
+        cheat_skip!(
+            step,
+            name = "DHCP lease",
+            reason = "offline install",
+            protects = "Network comes up",
+            severity = "MEDIUM",
+            cheats = ["Skip network checks everywhere"],
+            consequence = "No network after install",
+            category = "network",
+        );
+        cheat_check!(
+            step,
+            name = "Untagged",
+            condition = true,
+            protects = "p",
+            severity = "LOW",
+            cheats = ["c"],
+            consequence = "q",
+            expected = "e",
+            actual = "e"
+        );
+
+        assert_eq!(step.category(2), Some("network"));
+        assert_eq!(step.category(3), None);
+        let mut report = crate::TestReport::new();
+        report.add_step(step);
+        let summary = report.category_summary();
+        assert_eq!(summary.keys().collect::<Vec<_>>(), ["disk", "network"]);
+        assert_eq!((summary["disk"].passed, summary["disk"].failed), (1, 1));
+        assert_eq!(
+            (summary["network"].total, summary["network"].skipped),
+            (1, 1)
+        );
+    }
+
+    #[test]
+    fn test_cheat_check_records_failure_severity() {
+        let _globals = lock_globals();
+        let mut step = StepResult::new(1, "Install");
+        for (severity, condition) in [("CRITICAL", true), ("HIGH", false), ("LOW", false)] {
+            cheat_check!(
+                step,
+                name = severity,
+                condition = condition,
+                protects = "Disk has correct partitions",
+                severity = severity,
+                cheats = ["Accept any output"],
+                consequence = "Installation fails",
+                expected = "vda1",
+                actual = "nothing"
+            );
+        }
+
+        assert_eq!(step.info(0).unwrap().severity, None);
+        assert_eq!(step.info(1).unwrap().severity, Some(crate::Severity::High));
+        let mut report = crate::TestReport::new();
+        report.add_step(step);
+        assert_eq!(report.suggested_exit_code(), 3);
+    }
+
+    #[cfg(feature = "catalog")]
+    cheat_profile!(
+        BOOTLOADER_INSTALLED,
+        protects = "Bootloader is installed",
+        severity = crate::Severity::High,
+        cheats = ["Check only that the ESP is mounted"],
+        consequence = "System does not boot",
+    );
+
+    cheat_doc!(
+        id = "TEST-DOC-001",
+        protects = "Partition table is GPT",
+        severity = "HIGH",
+        cheats = ["Construct the layout without checking the label"],
+        consequence = "UEFI firmware cannot find the ESP",
+    );
+
+    #[cfg(feature = "catalog")]
+    const GPT_CHEATS: &[&str] = &["Write an MBR label", "Skip the label check"];
+
+    #[cfg(feature = "catalog")]
+    cheat_doc!(
+        id = "TEST-DOC-002",
+        protects = "Partition table is GPT on every disk",
+        severity = "HIGH",
+        cheats = GPT_CHEATS,
+        consequence = "UEFI firmware cannot find the ESP",
+    );
+
+    #[test]
+    fn test_cheat_doc_is_a_statement() {
+        cheat_doc!(
+            protects = "Mount options are applied",
+            severity = crate::Severity::Low,
+            cheats = [],
+            consequence = "Filesystem is mounted read-write",
+        );
+        let result = StepResult::new(1, "Doc");
+        assert!(result.checks().is_empty());
+    }
+
+    #[cfg(feature = "catalog")]
+    #[test]
+    fn test_cheat_doc_is_in_catalog() {
+        let catalog = crate::cheat_catalog();
+        let doc = catalog
+            .iter()
+            .find(|entry| entry.id() == Some("TEST-DOC-001"))
+            .expect("cheat_doc! entry is not in the catalog");
+        assert_eq!(doc.protects(), "Partition table is GPT");
+        assert_eq!(doc.severity(), crate::Severity::High);
+        assert_eq!(
+            doc.cheats(),
+            ["Construct the layout without checking the label"]
+        );
+        assert!(catalog
+            .iter()
+            .any(|entry| entry.protects() == "Mount options are applied"));
+        let shared = catalog
+            .iter()
+            .find(|entry| entry.id() == Some("TEST-DOC-002"))
+            .expect("cheat_doc! with a const cheats slice is not in the catalog");
+        assert_eq!(shared.cheats(), GPT_CHEATS);
+    }
+
+    #[cfg(feature = "catalog")]
+    #[test]
+    fn test_cheat_catalog_lists_profiles() {
+        let catalog = crate::cheat_catalog();
+        let find = |protects: &str| {
+            catalog
+                .iter()
+                .find(|entry| entry.protects() == protects)
+                .unwrap_or_else(|| panic!("{protects:?} is not in the catalog"))
+        };
+
+        let disk = find("Disk is partitioned");
+        assert_eq!(disk.severity(), crate::Severity::Critical);
+        assert_eq!(disk.cheats(), ["Accept any output", "Skip verification"]);
+        assert_eq!(disk.location().file, file!());
+        let boot = find("Bootloader is installed");
+        assert_eq!(boot.severity(), crate::Severity::High);
+        assert_eq!(boot.consequence(), "System does not boot");
+        assert!(disk.location().line < boot.location().line);
+    }
 }
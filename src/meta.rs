@@ -0,0 +1,201 @@
+//! Reusable cheat documentation shared by several checks.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::severity::{IntoSeverity, ParseSeverityError};
+use crate::{CheatError, Severity};
+
+/// The protects/severity/cheats/consequence block of a cheat-guarded check.
+///
+/// Define one per subsystem and pass it as `meta = ...` to [`cheat_bail!`]
+/// or [`cheat_ensure!`] instead of repeating the four fields.
+///
+/// [`cheat_bail!`]: crate::cheat_bail
+/// [`cheat_ensure!`]: crate::cheat_ensure
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let disk = CheatMeta::builder()
+///     .protects("Disk is partitioned correctly")
+///     .severity("CRITICAL")
+///     .cheat("Accept any sfdisk output")
+///     .cheat("Skip verification")
+///     .consequence("No partitions, installation fails")
+///     .build();
+///
+/// cheat_ensure!(has_vda1, meta = disk, "vda1 not found");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheatMeta {
+    /// What user scenario the check protects.
+    pub protects: String,
+    /// How bad it is for users if the check is cheated.
+    pub severity: Severity,
+    /// Ways the check could be cheated.
+    pub cheats: Vec<String>,
+    /// What users experience if the check is cheated.
+    pub consequence: String,
+}
+
+impl CheatMeta {
+    pub fn builder() -> CheatMetaBuilder {
+        CheatMetaBuilder::default()
+    }
+
+    /// A copy with `cheats` appended after this metadata's own cheats.
+    pub fn with_extra_cheats(
+        &self,
+        cheats: impl IntoIterator<Item = impl Into<String>>,
+    ) -> CheatMeta {
+        let mut meta = self.clone();
+        meta.cheats.extend(cheats.into_iter().map(Into::into));
+        meta
+    }
+
+    /// A [`CheatError`] carrying this documentation and `message`.
+    pub fn to_error(&self, message: impl Into<String>) -> CheatError {
+        CheatError::new(
+            self.protects.clone(),
+            self.severity,
+            self.cheats.iter().cloned(),
+            self.consequence.clone(),
+            message,
+        )
+    }
+}
+
+/// Builder returned by [`CheatMeta::builder`].
+#[derive(Debug, Clone, Default)]
+pub struct CheatMetaBuilder {
+    protects: Option<String>,
+    severity: Option<Result<Severity, ParseSeverityError>>,
+    cheats: Vec<String>,
+    consequence: Option<String>,
+}
+
+impl CheatMetaBuilder {
+    pub fn protects(mut self, protects: impl Into<String>) -> Self {
+        self.protects = Some(protects.into());
+        self
+    }
+
+    /// A [`Severity`], or one of "CRITICAL", "HIGH", "MEDIUM", "LOW".
+    pub fn severity(mut self, severity: impl IntoSeverity) -> Self {
+        self.severity = Some(severity.into_severity());
+        self
+    }
+
+    /// Add one cheat vector; call once per cheat, in banner order.
+    pub fn cheat(mut self, cheat: impl Into<String>) -> Self {
+        self.cheats.push(cheat.into());
+        self
+    }
+
+    /// Add several cheat vectors at once, e.g. from a `const &[&str]`.
+    pub fn cheats<I>(mut self, cheats: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.cheats
+            .extend(cheats.into_iter().map(|cheat| cheat.as_ref().to_string()));
+        self
+    }
+
+    pub fn consequence(mut self, consequence: impl Into<String>) -> Self {
+        self.consequence = Some(consequence.into());
+        self
+    }
+
+    /// # Panics
+    ///
+    /// If `protects`, `severity`, or `consequence` was never set, or the
+    /// severity name is unknown. Metadata is fixed at the definition site, so
+    /// this is a programming error rather than a test failure.
+    pub fn build(self) -> CheatMeta {
+        let severity = match self.severity {
+            Some(severity) => severity.unwrap_or_else(|err| panic!("{}", err)),
+            None => panic!("CheatMeta::builder() is missing `severity`"),
+        };
+        CheatMeta {
+            protects: self
+                .protects
+                .expect("CheatMeta::builder() is missing `protects`"),
+            severity,
+            cheats: self.cheats,
+            consequence: self
+                .consequence
+                .expect("CheatMeta::builder() is missing `consequence`"),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    fn disk() -> CheatMeta {
+        CheatMeta::builder()
+            .protects("Disk is partitioned")
+            .severity("critical")
+            .cheat("Accept any output")
+            .cheat("Skip verification")
+            .consequence("Installation fails")
+            .build()
+    }
+
+    #[test]
+    fn test_builder_collects_fields() {
+        let meta = disk();
+        assert_eq!(meta.protects, "Disk is partitioned");
+        assert_eq!(meta.severity, Severity::Critical);
+        assert_eq!(meta.cheats, ["Accept any output", "Skip verification"]);
+        assert_eq!(meta.consequence, "Installation fails");
+    }
+
+    #[test]
+    fn test_to_error_copies_fields() {
+        let err = disk().to_error("vda1 not found");
+        assert_eq!(err.protects, "Disk is partitioned");
+        assert_eq!(err.cheats, ["Accept any output", "Skip verification"]);
+        assert_eq!(err.message, "vda1 not found");
+        assert!(err.location.is_none());
+    }
+
+    #[test]
+    fn test_with_extra_cheats_appends() {
+        let base = disk();
+        let meta = base.with_extra_cheats(["Mount whatever is there"]);
+        assert_eq!(
+            meta.cheats,
+            [
+                "Accept any output",
+                "Skip verification",
+                "Mount whatever is there"
+            ]
+        );
+        assert_eq!(base.cheats.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "missing `consequence`")]
+    fn test_build_panics_on_missing_field() {
+        CheatMeta::builder()
+            .protects("p")
+            .severity(Severity::Low)
+            .cheat("c")
+            .build();
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid cheat severity")]
+    fn test_build_panics_on_unknown_severity() {
+        CheatMeta::builder()
+            .protects("p")
+            .severity("SEVERE")
+            .consequence("q")
+            .build();
+    }
+}
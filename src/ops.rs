@@ -0,0 +1,213 @@
+//! Runtime support for [`cheat_retry!`](crate::cheat_retry),
+//! [`cheat_timeout!`](crate::cheat_timeout),
+//! [`cheat_assert_approx!`](crate::cheat_assert_approx), and
+//! [`cheat_ensure!`](crate::cheat_ensure).
+
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use crate::error::{bail_error, BailError};
+use crate::output::{report_bail_failure, report_failure};
+use crate::redact::redact;
+use crate::CheatError;
+
+/// Cheat vector appended to every [`cheat_retry!`](crate::cheat_retry) banner.
+pub const RETRY_CHEAT: &str = "Increase attempts to force a pass";
+
+/// Run `op` up to `attempts` times (at least once), sleeping `delay_ms`
+/// between tries. On exhaustion, returns the attempt count and the last
+/// error rendered with `{:#}`.
+pub fn retry<T, E, F>(attempts: u32, delay_ms: u64, mut op: F) -> Result<T, (u32, String)>
+where
+    F: FnMut() -> Result<T, E>,
+    E: Display,
+{
+    let attempts = attempts.max(1);
+    let mut attempt = 1;
+    loop {
+        match op() {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt >= attempts => return Err((attempt, format!("{:#}", err))),
+            Err(_) => {
+                attempt += 1;
+                thread::sleep(Duration::from_millis(delay_ms));
+            }
+        }
+    }
+}
+
+/// Cheat vector appended to every [`cheat_timeout!`](crate::cheat_timeout) banner.
+pub const TIMEOUT_CHEAT: &str = "Raise the timeout instead of fixing slowness";
+
+/// Run `op` on a worker thread and wait up to `timeout` for its value.
+///
+/// Returns `Err(timeout)` if the deadline passes; the worker is left to
+/// finish in the background. A panic in `op` is resumed on this thread.
+pub fn run_with_timeout<T, F>(timeout: Duration, op: F) -> Result<T, Duration>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel();
+    let worker = thread::spawn(move || {
+        // The receiver is gone if we already timed out.
+        let _ = tx.send(op());
+    });
+    match rx.recv_timeout(timeout) {
+        Ok(value) => Ok(value),
+        Err(mpsc::RecvTimeoutError::Timeout) => Err(timeout),
+        Err(mpsc::RecvTimeoutError::Disconnected) => match worker.join() {
+            Err(panic) => std::panic::resume_unwind(panic),
+            Ok(()) => unreachable!("worker exited without sending a value"),
+        },
+    }
+}
+
+/// Cheat vector appended to every [`cheat_assert_approx!`](crate::cheat_assert_approx) banner.
+pub const APPROX_CHEAT: &str = "Widen the tolerance until it passes";
+
+/// The relative error of `actual` if it exceeds `tolerance` (or is NaN).
+pub fn approx_error(actual: f64, expected: f64, tolerance: f64) -> Option<f64> {
+    let error = relative_error(actual, expected);
+    match error.partial_cmp(&tolerance) {
+        Some(Ordering::Less | Ordering::Equal) => None,
+        _ => Some(error),
+    }
+}
+
+/// `|actual - expected| / |expected|`, or infinity when only `expected` is zero.
+pub fn relative_error(actual: f64, expected: f64) -> f64 {
+    if expected == 0.0 {
+        return if actual == 0.0 { 0.0 } else { f64::INFINITY };
+    }
+    ((actual - expected) / expected).abs()
+}
+
+/// What [`cheat_ensure!`](crate::cheat_ensure) accepts as its condition: a
+/// `bool`, or an `Option`/`Result` whose value it evaluates to.
+pub trait Ensure {
+    type Output;
+
+    fn ensure(self) -> Result<Self::Output, EnsureFailure<Self::Output>>;
+}
+
+impl Ensure for bool {
+    type Output = ();
+
+    fn ensure(self) -> Result<(), EnsureFailure<()>> {
+        if self {
+            Ok(())
+        } else {
+            Err(EnsureFailure {
+                detail: None,
+                fallback: Some(()),
+            })
+        }
+    }
+}
+
+impl<T> Ensure for Option<T> {
+    type Output = T;
+
+    fn ensure(self) -> Result<T, EnsureFailure<T>> {
+        self.ok_or(EnsureFailure {
+            detail: None,
+            fallback: None,
+        })
+    }
+}
+
+impl<T, E: Display> Ensure for Result<T, E> {
+    type Output = T;
+
+    fn ensure(self) -> Result<T, EnsureFailure<T>> {
+        self.map_err(|err| EnsureFailure {
+            detail: Some(format!("{:#}", err)),
+            fallback: None,
+        })
+    }
+}
+
+/// A failed [`Ensure`] condition, waiting for its [`CheatError`].
+pub struct EnsureFailure<T> {
+    /// The `Err` value's text, appended to the message.
+    detail: Option<String>,
+    /// What to continue with when the bail is downgraded. Only a `bool`
+    /// condition has one; an `Option` or `Result` always bails.
+    fallback: Option<T>,
+}
+
+impl<T> EnsureFailure<T> {
+    /// Report `err` and decide whether to continue with the fallback value
+    /// (`Ok`) or bail (`Err`).
+    // Without `anyhow`, BailError is the CheatError itself.
+    #[allow(clippy::result_large_err)]
+    pub fn report(self, mut err: CheatError) -> Result<T, BailError> {
+        if let Some(detail) = self.detail {
+            err.message = redact(format!("{}: {}", err.message, detail));
+        }
+        match self.fallback {
+            Some(value) if report_bail_failure(&err) => Ok(value),
+            Some(_) => Err(bail_error(err)),
+            None => {
+                report_failure(&err);
+                Err(bail_error(err))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_error() {
+        assert_eq!(relative_error(101.0, 100.0), 0.01);
+        assert_eq!(relative_error(-90.0, -100.0), 0.1);
+        assert_eq!(relative_error(0.0, 0.0), 0.0);
+        assert_eq!(relative_error(1.0, 0.0), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_approx_error() {
+        assert_eq!(approx_error(101.0, 100.0, 0.01), None);
+        assert_eq!(approx_error(110.0, 100.0, 0.01), Some(0.1));
+        assert!(approx_error(f64::NAN, 100.0, 0.01).unwrap().is_nan());
+    }
+
+    #[test]
+    fn test_retry_stops_at_first_success() {
+        let mut calls = 0;
+        let result = retry(3, 0, || {
+            calls += 1;
+            Ok::<_, String>(calls)
+        });
+        assert_eq!(result, Ok(1));
+    }
+
+    #[test]
+    fn test_retry_zero_attempts_runs_once() {
+        let mut calls = 0;
+        let result = retry(0, 0, || {
+            calls += 1;
+            Err::<(), _>("nope")
+        });
+        assert_eq!(result, Err((1, "nope".to_string())));
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_run_with_timeout_resumes_panic() {
+        let result = std::panic::catch_unwind(|| {
+            run_with_timeout(Duration::from_secs(5), || -> u32 {
+                panic!("worker blew up")
+            })
+        });
+        let payload = result.unwrap_err();
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"worker blew up"));
+    }
+}
@@ -0,0 +1,1243 @@
+//! Reporting and printing of cheat-guarded failures.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{Display, Write as _};
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::config;
+use crate::stats::record_failure;
+use crate::text::{
+    border, format_cheats, format_metadata, hyperlink, indent, json_string, paint, rfc3339, wrap,
+};
+use crate::{CheatError, Location, Severity};
+
+/// Default border width of [`cheat_check!`](crate::cheat_check) failure banners.
+pub(crate) const CHECK_WIDTH: usize = 60;
+
+/// The configured banner width, or `default` when none is set.
+pub(crate) fn width(default: usize) -> usize {
+    config::banner_width().unwrap_or(default)
+}
+
+pub(crate) const WARNING_TITLE: &str = "CHEAT-GUARDED WARNING";
+
+pub(crate) const TODO_TITLE: &str = "CHEAT-GUARDED TODO";
+
+/// Where banners go; `None` means stderr.
+static WRITER: Mutex<Option<Box<dyn Write + Send>>> = Mutex::new(None);
+
+/// Send printed banners to `writer` instead of stderr.
+///
+/// Covers the [`cheat_check!`](crate::cheat_check) failure banner, the
+/// [`cheat_warn!`](crate::cheat_warn) banner, and downgraded bails. The
+/// `checking:` progress lines and GitHub annotations stay on stdout.
+pub fn set_cheat_writer(writer: Box<dyn Write + Send>) {
+    *WRITER
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(writer);
+}
+
+/// Go back to printing banners on stderr.
+pub fn reset_cheat_writer() {
+    *WRITER
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+}
+
+/// Whether banners go to stderr, the only sink they are colored for.
+fn writes_to_stderr() -> bool {
+    WRITER
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .is_none()
+}
+
+/// Write `text` and a newline to the configured sink.
+pub(crate) fn write_banner(text: &str) {
+    let mut writer = WRITER
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    match writer.as_mut() {
+        // A broken sink must not turn a report into a panic.
+        Some(writer) => {
+            let _ = writeln!(writer, "{}", text).and_then(|()| writer.flush());
+        }
+        // eprintln!, not io::stderr(), so libtest captures it like before.
+        None => eprintln!("{}", text),
+    }
+}
+
+/// When each recently printed banner was last shown, and how many repeats
+/// have been held back since the last banner that did print.
+struct Dedup {
+    shown: HashMap<u64, Instant>,
+    suppressed: usize,
+}
+
+static DEDUP: Mutex<Option<Dedup>> = Mutex::new(None);
+
+/// Which check failed: its protects and location.
+type RepeatKey = (String, Option<Location>);
+
+/// Failures so far per check, for `CHEAT_GUARD_ESCALATE_AFTER`.
+static REPEATS: Mutex<Option<HashMap<RepeatKey, usize>>> = Mutex::new(None);
+
+/// `err` one severity higher, if `CHEAT_GUARD_ESCALATE_AFTER` is set and the
+/// same protects and location have already failed that many times this run.
+fn escalate(err: &CheatError) -> Option<CheatError> {
+    let after = config::escalate_after()?;
+    let higher = match err.severity {
+        Severity::Low => Severity::Medium,
+        Severity::Medium => Severity::High,
+        Severity::High | Severity::Critical => Severity::Critical,
+    };
+    let mut repeats = REPEATS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let count = repeats
+        .get_or_insert_with(HashMap::new)
+        .entry((err.protects.clone(), err.location))
+        .or_default();
+    *count += 1;
+    if *count <= after || higher == err.severity {
+        return None;
+    }
+    let mut escalated = err.clone();
+    escalated.severity = higher;
+    Some(escalated)
+}
+
+/// Write the banner `render` makes of `err`, unless `CHEAT_GUARD_DEDUP_MS`
+/// is set and the same banner printed within that window.
+///
+/// Banners are the same when their protects, message, and location match.
+/// The next banner that does print is preceded by a
+/// `(suppressed N duplicate cheat banners)` line. A repeat escalated by
+/// `CHEAT_GUARD_ESCALATE_AFTER` is rendered one severity higher, after an
+/// `ESCALATED:` tag. `render` is told whether to style the banner, which it
+/// should only be when printing to stderr.
+fn write_error_banner(err: &CheatError, render: impl Fn(&CheatError, bool) -> String) {
+    let styled = writes_to_stderr();
+    let text = match escalate(err) {
+        Some(escalated) => format!("ESCALATED:{}", render(&escalated, styled)),
+        None => render(err, styled),
+    };
+    let text = text.as_str();
+    let Some(window) = config::dedup_window() else {
+        write_banner(text);
+        return;
+    };
+    let mut hasher = DefaultHasher::new();
+    err.protects.hash(&mut hasher);
+    err.message.hash(&mut hasher);
+    err.location
+        .map(|location| (location.file, location.line, location.column))
+        .hash(&mut hasher);
+    let key = hasher.finish();
+
+    let now = Instant::now();
+    let mut guard = DEDUP
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let dedup = guard.get_or_insert_with(|| Dedup {
+        shown: HashMap::new(),
+        suppressed: 0,
+    });
+    if dedup
+        .shown
+        .get(&key)
+        .is_some_and(|&shown| now.duration_since(shown) < window)
+    {
+        dedup.suppressed += 1;
+        return;
+    }
+    dedup
+        .shown
+        .retain(|_, &mut shown| now.duration_since(shown) < window);
+    dedup.shown.insert(key, now);
+    let suppressed = std::mem::take(&mut dedup.suppressed);
+    drop(guard);
+
+    if suppressed > 0 {
+        let noun = if suppressed == 1 { "banner" } else { "banners" };
+        write_banner(&format!(
+            "(suppressed {} duplicate cheat {})",
+            suppressed, noun
+        ));
+    }
+    write_banner(text);
+}
+
+/// The banner printed by [`cheat_warn!`](crate::cheat_warn).
+pub(crate) fn warning_banner(err: &CheatError, styled: bool) -> String {
+    err.styled_banner(WARNING_TITLE, styled).to_string()
+}
+
+/// Print a `CHEAT-GUARDED WARNING` banner.
+pub(crate) fn emit_warning(err: &CheatError) {
+    note_undocumented(&err.protects, err);
+    write_error_banner(err, warning_banner);
+}
+
+/// Report a [`cheat_warn!`](crate::cheat_warn).
+///
+/// Returns `true` under `CHEAT_GUARD_STRICT`, in which case the failure has
+/// been counted and the caller must bail with it. Otherwise the warning
+/// banner is printed.
+pub fn report_warning(err: &CheatError) -> bool {
+    if config::strict() {
+        report_failure(err);
+        return true;
+    }
+    emit_warning(err);
+    false
+}
+
+/// Report a [`cheat_todo!`](crate::cheat_todo) stub.
+///
+/// Returns `true` under `CHEAT_GUARD_STRICT_TODO`, in which case the failure
+/// has been counted and the caller must bail. Otherwise debug builds print a
+/// `CHEAT-GUARDED TODO` banner and release builds stay quiet.
+pub fn report_todo(err: &CheatError) -> bool {
+    if config::strict_todo() {
+        report_failure(err);
+        return true;
+    }
+    if cfg!(debug_assertions) && !config::tracing_only() {
+        write_error_banner(err, |err, styled| {
+            err.styled_banner(TODO_TITLE, styled).to_string()
+        });
+    }
+    false
+}
+
+/// Names already reminded about by [`note_undocumented`].
+static UNDOCUMENTED: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// Once per name, remind that a `cheats = []` check still needs its cheats
+/// written down.
+fn note_undocumented(name: &dyn Display, err: &CheatError) {
+    if !err.cheats.is_empty() {
+        return;
+    }
+    let name = name.to_string();
+    let first = UNDOCUMENTED
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .insert(name.clone());
+    if first {
+        write_banner(&format!(
+            "cheat_guard: check '{}' has no documented cheat vectors",
+            name
+        ));
+    }
+}
+
+/// Count a failure and send it to the enabled `tracing`/`log`/`otel`/`metrics`
+/// integrations.
+pub fn report_failure(err: &CheatError) {
+    report_failure_in(None, err);
+}
+
+/// [`report_failure`] for a check filed under `category`, which labels the
+/// `metrics` counter.
+fn report_failure_in(category: Option<&str>, err: &CheatError) {
+    record_failure(err.severity);
+    count_failure(category, err);
+    trace_failure(err);
+    log_failure(err);
+    otel_failure(err);
+}
+
+/// Count and report a failed [`cheat_bail!`](crate::cheat_bail).
+///
+/// Returns `true` if the failure is downgraded by `CHEAT_GUARD_MIN_SEVERITY`,
+/// in which case the banner has been printed and the caller must not bail.
+pub fn report_bail_failure(err: &CheatError) -> bool {
+    report_failure(err);
+    note_undocumented(&err.protects, err);
+    if !config::is_downgraded(err.severity) {
+        return false;
+    }
+    if !config::tracing_only() {
+        write_error_banner(err, |err, styled| {
+            format!(
+                "DOWNGRADED:{}",
+                crate::formatter::render_styled(err, styled)
+            )
+        });
+    }
+    true
+}
+
+/// Whether `CHEAT_GUARD_STREAM=jsonl` is set.
+pub fn streaming() -> bool {
+    config::stream_jsonl()
+}
+
+/// Print the `checking:` progress line for a [`cheat_check!`](crate::cheat_check),
+/// unless checks are being streamed as JSON Lines.
+pub fn announce_check(name: &dyn Display, protects: &dyn Display) {
+    if !config::stream_jsonl() {
+        println!("    checking: {} (protects: {})", name, protects);
+    }
+}
+
+/// The `(took 1.2s)` note for a check whose condition took `elapsed`, or
+/// `None` if it was faster than `CHEAT_GUARD_SLOW_MS`.
+pub(crate) fn slow_note(elapsed: Duration) -> Option<String> {
+    (elapsed > config::slow_threshold()).then(|| format!("      (took {:.1?})", elapsed))
+}
+
+/// Print the [`slow_note`] after a check's `checking:` line, unless checks are
+/// being streamed as JSON Lines.
+pub fn announce_duration(elapsed: Duration) {
+    if config::stream_jsonl() {
+        return;
+    }
+    if let Some(note) = slow_note(elapsed) {
+        println!("{}", note);
+    }
+}
+
+/// Write the JSON Lines record of one check to the banner writer.
+///
+/// `result` is `pass`, `fail`, or `warn`; `severity` is `None` if a passing
+/// check's severity did not parse. A `timestamp`, the one recorded in the
+/// check's [`CheckInfo`](crate::CheckInfo), is written in RFC 3339 form.
+pub fn stream_check(
+    name: &dyn Display,
+    result: &str,
+    severity: Option<Severity>,
+    protects: &dyn Display,
+    timestamp: Option<SystemTime>,
+) {
+    let severity = match severity {
+        Some(severity) => json_string(severity.as_str()),
+        None => "null".to_string(),
+    };
+    let timestamp = match timestamp {
+        Some(timestamp) => format!(",\"timestamp\":{}", json_string(&rfc3339(timestamp))),
+        None => String::new(),
+    };
+    write_banner(&format!(
+        "{{\"name\":{},\"result\":{},\"severity\":{},\"protects\":{}{}}}",
+        json_string(&name.to_string()),
+        json_string(result),
+        severity,
+        json_string(&protects.to_string()),
+        timestamp
+    ));
+}
+
+/// Count, print, and annotate a failed [`cheat_check!`](crate::cheat_check).
+///
+/// `warn` marks checks recorded as warnings rather than failures.
+/// `expected` and `actual` feed the `DIFF:` section of the `diff` feature,
+/// `category` labels the `metrics` counter, and `timestamp` is streamed.
+pub fn report_check_failure(
+    name: &dyn Display,
+    err: &CheatError,
+    warn: bool,
+    expected: &str,
+    actual: &str,
+    category: Option<&str>,
+    timestamp: Option<SystemTime>,
+) {
+    let label = if warn { "WARNING" } else { "FAILED" };
+    report_failure_in(category, err);
+    if config::stream_jsonl() {
+        let result = if warn { "warn" } else { "fail" };
+        stream_check(name, result, Some(err.severity), &err.protects, timestamp);
+        return;
+    }
+    note_undocumented(name, err);
+    if config::tracing_only() {
+        return;
+    }
+
+    // Print cheat vectors on failure
+    write_error_banner(err, |err, styled| {
+        check_banner(label, name, err, expected, actual, styled)
+    });
+
+    if let Some(location) = &err.location {
+        let message = format!(
+            "CHEAT-GUARDED CHECK {}: {}\nPROTECTS: {}\nSEVERITY: {}",
+            label, name, err.protects, err.severity
+        );
+        let level = if warn { "warning" } else { "error" };
+        if let Some(annotation) = github_annotation(level, location.file, location.line, &message) {
+            println!("{}", annotation);
+        }
+    }
+}
+
+/// The stderr banner of a failed [`cheat_check!`](crate::cheat_check),
+/// colored and linked as configured when `styled`.
+fn check_banner(
+    label: &str,
+    name: &dyn Display,
+    err: &CheatError,
+    expected: &str,
+    actual: &str,
+    styled: bool,
+) -> String {
+    let color = (styled && config::color_enabled()).then(|| err.severity.ansi_color());
+    let links = styled && config::links_enabled();
+    let width = width(CHECK_WIDTH);
+    let border = paint(border(width), color);
+    let mut out = String::new();
+    // Writing to a String cannot fail.
+    let _ = writeln!(out, "\n{}", border);
+    let _ = writeln!(out, "CHEAT-GUARDED CHECK {}: {}", label, name);
+    let _ = writeln!(out, "{}", border);
+    let _ = writeln!(out, "PROTECTS: {}", wrap(&err.protects, width, 10));
+    let _ = writeln!(out, "SEVERITY: {}", paint(err.severity.as_str(), color));
+    if let Some(location) = &err.location {
+        let _ = writeln!(out, "LOCATION: {}", location);
+    }
+    if let Some(doc_url) = &err.doc_url {
+        let _ = writeln!(out, "SEE: {}", hyperlink(doc_url, links));
+    }
+    let _ = writeln!(out, "CHEATS:");
+    let _ = writeln!(out, "{}", format_cheats(&err.cheats));
+    let _ = writeln!(out, "CONSEQUENCE: {}", wrap(&err.consequence, width, 13));
+    if let Some(remedy) = &err.remedy {
+        let _ = writeln!(out, "SUGGESTED FIX: {}", wrap(remedy, width, 15));
+    }
+    if !err.metadata.is_empty() {
+        let _ = writeln!(out, "METADATA:");
+        let _ = writeln!(out, "{}", format_metadata(&err.metadata));
+    }
+    for (label, text) in [("STDOUT", &err.stdout), ("STDERR", &err.stderr)] {
+        if let Some(text) = text {
+            let _ = writeln!(out, "{}:", label);
+            let _ = writeln!(out, "{}", indent(text.trim_end_matches('\n')));
+        }
+    }
+    if let Some(diff) = diff_section(expected, actual) {
+        let _ = writeln!(out, "DIFF:");
+        let _ = writeln!(out, "{}", diff);
+    }
+    let _ = write!(out, "{}", border);
+    out
+}
+
+/// A unified line diff from `expected` to `actual`, if they differ.
+#[cfg(feature = "diff")]
+fn diff_section(expected: &str, actual: &str) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+    let diff = similar::TextDiff::from_lines(expected, actual)
+        .unified_diff()
+        .header("expected", "actual")
+        .missing_newline_hint(false)
+        .to_string();
+    Some(diff.trim_end_matches('\n').to_string())
+}
+
+#[cfg(not(feature = "diff"))]
+fn diff_section(_expected: &str, _actual: &str) -> Option<String> {
+    None
+}
+
+/// Emit a `tracing` error event with the failure's cheat metadata.
+#[cfg(feature = "tracing")]
+fn trace_failure(err: &CheatError) {
+    tracing::error!(
+        target: "cheat_guard",
+        protects = %err.protects,
+        severity = %err.severity,
+        cheats = %err.cheats.join("; "),
+        consequence = %err.consequence,
+        error = %err.message,
+        "cheat-guarded failure"
+    );
+}
+
+#[cfg(not(feature = "tracing"))]
+fn trace_failure(_err: &CheatError) {}
+
+/// Emit a `log` error record with the severity and banner.
+#[cfg(feature = "log")]
+fn log_failure(err: &CheatError) {
+    log::error!(target: "cheat_guard", "[{}] {}", err.severity, err);
+}
+
+#[cfg(not(feature = "log"))]
+fn log_failure(_err: &CheatError) {}
+
+/// Add a `cheat-guarded failure` event, with the cheat metadata as `cheat.*`
+/// attributes, to the active OpenTelemetry span.
+#[cfg(feature = "otel")]
+fn otel_failure(err: &CheatError) {
+    use opentelemetry::trace::get_active_span;
+    use opentelemetry::{Array, KeyValue, StringValue, Value};
+
+    get_active_span(|span| {
+        if !span.is_recording() {
+            return;
+        }
+        let cheats = err.cheats.iter().cloned().map(StringValue::from).collect();
+        span.add_event(
+            "cheat-guarded failure",
+            vec![
+                KeyValue::new("cheat.severity", err.severity.as_str()),
+                KeyValue::new("cheat.protects", err.protects.clone()),
+                KeyValue::new("cheat.cheats", Value::Array(Array::String(cheats))),
+                KeyValue::new("cheat.consequence", err.consequence.clone()),
+            ],
+        );
+    });
+}
+
+#[cfg(not(feature = "otel"))]
+fn otel_failure(_err: &CheatError) {}
+
+/// Increment `cheat_guard_failures_total`, labeled with the severity and,
+/// when there is one, the check's category.
+#[cfg(feature = "metrics")]
+fn count_failure(category: Option<&str>, err: &CheatError) {
+    const NAME: &str = "cheat_guard_failures_total";
+    let severity = err.severity.as_str();
+    match category {
+        Some(category) => {
+            metrics::counter!(NAME, "severity" => severity, "category" => category.to_string())
+                .increment(1)
+        }
+        None => metrics::counter!(NAME, "severity" => severity).increment(1),
+    }
+}
+
+#[cfg(not(feature = "metrics"))]
+fn count_failure(_category: Option<&str>, _err: &CheatError) {}
+
+/// A GitHub Actions workflow command (`::error file=...,line=...::msg`)
+/// for a failed check, or `None` outside GitHub Actions.
+pub(crate) fn github_annotation(
+    level: &str,
+    file: &str,
+    line: u32,
+    message: &str,
+) -> Option<String> {
+    if !config::in_github_actions() {
+        return None;
+    }
+    Some(format!(
+        "::{} file={},line={}::{}",
+        level,
+        escape_property(file),
+        line,
+        escape_data(message)
+    ))
+}
+
+/// Escape a workflow command message.
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a workflow command property value.
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::BAIL_WIDTH;
+    use crate::test_util::{EnvGuard, SharedBuf};
+    use crate::Severity;
+
+    #[test]
+    fn test_border_widths() {
+        let _env = EnvGuard::unset(&[config::WIDTH_ENV]);
+        assert_eq!(border(width(BAIL_WIDTH)), "=".repeat(70));
+        assert_eq!(border(width(CHECK_WIDTH)), "=".repeat(60));
+    }
+
+    #[test]
+    fn test_configured_width_applies_to_both_banners() {
+        let _env = EnvGuard::set(&[(config::WIDTH_ENV, "40"), (config::COLOR_ENV, "never")]);
+        let err = CheatError::new("p", Severity::Low, ["c"], "q", "m");
+
+        let bail = err.to_string();
+        let check = check_banner("FAILED", &"name", &err, "a", "b", false);
+        for banner in [&bail, &check] {
+            let borders: Vec<_> = banner
+                .lines()
+                .filter(|line| line.starts_with("==") && !line.contains(' '))
+                .collect();
+            assert_eq!(borders.len(), 3);
+            assert!(borders.iter().all(|line| line.len() == 40), "{banner}");
+        }
+    }
+
+    #[test]
+    fn test_warning_banner_title() {
+        let err = CheatError::new("p", Severity::Low, ["c"], "q", "odd layout");
+        let banner = warning_banner(&err, false);
+        assert!(banner.contains("=== CHEAT-GUARDED WARNING ==="));
+        assert!(!banner.contains("FAILURE"));
+        assert!(banner.contains("ERROR:\nodd layout\n"));
+    }
+
+    /// Set in the child run by [`test_default_writer_is_captured`].
+    const CAPTURE_PROBE_ENV: &str = "CHEAT_GUARD_TEST_CAPTURE_PROBE";
+
+    #[test]
+    fn test_default_writer_is_captured() {
+        if std::env::var_os(CAPTURE_PROBE_ENV).is_some() {
+            write_banner("CAPTURE PROBE");
+            return;
+        }
+        let run = |nocapture: bool| {
+            let mut command = std::process::Command::new(std::env::current_exe().unwrap());
+            command
+                .args(["--exact", "output::tests::test_default_writer_is_captured"])
+                .env(CAPTURE_PROBE_ENV, "1");
+            if nocapture {
+                command.arg("--nocapture");
+            }
+            let output = command.output().unwrap();
+            assert!(output.status.success());
+            String::from_utf8_lossy(&output.stderr).into_owned()
+                + &String::from_utf8_lossy(&output.stdout)
+        };
+
+        assert!(!run(false).contains("CAPTURE PROBE"));
+        assert!(run(true).contains("CAPTURE PROBE"));
+    }
+
+    #[test]
+    fn test_check_banner_captured_output() {
+        let _env = EnvGuard::set(&[(config::COLOR_ENV, "never")]);
+        let err = CheatError::new("p", Severity::High, ["c"], "q", "m")
+            .with_stdout("vda1\nvda2\n")
+            .with_stderr("sfdisk: warning");
+        let banner = check_banner("FAILED", &"name", &err, "a", "b", false);
+        assert!(
+            banner.contains("STDOUT:\n  vda1\n  vda2\nSTDERR:\n  sfdisk: warning\n"),
+            "{banner}"
+        );
+
+        let bail = err.banner(crate::error::FAILURE_TITLE).to_string();
+        assert!(
+            bail.contains("\nSTDOUT:\nvda1\nvda2\n\nSTDERR:\nsfdisk: warning\n"),
+            "{bail}"
+        );
+    }
+
+    #[test]
+    fn test_escalate_after_repeated_failures() {
+        let _env = EnvGuard::set(&[
+            (config::ESCALATE_AFTER_ENV, "5"),
+            (config::COLOR_ENV, "never"),
+        ]);
+        let buf = SharedBuf::default();
+        set_cheat_writer(Box::new(buf.clone()));
+
+        let err = CheatError::new(
+            "Mirror responds quickly",
+            Severity::Medium,
+            ["c"],
+            "q",
+            "slow",
+        )
+        .with_location(Location::new("src/net.rs", 7, 5));
+        for _ in 0..6 {
+            emit_warning(&err);
+        }
+        reset_cheat_writer();
+
+        let captured = buf.contents();
+        let banners: Vec<&str> = captured
+            .split("=== CHEAT-GUARDED WARNING ===")
+            .skip(1)
+            .collect();
+        assert_eq!(banners.len(), 6);
+        assert!(banners[..5]
+            .iter()
+            .all(|banner| banner.contains("SEVERITY: MEDIUM")));
+        assert!(banners[5].contains("SEVERITY: HIGH"), "{captured}");
+        assert_eq!(captured.matches("ESCALATED:").count(), 1);
+        assert!(captured.rfind("ESCALATED:") > captured.rfind("SEVERITY: MEDIUM"));
+    }
+
+    #[test]
+    fn test_dedup_suppresses_repeated_banners() {
+        let _env = EnvGuard::set(&[(config::DEDUP_ENV, "60000")]);
+        let buf = SharedBuf::default();
+        set_cheat_writer(Box::new(buf.clone()));
+
+        let repeated = CheatError::new("Mirror is reachable", Severity::Low, ["c"], "q", "timeout");
+        for _ in 0..5 {
+            emit_warning(&repeated);
+        }
+        let other = CheatError::new("Mirror is reachable", Severity::Low, ["c"], "q", "refused");
+        emit_warning(&other);
+        reset_cheat_writer();
+
+        let captured = buf.contents();
+        assert_eq!(captured.matches("ERROR:\ntimeout\n").count(), 1);
+        let note = captured
+            .find("(suppressed 4 duplicate cheat banners)\n")
+            .expect(&captured);
+        let refused = captured.find("ERROR:\nrefused\n").unwrap();
+        assert!(captured.find("ERROR:\ntimeout\n").unwrap() < note && note < refused);
+    }
+
+    #[test]
+    fn test_stream_jsonl_writes_one_object_per_check() {
+        let _env = EnvGuard::set(&[(config::STREAM_ENV, "jsonl")]);
+        let buf = SharedBuf::default();
+        set_cheat_writer(Box::new(buf.clone()));
+
+        let mut step = crate::StepResult::new(1, "Partition Disk");
+        for (name, condition) in [("vda1 exists", true), ("vda2 exists", false)] {
+            crate::cheat_check!(
+                step,
+                name = name,
+                condition = condition,
+                protects = "Disk is \"partitioned\"",
+                severity = "HIGH",
+                cheats = [],
+                consequence = "Installation fails",
+                expected = "present",
+                actual = "missing"
+            );
+        }
+        crate::cheat_check!(
+            step,
+            name = "layout",
+            condition = false,
+            protects = "Layout matches",
+            severity = "LOW",
+            cheats = ["Accept any layout"],
+            consequence = "Odd mounts",
+            expected = "3 partitions",
+            actual = "4 partitions",
+            warn = true
+        );
+        reset_cheat_writer();
+
+        let captured = buf.contents();
+        #[allow(unused_mut)]
+        let mut lines: Vec<serde_json::Value> = captured
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        // The streamed stamp is the one recorded with the check.
+        #[cfg(feature = "timestamps")]
+        for (index, line) in lines.iter_mut().enumerate() {
+            assert_eq!(
+                line.as_object_mut().unwrap().remove("timestamp"),
+                Some(rfc3339(step.timestamp(index).unwrap()).into())
+            );
+        }
+        assert_eq!(
+            lines,
+            [
+                serde_json::json!({"name": "vda1 exists", "result": "pass", "severity": "HIGH", "protects": "Disk is \"partitioned\""}),
+                serde_json::json!({"name": "vda2 exists", "result": "fail", "severity": "HIGH", "protects": "Disk is \"partitioned\""}),
+                serde_json::json!({"name": "layout", "result": "warn", "severity": "LOW", "protects": "Layout matches"}),
+            ]
+        );
+        assert_eq!(step.summary().failed, 1);
+    }
+
+    #[test]
+    fn test_writer_captures_check_banner() {
+        let _env = EnvGuard::set(&[(config::COLOR_ENV, "never")]);
+        let buf = SharedBuf::default();
+        set_cheat_writer(Box::new(buf.clone()));
+
+        let err = CheatError::new("Disk is partitioned", Severity::High, ["Skip it"], "q", "m");
+        report_check_failure(&"Partition table created", &err, false, "", "", None, None);
+        reset_cheat_writer();
+
+        let captured = buf.contents();
+        let border = "=".repeat(60);
+        assert!(captured.contains(&format!(
+            "\n{border}\nCHEAT-GUARDED CHECK FAILED: Partition table created\n{border}\n\
+             PROTECTS: Disk is partitioned\nSEVERITY: HIGH\nCHEATS:\n  1. Skip it\n\
+             CONSEQUENCE: q\n{border}\n"
+        )));
+    }
+
+    #[test]
+    fn test_writer_banners_are_never_colored() {
+        let _env = EnvGuard::set(&[(config::COLOR_ENV, "always"), (config::LINKS_ENV, "always")]);
+        let buf = SharedBuf::default();
+        set_cheat_writer(Box::new(buf.clone()));
+
+        let err =
+            CheatError::new("p", Severity::High, ["c"], "q", "m").with_doc_url("https://x.dev");
+        report_check_failure(&"Layout", &err, false, "", "", None, None);
+        emit_warning(&err);
+        reset_cheat_writer();
+
+        let captured = buf.contents();
+        assert!(captured.contains("SEVERITY: HIGH\n"), "{captured}");
+        assert!(!captured.contains('\x1b'), "{captured:?}");
+        let banner = check_banner("FAILED", &"Layout", &err, "", "", true);
+        assert!(
+            banner.contains("SEVERITY: \x1b[93mHIGH\x1b[0m\n"),
+            "{banner:?}"
+        );
+    }
+
+    #[test]
+    fn test_check_banner_remedy() {
+        let _env = EnvGuard::set(&[(config::COLOR_ENV, "never")]);
+        let err = CheatError::new("p", Severity::High, ["c"], "q", "m");
+        let plain = check_banner("FAILED", &"Layout", &err, "", "", false);
+        assert!(!plain.contains("SUGGESTED FIX:"));
+
+        let err = err.with_remedy("run sfdisk --reset and retry");
+        let banner = check_banner("FAILED", &"Layout", &err, "", "", false);
+        assert!(
+            banner.contains("CONSEQUENCE: q\nSUGGESTED FIX: run sfdisk --reset and retry\n"),
+            "{banner}"
+        );
+    }
+
+    #[test]
+    fn test_check_banner_doc_url() {
+        let _env = EnvGuard::set(&[(config::COLOR_ENV, "never")]);
+        let err = CheatError::new("p", Severity::High, ["c"], "q", "m");
+        assert!(!check_banner("FAILED", &"Layout", &err, "", "", false).contains("SEE:"));
+
+        let err = err.with_doc_url("https://wiki.levitate.dev/cheats/disk");
+        let banner = check_banner("FAILED", &"Layout", &err, "", "", false);
+        assert!(
+            banner.contains("SEVERITY: HIGH\nSEE: https://wiki.levitate.dev/cheats/disk\nCHEATS:"),
+            "{banner}"
+        );
+    }
+
+    #[test]
+    fn test_check_banner_links_doc_url() {
+        let url = "https://wiki.levitate.dev/cheats/disk";
+        let link = format!("SEE: \x1b]8;;{url}\x1b\\{url}\x1b]8;;\x1b\\\n");
+        let err = CheatError::new("p", Severity::High, ["c"], "q", "m").with_doc_url(url);
+        let banner = || check_banner("FAILED", &"Layout", &err, "", "", true);
+
+        let _env = EnvGuard::set(&[(config::COLOR_ENV, "never"), (config::LINKS_ENV, "always")]);
+        assert!(banner().contains(&link), "{:?}", banner());
+        std::env::set_var(config::LINKS_ENV, "never");
+        assert!(!banner().contains("\x1b]8"));
+        assert!(banner().contains(&format!("SEE: {url}\n")));
+        std::env::remove_var(config::LINKS_ENV);
+        assert!(!banner().contains("\x1b]8"));
+        std::env::set_var(config::COLOR_ENV, "always");
+        assert!(banner().contains(&link));
+    }
+
+    #[test]
+    fn test_check_banner_metadata() {
+        let _env = EnvGuard::set(&[(config::COLOR_ENV, "never")]);
+        let err = CheatError::new("p", Severity::High, ["c"], "q", "m");
+        assert!(!check_banner("FAILED", &"Layout", &err, "", "", false).contains("METADATA:"));
+
+        let err = err
+            .with_metadata("device", "/dev/vda")
+            .with_metadata("cmd", "sfdisk");
+        let banner = check_banner("FAILED", &"Layout", &err, "", "", false);
+        assert!(
+            banner.contains("CONSEQUENCE: q\nMETADATA:\n  device: /dev/vda\n  cmd:    sfdisk\n"),
+            "{banner}"
+        );
+    }
+
+    #[cfg(feature = "diff")]
+    #[test]
+    fn test_check_banner_diff() {
+        let _env = EnvGuard::set(&[(config::COLOR_ENV, "never")]);
+        let err = CheatError::new("p", Severity::High, ["c"], "q", "m");
+        let expected = "vda1 part /boot\nvda2 part /\nvda3 part swap\n";
+        let actual = "vda1 part /boot\nvda2 part /home\nvda3 part swap\n";
+
+        let banner = check_banner("FAILED", &"Layout", &err, expected, actual, false);
+        assert!(
+            banner.contains("\nDIFF:\n--- expected\n+++ actual\n"),
+            "{banner}"
+        );
+        assert!(banner.contains("\n-vda2 part /\n"));
+        assert!(banner.contains("\n+vda2 part /home\n"));
+        assert!(banner.contains("\n vda1 part /boot\n"));
+        assert!(
+            !check_banner("FAILED", &"Layout", &err, expected, expected, false).contains("DIFF:")
+        );
+    }
+
+    #[test]
+    fn test_todo_banner_unless_strict() {
+        let _env = EnvGuard::unset(&[config::STRICT_TODO_ENV, config::TRACING_ONLY_ENV]);
+        let buf = SharedBuf::default();
+        set_cheat_writer(Box::new(buf.clone()));
+
+        let err = CheatError::new("p", Severity::High, ["c"], "q", "write the mount check");
+        let lenient = report_todo(&err);
+        std::env::set_var(config::STRICT_TODO_ENV, "1");
+        let strict = report_todo(&err);
+        reset_cheat_writer();
+
+        assert!(!lenient);
+        assert!(strict);
+        let captured = buf.contents();
+        assert_eq!(captured.matches("=== CHEAT-GUARDED TODO ===").count(), 1);
+        assert!(captured.contains("write the mount check"));
+    }
+
+    #[test]
+    fn test_undocumented_note_printed_once() {
+        let _env = EnvGuard::unset(&[config::MIN_SEVERITY_ENV, config::TRACING_ONLY_ENV]);
+        let buf = SharedBuf::default();
+        set_cheat_writer(Box::new(buf.clone()));
+
+        let err = CheatError::new(
+            "Scaffolded check",
+            Severity::Low,
+            Vec::<String>::new(),
+            "q",
+            "m",
+        );
+        report_bail_failure(&err);
+        report_bail_failure(&err);
+        report_check_failure(&"Scaffolded step", &err, false, "a", "b", None, None);
+        reset_cheat_writer();
+
+        let captured = buf.contents();
+        let note = "cheat_guard: check 'Scaffolded check' has no documented cheat vectors\n";
+        assert_eq!(captured.matches(note).count(), 1);
+        assert!(captured.contains("check 'Scaffolded step' has no documented"));
+        assert!(captured.contains("CHEATS:\n  (none documented)\n"));
+    }
+
+    #[test]
+    fn test_github_annotation_escapes_message() {
+        let _env = EnvGuard::set(&[(config::GITHUB_ACTIONS_ENV, "true")]);
+        let annotation =
+            github_annotation("error", "src/disk.rs", 42, "100% broken\r\nsecond line").unwrap();
+        assert_eq!(
+            annotation,
+            "::error file=src/disk.rs,line=42::100%25 broken%0D%0Asecond line"
+        );
+    }
+
+    #[test]
+    fn test_github_annotation_needs_env() {
+        let _env = EnvGuard::unset(&[config::GITHUB_ACTIONS_ENV]);
+        assert!(github_annotation("error", "src/disk.rs", 42, "msg").is_none());
+    }
+
+    #[cfg(feature = "tracing")]
+    mod tracing_events {
+        use super::*;
+        use std::collections::BTreeMap;
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        type Captured = Arc<Mutex<Vec<(String, BTreeMap<String, String>)>>>;
+
+        /// Records the target and fields of every event.
+        struct Capture(Captured);
+
+        struct Fields<'a>(&'a mut BTreeMap<String, String>);
+
+        impl Visit for Fields<'_> {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                self.0
+                    .insert(field.name().to_string(), format!("{:?}", value));
+            }
+        }
+
+        impl Subscriber for Capture {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, _: &Attributes<'_>) -> Id {
+                Id::from_u64(1)
+            }
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, event: &Event<'_>) {
+                let mut fields = BTreeMap::new();
+                event.record(&mut Fields(&mut fields));
+                let target = event.metadata().target().to_string();
+                self.0.lock().unwrap().push((target, fields));
+            }
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        #[test]
+        fn test_check_failure_emits_event() {
+            let captured = Captured::default();
+            let err = CheatError::new(
+                "Disk is partitioned",
+                Severity::Critical,
+                ["Skip check", "Accept any output"],
+                "No partitions",
+                "expected: vda1, actual: nothing",
+            );
+
+            tracing::subscriber::with_default(Capture(captured.clone()), || {
+                report_check_failure(&"Partition table created", &err, false, "", "", None, None);
+            });
+
+            let events = captured.lock().unwrap();
+            assert_eq!(events.len(), 1);
+            let (target, fields) = &events[0];
+            assert_eq!(target, "cheat_guard");
+            assert_eq!(fields["protects"], "Disk is partitioned");
+            assert_eq!(fields["severity"], "CRITICAL");
+            assert_eq!(fields["cheats"], "Skip check; Accept any output");
+            assert_eq!(fields["consequence"], "No partitions");
+            assert_eq!(fields["error"], "expected: vda1, actual: nothing");
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    mod metric_counts {
+        use crate::__private::BailError;
+        use crate::StepResult;
+        use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+        use std::collections::BTreeMap;
+
+        type Labels = Vec<(String, String)>;
+
+        fn labels(pairs: &[(&str, &str)]) -> Labels {
+            pairs
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect()
+        }
+
+        fn bail() -> Result<(), BailError> {
+            crate::cheat_bail!(
+                protects = "Disk is partitioned",
+                severity = "HIGH",
+                cheats = ["Skip it"],
+                consequence = "q",
+                "boom"
+            );
+            Ok(())
+        }
+
+        fn ensure() -> Result<(), BailError> {
+            crate::cheat_ensure!(
+                false,
+                protects = "Disk is partitioned",
+                severity = "HIGH",
+                cheats = ["Skip it"],
+                consequence = "q",
+                "boom"
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn test_failures_counted_by_severity_and_category() {
+            let _env = crate::test_util::EnvGuard::unset(&[
+                crate::config::MIN_SEVERITY_ENV,
+                crate::config::STREAM_ENV,
+            ]);
+            let recorder = DebuggingRecorder::new();
+            let snapshotter = recorder.snapshotter();
+
+            metrics::with_local_recorder(&recorder, || {
+                assert!(bail().is_err());
+                assert!(ensure().is_err());
+                let mut step = StepResult::new(1, "Partition Disk");
+                for condition in [false, true, false] {
+                    crate::cheat_check!(
+                        step,
+                        name = "vda1",
+                        condition = condition,
+                        protects = "Disk is partitioned",
+                        severity = "LOW",
+                        cheats = ["Skip it"],
+                        consequence = "q",
+                        expected = "vda1",
+                        actual = "nothing",
+                        category = "disk"
+                    );
+                }
+            });
+
+            let counts: BTreeMap<Labels, u64> = snapshotter
+                .snapshot()
+                .into_vec()
+                .into_iter()
+                .map(|(key, _, _, value)| {
+                    assert_eq!(key.key().name(), "cheat_guard_failures_total");
+                    let labels = key
+                        .key()
+                        .labels()
+                        .map(|label| (label.key().to_string(), label.value().to_string()))
+                        .collect();
+                    match value {
+                        DebugValue::Counter(count) => (labels, count),
+                        other => panic!("not a counter: {other:?}"),
+                    }
+                })
+                .collect();
+            assert_eq!(
+                counts,
+                BTreeMap::from([
+                    (labels(&[("severity", "HIGH")]), 2),
+                    (labels(&[("severity", "LOW"), ("category", "disk")]), 2),
+                ])
+            );
+        }
+    }
+
+    #[cfg(feature = "otel")]
+    mod otel_events {
+        use super::*;
+        use opentelemetry::trace::{Span, SpanContext, Status, TraceContextExt};
+        use opentelemetry::{Array, Context, KeyValue, Value};
+        use std::borrow::Cow;
+        use std::sync::{Arc, Mutex};
+        use std::time::SystemTime;
+
+        type Events = Arc<Mutex<Vec<(String, Vec<KeyValue>)>>>;
+
+        /// A span that keeps the events added to it.
+        struct Recording {
+            context: SpanContext,
+            events: Events,
+        }
+
+        impl Span for Recording {
+            fn add_event_with_timestamp<T>(
+                &mut self,
+                name: T,
+                _: SystemTime,
+                attributes: Vec<KeyValue>,
+            ) where
+                T: Into<Cow<'static, str>>,
+            {
+                let event = (name.into().into_owned(), attributes);
+                self.events.lock().unwrap().push(event);
+            }
+            fn span_context(&self) -> &SpanContext {
+                &self.context
+            }
+            fn is_recording(&self) -> bool {
+                true
+            }
+            fn set_attribute(&mut self, _: KeyValue) {}
+            fn set_status(&mut self, _: Status) {}
+            fn update_name<T>(&mut self, _: T)
+            where
+                T: Into<Cow<'static, str>>,
+            {
+            }
+            fn add_link(&mut self, _: SpanContext, _: Vec<KeyValue>) {}
+            fn end_with_timestamp(&mut self, _: SystemTime) {}
+        }
+
+        #[test]
+        fn test_failure_adds_span_event() {
+            let events = Events::default();
+            let span = Recording {
+                context: SpanContext::empty_context(),
+                events: events.clone(),
+            };
+            let err = CheatError::new(
+                "Disk is partitioned",
+                Severity::Critical,
+                ["Skip check", "Accept any output"],
+                "No partitions",
+                "expected: vda1, actual: nothing",
+            );
+
+            {
+                let _active = Context::current_with_span(span).attach();
+                report_failure(&err);
+            }
+            report_failure(&err);
+
+            let events = events.lock().unwrap();
+            assert_eq!(events.len(), 1);
+            let (name, attributes) = &events[0];
+            assert_eq!(name, "cheat-guarded failure");
+            let attribute = |key: &str| {
+                attributes
+                    .iter()
+                    .find(|kv| kv.key.as_str() == key)
+                    .map(|kv| kv.value.clone())
+                    .unwrap()
+            };
+            assert_eq!(attribute("cheat.severity"), Value::from("CRITICAL"));
+            assert_eq!(
+                attribute("cheat.protects"),
+                Value::from("Disk is partitioned")
+            );
+            assert_eq!(
+                attribute("cheat.cheats"),
+                Value::Array(Array::String(vec![
+                    "Skip check".into(),
+                    "Accept any output".into()
+                ]))
+            );
+            assert_eq!(attribute("cheat.consequence"), Value::from("No partitions"));
+        }
+    }
+
+    #[cfg(feature = "log")]
+    mod log_records {
+        use super::*;
+        use log::{Level, Log, Metadata, Record};
+        use std::sync::Mutex;
+
+        /// Keeps every record; the logger is process-wide, so tests filter by content.
+        struct Capture(Mutex<Vec<(Level, String)>>);
+
+        impl Log for Capture {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn log(&self, record: &Record<'_>) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push((record.level(), record.args().to_string()));
+            }
+            fn flush(&self) {}
+        }
+
+        static LOGGER: Capture = Capture(Mutex::new(Vec::new()));
+
+        #[test]
+        fn test_bail_failure_logs_error_record() {
+            log::set_logger(&LOGGER).unwrap();
+            log::set_max_level(log::LevelFilter::Trace);
+
+            let err = CheatError::new("p", Severity::High, ["c"], "q", "log-record-marker");
+            report_bail_failure(&err);
+
+            let records = LOGGER.0.lock().unwrap();
+            let (level, message) = records
+                .iter()
+                .find(|(_, message)| message.contains("log-record-marker"))
+                .unwrap();
+            assert_eq!(*level, Level::Error);
+            assert!(message.starts_with("[HIGH] "));
+            assert!(message.contains("PROTECTS: p"));
+        }
+    }
+}
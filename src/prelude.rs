@@ -0,0 +1,47 @@
+//! The commonly needed macros and types in one import.
+//!
+//! ```rust,ignore
+//! use leviso_cheat_guard::prelude::*;
+//! ```
+
+#[cfg(feature = "regex")]
+pub use crate::cheat_matches;
+#[cfg(feature = "std")]
+pub use crate::{
+    cheat_assert_approx, cheat_assert_eq, cheat_assert_within, cheat_bail, cheat_check,
+    cheat_contains, cheat_doc, cheat_ensure, cheat_exec_contains, cheat_expect, cheat_group,
+    cheat_panic, cheat_profile, cheat_result, cheat_retry, cheat_scope, cheat_skip, cheat_timeout,
+    cheat_todo, cheat_warn, CheckResult, ConsoleExec, StepResult,
+};
+pub use crate::{severity, CheatError, CheatMeta, Severity};
+
+#[cfg(all(test, feature = "anyhow"))]
+mod tests {
+    use crate::prelude::*;
+
+    fn verify(output: &str) -> anyhow::Result<()> {
+        cheat_ensure!(
+            output.contains("vda1"),
+            protects = "Disk is partitioned",
+            severity = Severity::Critical,
+            cheats = ["Accept any output"],
+            consequence = "Installation fails",
+            "vda1 not found"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_prelude_brings_in_macros_and_types() {
+        assert!(verify("vda1").is_ok());
+        let err = verify("vdb").unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<CheatError>().unwrap().severity,
+            Severity::Critical
+        );
+
+        let mut step = StepResult::new(1, "Partition");
+        step.add_check("table", CheckResult::Pass("vda1".into()));
+        assert!(step.all_passed());
+    }
+}
@@ -0,0 +1,220 @@
+//! Run-level aggregation of step results.
+
+use std::collections::BTreeMap;
+
+use crate::{exit_code_for, StepResult, StepSummary};
+
+/// All steps of an install-test run.
+#[derive(Debug, Clone, Default)]
+pub struct TestReport {
+    steps: Vec<StepResult>,
+}
+
+impl TestReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a finished step.
+    pub fn add_step(&mut self, step: StepResult) {
+        self.steps.push(step);
+    }
+
+    /// Append every step of `other` after this report's own, in `other`'s
+    /// order.
+    ///
+    /// Steps are never combined: merging a step with the same number as one
+    /// already here keeps both. Use [`StepResult::merge`] to fold checks into
+    /// one step.
+    pub fn merge(&mut self, other: TestReport) {
+        self.steps.extend(other.steps);
+    }
+
+    /// Steps in the order they were added.
+    pub fn steps(&self) -> &[StepResult] {
+        &self.steps
+    }
+
+    /// Check counts summed over every step.
+    pub fn summary(&self) -> StepSummary {
+        let mut summary = StepSummary::default();
+        for step in &self.steps {
+            summary += step.summary();
+        }
+        summary
+    }
+
+    /// Check counts per category, summed over every step; see
+    /// [`StepResult::category_summary`].
+    pub fn category_summary(&self) -> BTreeMap<String, StepSummary> {
+        let mut summaries = BTreeMap::<String, StepSummary>::new();
+        for step in &self.steps {
+            for (category, summary) in step.category_summary() {
+                *summaries.entry(category).or_default() += summary;
+            }
+        }
+        summaries
+    }
+
+    /// The exit code for this run: [`exit_code_for`] the most severe failing
+    /// check across every step, or 0 when nothing failed.
+    pub fn suggested_exit_code(&self) -> i32 {
+        self.steps
+            .iter()
+            .filter_map(StepResult::worst_failure)
+            .max()
+            .map_or(0, exit_code_for)
+    }
+
+    /// Steps with at least one failing check.
+    pub fn failed_steps(&self) -> Vec<&StepResult> {
+        self.steps
+            .iter()
+            .filter(|step| !step.all_passed())
+            .collect()
+    }
+
+    /// True when no step has a failing check.
+    pub fn overall_passed(&self) -> bool {
+        self.steps.iter().all(StepResult::all_passed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CheckInfo, CheckResult, Severity};
+
+    fn step(step: u32, name: &str, results: Vec<CheckResult>) -> StepResult {
+        let mut result = StepResult::new(step, name);
+        for (i, check) in results.into_iter().enumerate() {
+            result.add_check(format!("check {}", i + 1), check);
+        }
+        result
+    }
+
+    fn fail() -> CheckResult {
+        CheckResult::Fail {
+            expected: "vda1".into(),
+            actual: "nothing".into(),
+        }
+    }
+
+    #[test]
+    fn test_report_with_one_failing_step() {
+        let mut report = TestReport::new();
+        report.add_step(step(1, "Boot", vec![CheckResult::Pass("booted".into())]));
+        report.add_step(step(
+            2,
+            "Partition Disk",
+            vec![CheckResult::Pass("gpt".into()), fail()],
+        ));
+        report.add_step(step(3, "Mount", vec![CheckResult::Skip("no ESP".into())]));
+
+        assert!(!report.overall_passed());
+        let failed = report.failed_steps();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].step(), 2);
+
+        let summary = report.summary();
+        assert_eq!(summary.total, 4);
+        assert_eq!(summary.passed, 2);
+        assert_eq!(summary.failed, 1);
+        assert_eq!(summary.skipped, 1);
+    }
+
+    #[test]
+    fn test_category_summary_across_steps() {
+        let mut boot = StepResult::new(1, "Boot");
+        boot.add_check_in("bootloader", "efi entry", CheckResult::Pass("entry".into()));
+        boot.add_check_in("disk", "esp mounted", fail());
+        let mut disk = StepResult::new(2, "Partition Disk");
+        disk.add_check_in("disk", "vda1", CheckResult::Pass("vda1".into()));
+        disk.add_check_in("disk", "vda2", fail());
+        disk.add_check("layout", CheckResult::Pass("gpt".into()));
+
+        let mut report = TestReport::new();
+        report.add_step(boot);
+        report.add_step(disk);
+
+        let summary = report.category_summary();
+        assert_eq!(summary.len(), 2);
+        assert_eq!(
+            summary["disk"],
+            StepSummary {
+                total: 3,
+                passed: 1,
+                failed: 2,
+                skipped: 0,
+                warned: 0,
+            }
+        );
+        assert_eq!(summary["bootloader"].passed, 1);
+        assert_eq!(summary["bootloader"].failed, 0);
+    }
+
+    #[test]
+    fn test_suggested_exit_code_uses_worst_failure() {
+        let failed = |severity| CheckInfo {
+            severity: Some(severity),
+            ..CheckInfo::default()
+        };
+        let mut boot = StepResult::new(1, "Boot");
+        boot.add_check_with(
+            "kernel",
+            CheckResult::Pass("booted".into()),
+            failed(Severity::Critical),
+        );
+        boot.add_check_with("esp", fail(), failed(Severity::Medium));
+        let mut disk = StepResult::new(2, "Partition Disk");
+        disk.add_check_with("vda2", fail(), failed(Severity::High));
+        disk.add_check_with("vda3", fail(), failed(Severity::Low));
+
+        let mut report = TestReport::new();
+        assert_eq!(report.suggested_exit_code(), 0);
+        report.add_step(boot);
+        assert_eq!(report.suggested_exit_code(), 2);
+        report.add_step(disk);
+        assert_eq!(report.suggested_exit_code(), 3);
+    }
+
+    #[test]
+    fn test_merge_keeps_every_step() {
+        let mut report = TestReport::new();
+        report.add_step(step(1, "Boot", vec![CheckResult::Pass("booted".into())]));
+        report.add_step(step(2, "Disk", vec![fail()]));
+        let mut other = TestReport::new();
+        other.add_step(step(
+            2,
+            "Disk again",
+            vec![CheckResult::Pass("vda1".into())],
+        ));
+        other.add_step(step(
+            3,
+            "Mount",
+            vec![
+                CheckResult::Pass("/".into()),
+                CheckResult::Skip("no /home".into()),
+            ],
+        ));
+
+        report.merge(other);
+
+        let steps: Vec<(u32, &str)> = report
+            .steps()
+            .iter()
+            .map(|s| (s.step(), s.name()))
+            .collect();
+        assert_eq!(
+            steps,
+            [(1, "Boot"), (2, "Disk"), (2, "Disk again"), (3, "Mount")]
+        );
+        let summary = report.summary();
+        assert_eq!((summary.total, summary.passed, summary.failed), (5, 3, 1));
+    }
+
+    #[test]
+    fn test_empty_report_passes() {
+        assert!(TestReport::new().overall_passed());
+    }
+}
@@ -0,0 +1,128 @@
+//! RAII tally of the checks made within a scope.
+
+use std::thread;
+
+use crate::output::write_banner;
+use crate::CheckResult;
+
+/// Counts the [`CheckResult`]s fed to it and reports them when dropped.
+///
+/// Built by [`cheat_scope!`](crate::cheat_scope). On drop it prints
+/// `scope 'NAME': P/T checks passed` to the
+/// [banner writer](crate::set_cheat_writer), including on an early return.
+/// If the thread is unwinding from a panic, the line ends in
+/// `(panicked)`, since the checks after the panic never ran.
+#[derive(Debug)]
+pub struct CheatScope {
+    name: String,
+    passed: usize,
+    total: usize,
+}
+
+impl CheatScope {
+    pub fn new(name: impl Into<String>) -> Self {
+        CheatScope {
+            name: name.into(),
+            passed: 0,
+            total: 0,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Count one check. Skips and warnings count as passed, as in
+    /// [`CheckResult::passed`].
+    pub fn record(&mut self, result: &CheckResult) {
+        self.total += 1;
+        if result.passed() {
+            self.passed += 1;
+        }
+    }
+
+    pub fn passed(&self) -> usize {
+        self.passed
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// The line printed on drop, without the panic annotation.
+    pub fn summary_line(&self) -> String {
+        format!(
+            "scope '{}': {}/{} checks passed",
+            self.name, self.passed, self.total
+        )
+    }
+}
+
+impl Drop for CheatScope {
+    fn drop(&mut self) {
+        let line = self.summary_line();
+        if thread::panicking() {
+            write_banner(&format!("{line} (panicked)"));
+        } else {
+            write_banner(&line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{lock_globals, SharedBuf};
+    use crate::{reset_cheat_writer, set_cheat_writer};
+
+    fn pass() -> CheckResult {
+        CheckResult::Pass("ok".to_string())
+    }
+
+    fn fail() -> CheckResult {
+        CheckResult::Fail {
+            expected: "vda1".to_string(),
+            actual: "nothing".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_scope_prints_summary_on_drop() {
+        let _globals = lock_globals();
+        let buf = SharedBuf::default();
+        set_cheat_writer(Box::new(buf.clone()));
+
+        {
+            let mut scope = crate::cheat_scope!("Partition Disk");
+            for result in [pass(), pass(), fail(), pass()] {
+                scope.record(&result);
+            }
+            assert_eq!((scope.passed(), scope.total()), (3, 4));
+        }
+        reset_cheat_writer();
+
+        let captured = buf.contents();
+        assert_eq!(captured, "scope 'Partition Disk': 3/4 checks passed\n");
+    }
+
+    #[test]
+    fn test_scope_annotates_panics() {
+        let _globals = lock_globals();
+        let buf = SharedBuf::default();
+        set_cheat_writer(Box::new(buf.clone()));
+
+        let unwound = std::panic::catch_unwind(|| {
+            let mut scope = CheatScope::new("Install Bootloader");
+            scope.record(&pass());
+            panic!("bootctl crashed");
+        });
+        reset_cheat_writer();
+
+        assert!(unwound.is_err());
+        let captured = buf.contents();
+        assert_eq!(
+            captured,
+            "scope 'Install Bootloader': 1/1 checks passed (panicked)\n"
+        );
+    }
+}
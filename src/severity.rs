@@ -0,0 +1,213 @@
+//! Typed severity levels for cheat-guarded checks.
+
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::str::FromStr;
+
+/// How badly users are affected if a guarded check is cheated.
+///
+/// Displays as the uppercase name used in banners (`CRITICAL`, `HIGH`, ...).
+/// Ordered from least to most severe, so `sev >= Severity::High` selects the
+/// hard failures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    /// Every severity, in ascending order.
+    pub fn all() -> [Severity; 4] {
+        [
+            Severity::Low,
+            Severity::Medium,
+            Severity::High,
+            Severity::Critical,
+        ]
+    }
+
+    /// The uppercase name shown in banners.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Critical => "CRITICAL",
+            Severity::High => "HIGH",
+            Severity::Medium => "MEDIUM",
+            Severity::Low => "LOW",
+        }
+    }
+
+    /// The ANSI SGR color code used when banners are colorized.
+    #[cfg(feature = "std")]
+    pub(crate) fn ansi_color(&self) -> &'static str {
+        match self {
+            Severity::Critical => "31",
+            Severity::High => "93",
+            Severity::Medium => "33",
+            Severity::Low => "36",
+        }
+    }
+}
+
+/// The process exit code for a run whose worst failure has `severity`:
+/// CRITICAL is 4, HIGH 3, MEDIUM 2, and LOW 1. A run with no failures should
+/// exit with 0.
+pub fn exit_code_for(severity: Severity) -> i32 {
+    match severity {
+        Severity::Critical => 4,
+        Severity::High => 3,
+        Severity::Medium => 2,
+        Severity::Low => 1,
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Severity {
+    type Err = ParseSeverityError;
+
+    /// Parse a severity name, ignoring ASCII case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Severity::all()
+            .into_iter()
+            .find(|sev| s.eq_ignore_ascii_case(sev.as_str()))
+            .ok_or_else(|| ParseSeverityError {
+                input: s.to_string(),
+            })
+    }
+}
+
+/// Error returned when a string is not a known severity name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSeverityError {
+    input: String,
+}
+
+impl ParseSeverityError {
+    /// The string that failed to parse.
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+}
+
+impl fmt::Display for ParseSeverityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid cheat severity {:?}: expected one of CRITICAL, HIGH, MEDIUM, LOW",
+            self.input
+        )
+    }
+}
+
+impl core::error::Error for ParseSeverityError {}
+
+/// Anything accepted where a severity is expected: a [`Severity`] or a
+/// severity name parsed with [`FromStr`].
+pub trait IntoSeverity {
+    fn into_severity(self) -> Result<Severity, ParseSeverityError>;
+}
+
+impl IntoSeverity for Severity {
+    fn into_severity(self) -> Result<Severity, ParseSeverityError> {
+        Ok(self)
+    }
+}
+
+impl IntoSeverity for &Severity {
+    fn into_severity(self) -> Result<Severity, ParseSeverityError> {
+        Ok(*self)
+    }
+}
+
+impl IntoSeverity for &str {
+    fn into_severity(self) -> Result<Severity, ParseSeverityError> {
+        self.parse()
+    }
+}
+
+impl IntoSeverity for String {
+    fn into_severity(self) -> Result<Severity, ParseSeverityError> {
+        self.parse()
+    }
+}
+
+impl IntoSeverity for &String {
+    fn into_severity(self) -> Result<Severity, ParseSeverityError> {
+        self.parse()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_is_uppercase() {
+        assert_eq!(Severity::Critical.to_string(), "CRITICAL");
+        assert_eq!(Severity::High.to_string(), "HIGH");
+        assert_eq!(Severity::Medium.to_string(), "MEDIUM");
+        assert_eq!(Severity::Low.to_string(), "LOW");
+    }
+
+    #[test]
+    fn test_from_str_round_trips() {
+        for sev in Severity::all() {
+            assert_eq!(sev.to_string().parse::<Severity>(), Ok(sev));
+        }
+        assert_eq!("critical".parse::<Severity>(), Ok(Severity::Critical));
+    }
+
+    #[test]
+    fn test_ordering_is_ascending() {
+        assert!(Severity::Low < Severity::Medium);
+        assert!(Severity::Medium < Severity::High);
+        assert!(Severity::High < Severity::Critical);
+        assert!(Severity::Critical >= Severity::High);
+    }
+
+    #[test]
+    fn test_all_is_sorted_ascending() {
+        let all = Severity::all();
+        assert_eq!(
+            all,
+            [
+                Severity::Low,
+                Severity::Medium,
+                Severity::High,
+                Severity::Critical
+            ]
+        );
+        let mut sorted = all;
+        sorted.sort();
+        assert_eq!(sorted, all);
+    }
+
+    #[test]
+    fn test_hash_buckets_by_severity() {
+        let mut counts = std::collections::HashMap::new();
+        for sev in [Severity::High, Severity::Low, Severity::High] {
+            *counts.entry(sev).or_insert(0) += 1;
+        }
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[&Severity::High], 2);
+    }
+
+    #[test]
+    fn test_exit_code_for() {
+        let codes: Vec<i32> = Severity::all().into_iter().map(exit_code_for).collect();
+        assert_eq!(codes, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_from_str_rejects_unknown() {
+        let err = "CRITCAL".parse::<Severity>().unwrap_err();
+        assert_eq!(err.input(), "CRITCAL");
+        assert!(err.to_string().contains("\"CRITCAL\""));
+    }
+}
@@ -0,0 +1,106 @@
+//! Run-wide tallies of cheat-guarded failures.
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::Severity;
+
+/// One counter per severity, indexed by [`index`].
+static FAILURES: [AtomicUsize; 4] = [
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+];
+
+fn index(severity: Severity) -> usize {
+    match severity {
+        Severity::Low => 0,
+        Severity::Medium => 1,
+        Severity::High => 2,
+        Severity::Critical => 3,
+    }
+}
+
+/// Count one cheat-guarded failure. Called from the macro failure paths.
+pub(crate) fn record_failure(severity: Severity) {
+    FAILURES[index(severity)].fetch_add(1, Ordering::Relaxed);
+}
+
+/// How many cheat-guarded failures fired so far, per severity.
+///
+/// Counts failures from [`cheat_bail!`](crate::cheat_bail) (including
+/// downgraded ones), [`cheat_ensure!`](crate::cheat_ensure), and
+/// [`cheat_check!`](crate::cheat_check). Every severity is present, with zero
+/// if it never fired.
+pub fn cheat_failure_counts() -> BTreeMap<Severity, usize> {
+    Severity::all()
+        .into_iter()
+        .map(|severity| (severity, FAILURES[index(severity)].load(Ordering::Relaxed)))
+        .collect()
+}
+
+/// Reset every failure counter to zero.
+pub fn reset_cheat_counts() {
+    for counter in &FAILURES {
+        counter.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Write a roll-up of [`cheat_failure_counts`] to `out`: the total, then one
+/// line per severity, most severe first.
+///
+/// There is no stable at-exit hook in std, so call it yourself at the end of
+/// `main`:
+///
+/// ```rust,ignore
+/// fn main() {
+///     let report = run_install_tests();
+///     leviso_cheat_guard::print_cheat_summary(&mut std::io::stderr()).ok();
+///     std::process::exit(report.suggested_exit_code());
+/// }
+/// ```
+pub fn print_cheat_summary(out: &mut impl Write) -> io::Result<()> {
+    write_summary(out, &cheat_failure_counts())
+}
+
+fn write_summary(out: &mut impl Write, counts: &BTreeMap<Severity, usize>) -> io::Result<()> {
+    let total: usize = counts.values().sum();
+    let noun = if total == 1 { "failure" } else { "failures" };
+    writeln!(out, "CHEAT-GUARDED SUMMARY: {} {}", total, noun)?;
+    for (severity, count) in counts.iter().rev() {
+        let label = format!("{}:", severity);
+        writeln!(out, "  {:<9} {}", label, count)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(counts: [usize; 4]) -> String {
+        let counts = Severity::all().into_iter().zip(counts).collect();
+        let mut out = Vec::new();
+        write_summary(&mut out, &counts).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_summary_lists_severities_most_severe_first() {
+        assert_eq!(
+            summary([1, 0, 3, 2]),
+            "CHEAT-GUARDED SUMMARY: 6 failures\n  \
+             CRITICAL: 2\n  \
+             HIGH:     3\n  \
+             MEDIUM:   0\n  \
+             LOW:      1\n"
+        );
+    }
+
+    #[test]
+    fn test_summary_singular_total() {
+        assert!(summary([0, 1, 0, 0]).starts_with("CHEAT-GUARDED SUMMARY: 1 failure\n"));
+    }
+}
@@ -0,0 +1,562 @@
+//! Step-level accumulation of check results.
+
+use std::collections::BTreeMap;
+#[cfg(feature = "timestamps")]
+use std::time::Instant;
+use std::time::{Duration, SystemTime};
+
+use crate::{CheatError, CheckResult, Severity};
+
+/// The checks recorded during one install-test step.
+///
+/// This is the `$result` that [`cheat_check!`](crate::cheat_check) records into.
+#[derive(Debug, Clone)]
+pub struct StepResult {
+    step: u32,
+    name: String,
+    checks: Vec<(String, CheckResult)>,
+    /// Extra details on each entry in `checks`, at the same index.
+    infos: Vec<CheckInfo>,
+    /// Sub-phases of this step, each owned, so the tree cannot form a cycle.
+    substeps: Vec<StepResult>,
+}
+
+/// Details recorded with a check beyond its name and result.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CheckInfo {
+    /// The subsystem the check belongs to, such as `"disk"`.
+    pub category: Option<String>,
+    /// The check's severity. [`cheat_check!`](crate::cheat_check) parses it
+    /// only on failure, so passing checks leave it `None`.
+    pub severity: Option<Severity>,
+    /// What user scenario the check protects.
+    pub protects: Option<String>,
+    /// Ways the check could be cheated; like `severity`, only filled in when
+    /// [`cheat_check!`](crate::cheat_check) fails.
+    pub cheats: Vec<String>,
+    /// What users experience if the check is cheated; filled in like `cheats`.
+    pub consequence: Option<String>,
+    /// How long the check's condition took to evaluate, when it was timed.
+    pub duration: Option<Duration>,
+    /// When the check was recorded. With the `timestamps` feature,
+    /// [`StepResult`] fills this in if it is `None`; without it the clock is
+    /// never read. The time is the wall clock at the first recorded check
+    /// plus a monotonic offset from it, so later checks never get earlier
+    /// stamps, whatever happens to the system clock during the run.
+    pub timestamp: Option<SystemTime>,
+    /// Captured standard output of the command under test, when a failing
+    /// [`cheat_check!`](crate::cheat_check) was given `stdout = ...`.
+    pub stdout: Option<String>,
+    /// Captured standard error of the command under test; set like `stdout`.
+    pub stderr: Option<String>,
+}
+
+/// The wall clock and monotonic clock read together at the first timestamp.
+#[cfg(feature = "timestamps")]
+static RUN_START: std::sync::OnceLock<(Instant, SystemTime)> = std::sync::OnceLock::new();
+
+/// The [`CheckInfo::timestamp`] of a check recorded now, or `None` without
+/// the `timestamps` feature.
+pub fn check_timestamp() -> Option<SystemTime> {
+    #[cfg(feature = "timestamps")]
+    {
+        let (started, wall) = RUN_START.get_or_init(|| (Instant::now(), SystemTime::now()));
+        Some(*wall + started.elapsed())
+    }
+    #[cfg(not(feature = "timestamps"))]
+    None
+}
+
+impl From<&CheatError> for CheckInfo {
+    /// The cheat documentation of `err`, without a category.
+    fn from(err: &CheatError) -> Self {
+        CheckInfo {
+            category: None,
+            severity: Some(err.severity),
+            protects: Some(err.protects.clone()),
+            cheats: err.cheats.clone(),
+            consequence: Some(err.consequence.clone()),
+            duration: None,
+            timestamp: None,
+            stdout: err.stdout.clone(),
+            stderr: err.stderr.clone(),
+        }
+    }
+}
+
+impl StepResult {
+    pub fn new(step: u32, name: impl Into<String>) -> Self {
+        StepResult {
+            step,
+            name: name.into(),
+            checks: Vec::new(),
+            infos: Vec::new(),
+            substeps: Vec::new(),
+        }
+    }
+
+    /// The step number.
+    pub fn step(&self) -> u32 {
+        self.step
+    }
+
+    /// The step name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Record a check result under `name`.
+    pub fn add_check(&mut self, name: impl Into<String>, result: CheckResult) {
+        self.add_check_with(name, result, CheckInfo::default());
+    }
+
+    /// Record a check result under `name`, counted under `category` in
+    /// [`category_summary`](Self::category_summary).
+    pub fn add_check_in(
+        &mut self,
+        category: impl Into<String>,
+        name: impl Into<String>,
+        result: CheckResult,
+    ) {
+        let info = CheckInfo {
+            category: Some(category.into()),
+            ..CheckInfo::default()
+        };
+        self.add_check_with(name, result, info);
+    }
+
+    /// Record a check result under `name` along with its `info`.
+    pub fn add_check_with(
+        &mut self,
+        name: impl Into<String>,
+        result: CheckResult,
+        info: CheckInfo,
+    ) {
+        #[cfg(feature = "timestamps")]
+        let info = CheckInfo {
+            timestamp: info.timestamp.or_else(check_timestamp),
+            ..info
+        };
+        self.checks.push((name.into(), result));
+        self.infos.push(info);
+    }
+
+    /// Append every check recorded in `other`, with its details, after this
+    /// step's own, and its substeps after this step's. `other`'s step number
+    /// and name are dropped.
+    pub fn merge(&mut self, other: StepResult) {
+        self.checks.extend(other.checks);
+        self.infos.extend(other.infos);
+        self.substeps.extend(other.substeps);
+    }
+
+    /// Nest `substep` under this step as one of its sub-phases, such as
+    /// probe, partition, and verify.
+    ///
+    /// [`summary`](Self::summary), [`all_passed`](Self::all_passed), and the
+    /// other roll-ups count substeps' checks along with this step's own.
+    pub fn add_substep(&mut self, substep: StepResult) {
+        self.substeps.push(substep);
+    }
+
+    /// Nested sub-phases, in the order they were added.
+    pub fn substeps(&self) -> &[StepResult] {
+        &self.substeps
+    }
+
+    /// Recorded `(name, result)` pairs, in insertion order. Substeps' checks
+    /// are not included.
+    pub fn checks(&self) -> &[(String, CheckResult)] {
+        &self.checks
+    }
+
+    /// Recorded `(name, result)` pairs, in insertion order, without tying
+    /// callers to how they are stored.
+    pub fn checks_iter(&self) -> impl Iterator<Item = (&str, &CheckResult)> {
+        self.checks
+            .iter()
+            .map(|(name, result)| (name.as_str(), result))
+    }
+
+    /// `(name, expected, actual)` of every failed check, in insertion order.
+    /// Like [`checks`](Self::checks), this leaves out substeps.
+    pub fn failed_checks(&self) -> impl Iterator<Item = (&str, &str, &str)> {
+        self.checks_iter().filter_map(|(name, result)| {
+            let (expected, actual) = result.as_fail()?;
+            Some((name, expected, actual))
+        })
+    }
+
+    /// Tally the recorded checks, including every substep's, by variant.
+    pub fn summary(&self) -> StepSummary {
+        let mut summary = self.own_summary();
+        for substep in &self.substeps {
+            summary += substep.summary();
+        }
+        summary
+    }
+
+    /// Counts of this step's own checks, leaving out its substeps'.
+    pub(crate) fn own_summary(&self) -> StepSummary {
+        let mut summary = StepSummary::default();
+        for (_, result) in &self.checks {
+            summary.record(result);
+        }
+        summary
+    }
+
+    /// The details recorded with the `index`th check.
+    pub fn info(&self, index: usize) -> Option<&CheckInfo> {
+        self.infos.get(index)
+    }
+
+    /// The category of the `index`th recorded check, if it was given one.
+    pub fn category(&self, index: usize) -> Option<&str> {
+        self.info(index)?.category.as_deref()
+    }
+
+    /// How long the `index`th recorded check's condition took, if it was timed.
+    pub fn duration(&self, index: usize) -> Option<Duration> {
+        self.info(index)?.duration
+    }
+
+    /// When the `index`th check was recorded, if a timestamp was taken; see
+    /// [`CheckInfo::timestamp`].
+    pub fn timestamp(&self, index: usize) -> Option<SystemTime> {
+        self.info(index)?.timestamp
+    }
+
+    /// `(name, duration)` of every timed check, in insertion order.
+    pub fn durations(&self) -> impl Iterator<Item = (&str, Duration)> {
+        self.checks
+            .iter()
+            .zip(&self.infos)
+            .filter_map(|((name, _), info)| Some((name.as_str(), info.duration?)))
+    }
+
+    /// The most severe failing check's severity, or `None` if nothing failed.
+    ///
+    /// A failure recorded without a severity counts as [`Severity::Low`].
+    pub fn worst_failure(&self) -> Option<Severity> {
+        self.checks
+            .iter()
+            .zip(&self.infos)
+            .filter(|((_, result), _)| !result.passed())
+            .map(|(_, info)| info.severity.unwrap_or(Severity::Low))
+            .chain(self.substeps.iter().filter_map(StepResult::worst_failure))
+            .max()
+    }
+
+    /// Tally the recorded checks by category. Checks without a category are
+    /// left out.
+    pub fn category_summary(&self) -> BTreeMap<String, StepSummary> {
+        let mut summaries = BTreeMap::<String, StepSummary>::new();
+        for ((_, result), info) in self.checks.iter().zip(&self.infos) {
+            if let Some(category) = &info.category {
+                summaries
+                    .entry(category.clone())
+                    .or_default()
+                    .record(result);
+            }
+        }
+        for substep in &self.substeps {
+            for (category, summary) in substep.category_summary() {
+                *summaries.entry(category).or_default() += summary;
+            }
+        }
+        summaries
+    }
+
+    /// True when no check, here or in a substep, failed. Skips and warnings
+    /// do not fail a step.
+    pub fn all_passed(&self) -> bool {
+        self.checks.iter().all(|(_, result)| result.passed())
+            && self.substeps.iter().all(StepResult::all_passed)
+    }
+
+    /// `Ok(())` if no check failed, otherwise an error listing every failed
+    /// check with its expected and actual values.
+    #[cfg(feature = "anyhow")]
+    pub fn into_result(self) -> anyhow::Result<()> {
+        if self.all_passed() {
+            return Ok(());
+        }
+
+        let summary = self.summary();
+        let mut msg = format!(
+            "Step {} ({}): {} of {} checks failed",
+            self.step, self.name, summary.failed, summary.total
+        );
+        self.push_failures("", &mut msg);
+        anyhow::bail!(msg)
+    }
+
+    /// Append each failed check to `msg`, naming substeps' checks by their
+    /// path under `prefix`.
+    #[cfg(feature = "anyhow")]
+    fn push_failures(&self, prefix: &str, msg: &mut String) {
+        for (name, expected, actual) in self.failed_checks() {
+            msg.push_str(&format!(
+                "\n  {}{}\n    expected: {}\n    actual:   {}",
+                prefix, name, expected, actual
+            ));
+        }
+        for substep in &self.substeps {
+            substep.push_failures(&format!("{}{} / ", prefix, substep.name), msg);
+        }
+    }
+}
+
+/// Counts of check results by variant.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StepSummary {
+    pub total: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub warned: usize,
+}
+
+impl std::ops::AddAssign for StepSummary {
+    fn add_assign(&mut self, other: StepSummary) {
+        self.total += other.total;
+        self.passed += other.passed;
+        self.failed += other.failed;
+        self.skipped += other.skipped;
+        self.warned += other.warned;
+    }
+}
+
+impl StepSummary {
+    fn record(&mut self, result: &CheckResult) {
+        self.total += 1;
+        match result {
+            CheckResult::Pass(_) => self.passed += 1,
+            CheckResult::Fail { .. } => self.failed += 1,
+            CheckResult::Skip(_) => self.skipped += 1,
+            CheckResult::Warn { .. } => self.warned += 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checks_keep_insertion_order() {
+        let mut step = StepResult::new(4, "Partition Disk");
+        step.add_check("first", CheckResult::Pass("ok".into()));
+        step.add_check(String::from("second"), CheckResult::Skip("BIOS".into()));
+
+        assert_eq!(step.step(), 4);
+        assert_eq!(step.name(), "Partition Disk");
+        let names: Vec<&str> = step.checks().iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, ["first", "second"]);
+    }
+
+    fn mixed_step() -> StepResult {
+        let mut step = StepResult::new(4, "Partition Disk");
+        step.add_check("vda1", CheckResult::Pass("vda1 exists".into()));
+        step.add_check("vda2", CheckResult::Pass("vda2 exists".into()));
+        step.add_check(
+            "swap",
+            CheckResult::Fail {
+                expected: "swap active".into(),
+                actual: "no swap".into(),
+            },
+        );
+        step.add_check("esp", CheckResult::Skip("BIOS boot".into()));
+        step.add_check(
+            "layout",
+            CheckResult::Warn {
+                message: "extra partition".into(),
+            },
+        );
+        step
+    }
+
+    #[test]
+    fn test_merge_appends_checks_in_order() {
+        let mut step = StepResult::new(4, "Partition Disk");
+        step.add_check_in("disk", "first", CheckResult::Pass("ok".into()));
+        let mut other = StepResult::new(9, "Other thread");
+        other.add_check("second", CheckResult::Skip("BIOS".into()));
+        other.add_check_in("boot", "third", CheckResult::Pass("ok".into()));
+
+        step.merge(other);
+
+        assert_eq!((step.step(), step.name()), (4, "Partition Disk"));
+        let names: Vec<&str> = step.checks().iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, ["first", "second", "third"]);
+        assert_eq!(step.category(0), Some("disk"));
+        assert_eq!(step.category(1), None);
+        assert_eq!(step.category(2), Some("boot"));
+    }
+
+    #[test]
+    fn test_failed_checks_of_mixed_step() {
+        let step = mixed_step();
+        let names: Vec<&str> = step.checks_iter().map(|(name, _)| name).collect();
+        assert_eq!(names, ["vda1", "vda2", "swap", "esp", "layout"]);
+        assert!(step.checks_iter().nth(3).unwrap().1.skipped());
+
+        let failed: Vec<_> = step.failed_checks().collect();
+        assert_eq!(failed, [("swap", "swap active", "no swap")]);
+    }
+
+    #[test]
+    fn test_summary_recurses_into_substeps() {
+        let mut step = StepResult::new(4, "Partition Disk");
+        step.add_check("table", CheckResult::Pass("gpt".into()));
+        let mut probe = StepResult::new(4, "probe");
+        probe.add_check_in("disk", "vda", CheckResult::Pass("vda found".into()));
+        probe.add_check("vdb", CheckResult::Skip("single disk".into()));
+        step.add_substep(probe);
+        step.add_substep(mixed_step());
+
+        assert_eq!(step.substeps().len(), 2);
+        assert_eq!(step.checks().len(), 1);
+        assert_eq!(
+            step.summary(),
+            StepSummary {
+                total: 8,
+                passed: 4,
+                failed: 1,
+                skipped: 2,
+                warned: 1,
+            }
+        );
+        assert!(!step.all_passed());
+        assert_eq!(step.worst_failure(), Some(Severity::Low));
+        assert_eq!(step.category_summary()["disk"].passed, 1);
+    }
+
+    #[cfg(feature = "timestamps")]
+    #[test]
+    fn test_timestamps_do_not_decrease() {
+        let mut step = StepResult::new(1, "Boot");
+        for name in ["first", "second", "third"] {
+            step.add_check(name, CheckResult::Pass("ok".into()));
+        }
+        let stamps: Vec<SystemTime> = (0..3).map(|i| step.timestamp(i).unwrap()).collect();
+        assert!(
+            stamps.windows(2).all(|pair| pair[0] <= pair[1]),
+            "{stamps:?}"
+        );
+    }
+
+    #[test]
+    fn test_summary_counts_each_variant() {
+        assert_eq!(
+            mixed_step().summary(),
+            StepSummary {
+                total: 5,
+                passed: 2,
+                failed: 1,
+                skipped: 1,
+                warned: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_all_passed_ignores_skips_and_warnings() {
+        let mut step = StepResult::new(1, "Boot");
+        step.add_check("esp", CheckResult::Skip("BIOS boot".into()));
+        step.add_check(
+            "layout",
+            CheckResult::Warn {
+                message: "extra partition".into(),
+            },
+        );
+        assert!(step.all_passed());
+        assert!(!mixed_step().all_passed());
+    }
+
+    #[test]
+    fn test_category_summary_tallies_by_category() {
+        let mut step = StepResult::new(4, "Partition Disk");
+        step.add_check_in("disk", "vda1", CheckResult::Pass("vda1 exists".into()));
+        step.add_check("untagged", CheckResult::Pass("ok".into()));
+        step.add_check_in(
+            "disk",
+            "swap",
+            CheckResult::Fail {
+                expected: "swap active".into(),
+                actual: "no swap".into(),
+            },
+        );
+        step.add_check_in("network", "dhcp", CheckResult::Skip("offline".into()));
+
+        assert_eq!(step.category(0), Some("disk"));
+        assert_eq!(step.category(1), None);
+        assert_eq!(step.category(9), None);
+        let summary = step.category_summary();
+        assert_eq!(summary.keys().collect::<Vec<_>>(), ["disk", "network"]);
+        assert_eq!(summary["disk"].total, 2);
+        assert_eq!(summary["disk"].failed, 1);
+        assert_eq!(summary["network"].skipped, 1);
+    }
+
+    #[test]
+    fn test_worst_failure() {
+        let mut step = StepResult::new(4, "Partition Disk");
+        assert_eq!(step.worst_failure(), None);
+        let info = |severity| CheckInfo {
+            severity: Some(severity),
+            ..CheckInfo::default()
+        };
+        step.add_check_with(
+            "vda1",
+            CheckResult::Pass("vda1".into()),
+            info(Severity::Critical),
+        );
+        step.add_check_with(
+            "esp",
+            CheckResult::Skip("BIOS".into()),
+            info(Severity::Critical),
+        );
+        assert_eq!(step.worst_failure(), None);
+
+        step.add_check("untagged", mixed_step().checks()[2].1.clone());
+        assert_eq!(step.worst_failure(), Some(Severity::Low));
+        step.add_check_with(
+            "swap",
+            mixed_step().checks()[2].1.clone(),
+            info(Severity::High),
+        );
+        assert_eq!(step.worst_failure(), Some(Severity::High));
+        assert_eq!(step.info(3).unwrap().severity, Some(Severity::High));
+    }
+
+    #[cfg(feature = "anyhow")]
+    #[test]
+    fn test_into_result_ok_when_nothing_failed() {
+        let mut step = StepResult::new(1, "Boot");
+        step.add_check("kernel", CheckResult::Pass("booted".into()));
+        step.add_check("esp", CheckResult::Skip("BIOS boot".into()));
+        assert!(step.into_result().is_ok());
+    }
+
+    #[cfg(feature = "anyhow")]
+    #[test]
+    fn test_into_result_lists_failed_checks() {
+        let mut step = mixed_step();
+        step.add_check(
+            "fstab",
+            CheckResult::Fail {
+                expected: "3 entries".into(),
+                actual: "0 entries".into(),
+            },
+        );
+
+        let msg = step.into_result().unwrap_err().to_string();
+        assert_eq!(
+            msg,
+            "Step 4 (Partition Disk): 2 of 6 checks failed\n  \
+             swap\n    expected: swap active\n    actual:   no swap\n  \
+             fstab\n    expected: 3 entries\n    actual:   0 entries"
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! Helpers shared by unit tests.
+
+use std::ffi::OsString;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+static GLOBALS: Mutex<()> = Mutex::new(());
+
+/// Serialize tests that read or write process-wide state such as env vars.
+pub fn lock_globals() -> MutexGuard<'static, ()> {
+    GLOBALS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Overrides environment variables for its lifetime, restoring them on drop.
+///
+/// Holds the global test lock so concurrent tests never observe the override.
+pub struct EnvGuard {
+    saved: Vec<(&'static str, Option<OsString>)>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl EnvGuard {
+    pub fn set(vars: &[(&'static str, &str)]) -> Self {
+        let guard = Self::save(vars.iter().map(|(key, _)| *key));
+        for (key, value) in vars {
+            std::env::set_var(key, value);
+        }
+        guard
+    }
+
+    pub fn unset(keys: &[&'static str]) -> Self {
+        let guard = Self::save(keys.iter().copied());
+        for key in keys {
+            std::env::remove_var(key);
+        }
+        guard
+    }
+
+    fn save(keys: impl Iterator<Item = &'static str>) -> Self {
+        let lock = lock_globals();
+        let saved = keys.map(|key| (key, std::env::var_os(key))).collect();
+        EnvGuard { saved, _lock: lock }
+    }
+}
+
+impl Drop for EnvGuard {
+    fn drop(&mut self) {
+        for (key, value) in &self.saved {
+            match value {
+                Some(value) => std::env::set_var(key, value),
+                None => std::env::remove_var(key),
+            }
+        }
+    }
+}
+
+/// A `Write` sink the test can read back after handing it over.
+#[derive(Clone, Default)]
+pub struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuf {
+    /// Everything written so far.
+    pub fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl Write for SharedBuf {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,345 @@
+//! String helpers for banner content.
+
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// The first `max_chars` characters of `text`, with `...` appended if cut.
+#[cfg(feature = "std")]
+pub fn preview(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// A failed check's `actual` value, cut to `CHEAT_GUARD_MAX_ACTUAL` bytes.
+#[cfg(feature = "std")]
+pub fn truncate_actual(actual: String) -> String {
+    truncate_bytes(actual, crate::config::max_actual())
+}
+
+/// `text` cut to at most `max_bytes` bytes on a char boundary, with
+/// `… (N bytes truncated)` appended when anything was removed.
+#[cfg(feature = "std")]
+pub(crate) fn truncate_bytes(mut text: String, max_bytes: usize) -> String {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    let removed = text.len() - end;
+    text.truncate(end);
+    text.push_str(&format!("\u{2026} ({} bytes truncated)", removed));
+    text
+}
+
+/// Collect a `cheats = ...` argument, inline array or not, into owned strings.
+#[cfg(feature = "std")]
+pub fn cheat_list<I>(cheats: I) -> Vec<String>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    dedupe_cheats(cheats.into_iter().map(|cheat| cheat.as_ref().to_string()))
+}
+
+/// `cheats` with repeats dropped, keeping the first spelling of each.
+///
+/// Two cheats are the same if they are equal after trimming whitespace.
+pub(crate) fn dedupe_cheats(cheats: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut seen = BTreeSet::new();
+    cheats
+        .into_iter()
+        .filter(|cheat| seen.insert(cheat.trim().to_string()))
+        .collect()
+}
+
+/// Shown in place of the numbered list when no cheats are documented.
+pub(crate) const NO_CHEATS_LINE: &str = "  (none documented)";
+
+/// Cheats as the numbered `  1. ...` lines shown in banners.
+pub fn format_cheats<S: AsRef<str>>(cheats: &[S]) -> String {
+    #[cfg(all(test, feature = "std"))]
+    FORMAT_CHEATS_CALLS.with(|calls| calls.set(calls.get() + 1));
+
+    if cheats.is_empty() {
+        return NO_CHEATS_LINE.to_string();
+    }
+    cheats
+        .iter()
+        .enumerate()
+        .map(|(i, c)| format!("  {}. {}", i + 1, c.as_ref()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `text` with every line indented by two spaces.
+#[cfg(feature = "std")]
+pub(crate) fn indent(text: &str) -> String {
+    text.lines()
+        .map(|line| format!("  {}", line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Metadata as `  key: value` lines, with the values aligned.
+pub(crate) fn format_metadata(metadata: &[(String, String)]) -> String {
+    let key_width = metadata.iter().map(|(key, _)| key.chars().count()).max();
+    metadata
+        .iter()
+        .map(|(key, value)| {
+            let label = format!("{}:", key);
+            format!(
+                "  {:<width$} {}",
+                label,
+                value,
+                width = key_width.unwrap_or(0) + 1
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(all(test, feature = "std"))]
+thread_local! {
+    /// How many times this thread has called [`format_cheats`].
+    pub(crate) static FORMAT_CHEATS_CALLS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// `text` word-wrapped so no line exceeds `width` characters.
+///
+/// The first line starts after an `indent`-character label such as
+/// `PROTECTS: `, and every later line is indented by `indent` spaces to line
+/// up under it. Lines that already fit are kept as-is, and words are never
+/// split, so a single word longer than the width stays on its own line.
+pub(crate) fn wrap(text: &str, width: usize, indent: usize) -> String {
+    let pad = " ".repeat(indent);
+    let mut out = String::with_capacity(text.len());
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            out.push('\n');
+            out.push_str(&pad);
+        }
+        if indent + line.chars().count() <= width {
+            out.push_str(line);
+            continue;
+        }
+        let mut used = indent;
+        for (j, word) in line.split_whitespace().enumerate() {
+            let len = word.chars().count();
+            if j > 0 {
+                if used + 1 + len > width {
+                    out.push('\n');
+                    out.push_str(&pad);
+                    used = indent;
+                } else {
+                    out.push(' ');
+                    used += 1;
+                }
+            }
+            out.push_str(word);
+            used += len;
+        }
+    }
+    out
+}
+
+/// A run of `=` as long as the widest banner, sliced by [`border`] so no
+/// banner allocates its border.
+pub(crate) const BORDER: &str = concat!(
+    "====================================================================================================",
+    "====================================================================================================",
+);
+
+/// A border line of `width` `=`, capped at the length of [`BORDER`].
+pub(crate) fn border(width: usize) -> &'static str {
+    &BORDER[..width.min(BORDER.len())]
+}
+
+/// `text` wrapped in the ANSI color `code`, or unchanged when `code` is `None`.
+pub(crate) fn paint<'a>(text: &'a str, code: Option<&'a str>) -> Painted<'a> {
+    Painted { text, code }
+}
+
+/// Displays as [`paint`] describes, without allocating.
+pub(crate) struct Painted<'a> {
+    text: &'a str,
+    code: Option<&'a str>,
+}
+
+impl fmt::Display for Painted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.code {
+            Some(code) => write!(f, "\x1b[{}m{}\x1b[0m", code, self.text),
+            None => f.write_str(self.text),
+        }
+    }
+}
+
+/// `url` as an OSC 8 terminal hyperlink to itself, or unchanged when `link`
+/// is false.
+pub(crate) fn hyperlink(url: &str, link: bool) -> String {
+    if link {
+        format!("\x1b]8;;{url}\x1b\\{url}\x1b]8;;\x1b\\")
+    } else {
+        url.to_string()
+    }
+}
+
+/// `text` as a quoted JSON string literal.
+pub(crate) fn json_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// `time` as an RFC 3339 UTC timestamp with milliseconds, such as
+/// `2024-03-09T14:05:00.250Z`. Times before the Unix epoch clamp to it.
+#[cfg(feature = "std")]
+pub(crate) fn rfc3339(time: std::time::SystemTime) -> String {
+    let since_epoch = time
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    // Civil-from-days, counting in 400-year eras from 0000-03-01.
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_border_is_sliced() {
+        assert_eq!(BORDER.len(), 200);
+        assert!(BORDER.bytes().all(|byte| byte == b'='));
+        assert_eq!(border(60), "=".repeat(60));
+        assert_eq!(border(500), BORDER);
+    }
+
+    #[test]
+    fn test_rfc3339() {
+        use std::time::{Duration, UNIX_EPOCH};
+        assert_eq!(rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+        let leap_day = UNIX_EPOCH + Duration::from_millis(951_782_400_250);
+        assert_eq!(rfc3339(leap_day), "2000-02-29T00:00:00.250Z");
+        let later = UNIX_EPOCH + Duration::from_secs(1_710_000_000);
+        assert_eq!(rfc3339(later), "2024-03-09T16:00:00.000Z");
+    }
+
+    #[test]
+    fn test_wrap_keeps_short_lines() {
+        assert_eq!(wrap("fits  as-is", 30, 10), "fits  as-is");
+        assert_eq!(wrap("one\ntwo", 20, 2), "one\n  two");
+    }
+
+    #[test]
+    fn test_wrap_breaks_on_whitespace_and_indents() {
+        let wrapped = wrap("Users lose every byte on the disk", 20, 10);
+        assert_eq!(
+            wrapped,
+            "Users lose\n          every byte\n          on the\n          disk"
+        );
+    }
+
+    #[test]
+    fn test_wrap_never_splits_long_words() {
+        assert_eq!(
+            wrap("a /dev/disk/by-uuid/0123 b", 10, 0),
+            "a\n/dev/disk/by-uuid/0123\nb"
+        );
+    }
+
+    #[test]
+    fn test_dedupe_cheats_trims_and_keeps_order() {
+        let cheats = [
+            "Skip check",
+            "skip check ",
+            " Skip check",
+            "Accept any output",
+        ];
+        assert_eq!(
+            cheat_list(cheats),
+            ["Skip check", "skip check ", "Accept any output"]
+        );
+    }
+
+    #[test]
+    fn test_truncate_bytes_short_unchanged() {
+        assert_eq!(truncate_bytes("vda1".to_string(), 4), "vda1");
+    }
+
+    #[test]
+    fn test_truncate_bytes_respects_char_boundary() {
+        // Each "ä" is two bytes, so a 5-byte limit must back off to 4.
+        let truncated = truncate_bytes("ääääää".to_string(), 5);
+        assert_eq!(truncated, "ää\u{2026} (8 bytes truncated)");
+    }
+
+    #[test]
+    fn test_format_cheats_empty() {
+        assert_eq!(format_cheats::<&str>(&[]), "  (none documented)");
+    }
+
+    #[test]
+    fn test_paint() {
+        assert_eq!(paint("HIGH", Some("93")).to_string(), "\x1b[93mHIGH\x1b[0m");
+        assert_eq!(paint("HIGH", None).to_string(), "HIGH");
+    }
+
+    #[test]
+    fn test_preview_short_text_unchanged() {
+        assert_eq!(preview("vda1 vda2", 200), "vda1 vda2");
+    }
+
+    #[test]
+    fn test_preview_cuts_on_char_boundary() {
+        assert_eq!(preview("ääää", 2), "ää...");
+    }
+
+    #[test]
+    fn test_format_cheats_numbers_lines() {
+        assert_eq!(format_cheats(&["a", "b"]), "  1. a\n  2. b");
+    }
+
+    #[test]
+    fn test_json_string_escapes() {
+        assert_eq!(json_string("a \"b\"\\\n\u{1}"), r#""a \"b\"\\\n\u0001""#);
+    }
+}
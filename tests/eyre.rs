@@ -0,0 +1,78 @@
+//! The bail macros under the `eyre` feature, returning `eyre::Report`.
+//!
+//! Built only for `--no-default-features --features eyre`; with `anyhow` on,
+//! the macros return `anyhow::Error` and this file compiles to nothing.
+
+#![cfg(all(feature = "eyre", not(feature = "anyhow")))]
+
+use leviso_cheat_guard::{cheat_bail, cheat_ensure, CheatError, Severity};
+
+fn verify_partition(output: &str) -> eyre::Result<()> {
+    if !output.contains("vda1") {
+        cheat_bail!(
+            protects = "Disk is partitioned",
+            severity = "CRITICAL",
+            cheats = ["Accept any output"],
+            consequence = "Installation fails",
+            "vda1 not found in: {}",
+            output
+        );
+    }
+    Ok(())
+}
+
+fn verify_both(output: &str) -> eyre::Result<()> {
+    cheat_ensure!(
+        output.contains("vda1") && output.contains("vda2"),
+        protects = "Both partitions were created",
+        severity = Severity::High,
+        cheats = ["Check vda1 OR vda2 instead of AND"],
+        consequence = "Missing partition causes mount failure",
+        "Expected vda1 AND vda2"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_bail_returns_eyre_report() {
+    assert!(verify_partition("vda1 vda2").is_ok());
+
+    let err = verify_partition("vdb").unwrap_err();
+    let cheat = err.downcast_ref::<CheatError>().unwrap();
+    assert_eq!(cheat.protects, "Disk is partitioned");
+    assert_eq!(cheat.severity, Severity::Critical);
+    assert_eq!(cheat.message, "vda1 not found in: vdb");
+
+    let banner = err.to_string();
+    assert!(banner.contains("=== CHEAT-GUARDED FAILURE ==="));
+    assert!(banner.contains("PROTECTS: Disk is partitioned"));
+    assert!(banner.contains("CHEAT VECTORS:\n  1. Accept any output\n"));
+    assert!(banner.contains("ERROR:\nvda1 not found in: vdb\n"));
+}
+
+#[test]
+fn test_ensure_returns_eyre_report() {
+    assert!(verify_both("vda1 vda2").is_ok());
+
+    let err = verify_both("vda1").unwrap_err();
+    let cheat = err.downcast_ref::<CheatError>().unwrap();
+    assert_eq!(cheat.severity, Severity::High);
+    assert!(err.to_string().contains("ERROR:\nExpected vda1 AND vda2\n"));
+}
+
+#[test]
+fn test_unknown_severity_bails_with_eyre() {
+    fn verify() -> eyre::Result<()> {
+        cheat_bail!(
+            protects = "p",
+            severity = "SEVERE",
+            cheats = ["c"],
+            consequence = "q",
+            "m"
+        );
+        Ok(())
+    }
+    let err = verify().unwrap_err();
+    assert!(err.downcast_ref::<CheatError>().is_none());
+    assert!(err.to_string().contains("\"SEVERE\""));
+}
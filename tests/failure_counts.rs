@@ -0,0 +1,83 @@
+//! Failure counters are process-wide, so this lives in its own test binary.
+
+#![cfg(feature = "anyhow")]
+
+use anyhow::Result;
+use leviso_cheat_guard::{
+    cheat_bail, cheat_check, cheat_ensure, cheat_failure_counts, print_cheat_summary,
+    reset_cheat_counts, Severity, StepResult,
+};
+
+fn bail_with(severity: Severity) -> Result<()> {
+    cheat_bail!(
+        protects = "Counting works",
+        severity = severity,
+        cheats = ["Never increment"],
+        consequence = "Dashboards lie",
+        "failure at {}",
+        severity
+    );
+    Ok(())
+}
+
+#[test]
+fn test_failures_are_tallied_by_severity() {
+    reset_cheat_counts();
+
+    let _ = bail_with(Severity::Critical);
+    let _ = bail_with(Severity::Critical);
+    let _ = (|| -> Result<()> {
+        cheat_ensure!(
+            false,
+            protects = "Counting works",
+            severity = "HIGH",
+            cheats = ["Never increment"],
+            consequence = "Dashboards lie",
+            "ensure failed"
+        );
+        Ok(())
+    })();
+    let mut step = StepResult::new(1, "Count");
+    cheat_check!(
+        step,
+        name = "passing check",
+        condition = true,
+        protects = "Counting works",
+        severity = "LOW",
+        cheats = ["Never increment"],
+        consequence = "Dashboards lie",
+        expected = "pass",
+        actual = "pass"
+    );
+    cheat_check!(
+        step,
+        name = "failing check",
+        condition = false,
+        protects = "Counting works",
+        severity = "LOW",
+        cheats = ["Never increment"],
+        consequence = "Dashboards lie",
+        expected = "pass",
+        actual = "fail"
+    );
+
+    let counts = cheat_failure_counts();
+    assert_eq!(counts[&Severity::Critical], 2);
+    assert_eq!(counts[&Severity::High], 1);
+    assert_eq!(counts[&Severity::Medium], 0);
+    assert_eq!(counts[&Severity::Low], 1);
+
+    let mut summary = Vec::new();
+    print_cheat_summary(&mut summary).unwrap();
+    assert_eq!(
+        String::from_utf8(summary).unwrap(),
+        "CHEAT-GUARDED SUMMARY: 4 failures\n  \
+         CRITICAL: 2\n  \
+         HIGH:     1\n  \
+         MEDIUM:   0\n  \
+         LOW:      1\n"
+    );
+
+    reset_cheat_counts();
+    assert!(cheat_failure_counts().values().all(|&count| count == 0));
+}
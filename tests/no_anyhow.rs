@@ -0,0 +1,72 @@
+//! The bail macros without the `anyhow` feature, returning `CheatError`.
+//!
+//! Built only for `--no-default-features --features std`; with `anyhow` or
+//! `eyre` on, the macros return their error type and this file compiles to
+//! nothing.
+
+#![cfg(all(feature = "std", not(any(feature = "anyhow", feature = "eyre"))))]
+// CheatError is returned by value, as downstream code without anyhow will.
+#![allow(clippy::result_large_err)]
+
+use leviso_cheat_guard::{cheat_bail, cheat_ensure, CheatError, Severity};
+
+fn verify_partition(output: &str) -> Result<(), CheatError> {
+    if !output.contains("vda1") {
+        cheat_bail!(
+            protects = "Disk is partitioned",
+            severity = "CRITICAL",
+            cheats = ["Accept any output"],
+            consequence = "Installation fails",
+            "vda1 not found in: {}",
+            output
+        );
+    }
+    Ok(())
+}
+
+fn verify_both(output: &str) -> Result<(), CheatError> {
+    cheat_ensure!(
+        output.contains("vda1") && output.contains("vda2"),
+        protects = "Both partitions were created",
+        severity = Severity::High,
+        cheats = ["Check vda1 OR vda2 instead of AND"],
+        consequence = "Missing partition causes mount failure",
+        "Expected vda1 AND vda2"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_bail_returns_cheat_error() {
+    assert!(verify_partition("vda1 vda2").is_ok());
+
+    let err = verify_partition("vdb").unwrap_err();
+    assert_eq!(err.protects, "Disk is partitioned");
+    assert_eq!(err.severity, Severity::Critical);
+    assert_eq!(err.message, "vda1 not found in: vdb");
+    assert!(err.location.is_some());
+}
+
+#[test]
+fn test_ensure_returns_cheat_error() {
+    assert!(verify_both("vda1 vda2").is_ok());
+
+    let err = verify_both("vda1").unwrap_err();
+    assert_eq!(err.severity, Severity::High);
+    assert!(err.to_string().contains("=== CHEAT-GUARDED FAILURE ==="));
+}
+
+#[test]
+fn test_cheat_result_yields_cheat_error() {
+    use leviso_cheat_guard::cheat_result;
+
+    let result: Result<(), CheatError> = cheat_result!(
+        false,
+        protects = "Disk is partitioned",
+        severity = "HIGH",
+        cheats = ["Accept any output"],
+        consequence = "Installation fails",
+        "vda1 not found"
+    );
+    assert_eq!(result.unwrap_err().message, "vda1 not found");
+}
@@ -0,0 +1,47 @@
+//! Banner rendering through the `core` + `alloc` surface only, so this
+//! passes with `--no-default-features` as well as the default build.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::string::String;
+
+use leviso_cheat_guard::{CheatError, CheatMeta, Location, Severity};
+
+#[test]
+fn test_write_banner_into_string() {
+    let err = CheatError::new(
+        "Disk is partitioned",
+        Severity::Critical,
+        ["Accept any output", "Skip verification"],
+        "Installation fails",
+        "vda1 not found",
+    )
+    .with_location(Location::new("src/disk.rs", 12, 5));
+
+    let mut banner = String::new();
+    err.write_banner(&mut banner).unwrap();
+
+    assert!(banner.contains("=== CHEAT-GUARDED FAILURE ===\n"));
+    assert!(banner.contains("PROTECTS: Disk is partitioned\nSEVERITY: CRITICAL\n"));
+    assert!(banner.contains("LOCATION: src/disk.rs:12:5\n"));
+    assert!(banner.contains("  1. Accept any output\n  2. Skip verification\n"));
+    assert!(banner.contains("ERROR:\nvda1 not found\n"));
+}
+
+#[test]
+fn test_meta_builds_errors() {
+    let meta = CheatMeta::builder()
+        .protects("Disk is partitioned")
+        .severity("high")
+        .cheat("Skip verification")
+        .consequence("Installation fails")
+        .build();
+
+    let mut banner = String::new();
+    meta.to_error("vda2 not found")
+        .write_banner(&mut banner)
+        .unwrap();
+    assert!(banner.contains("SEVERITY: HIGH\n"));
+}
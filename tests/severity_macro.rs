@@ -0,0 +1,8 @@
+//! `severity!` resolves known names and rejects unknown ones at compile time.
+
+#[test]
+fn test_severity_literals() {
+    let cases = trybuild::TestCases::new();
+    cases.pass("tests/ui/severity_known.rs");
+    cases.compile_fail("tests/ui/severity_unknown.rs");
+}
@@ -0,0 +1,51 @@
+//! `CheatError` as one variant of a downstream `thiserror` enum.
+
+// Embedded by value, as downstream error enums will.
+#![allow(clippy::large_enum_variant, clippy::result_large_err)]
+
+use leviso_cheat_guard::{CheatError, Severity};
+
+#[derive(Debug, thiserror::Error)]
+enum InstallError {
+    #[error(transparent)]
+    Cheat(#[from] CheatError),
+    #[error("disk {0} not found")]
+    MissingDisk(String),
+}
+
+fn partition(disk: &str, output: &str) -> Result<(), InstallError> {
+    if disk != "vda" {
+        return Err(InstallError::MissingDisk(disk.to_string()));
+    }
+    if !output.contains("vda1") {
+        let err = CheatError::new(
+            "Disk is partitioned",
+            Severity::Critical,
+            ["Accept any output"],
+            "Installation fails",
+            "vda1 not found",
+        );
+        Err(err)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_cheat_error_embeds_in_user_enum() {
+    assert!(partition("vda", "vda1").is_ok());
+
+    match partition("vda", "nothing") {
+        Err(InstallError::Cheat(err)) => {
+            assert_eq!(err.severity, Severity::Critical);
+            assert_eq!(err.message, "vda1 not found");
+            let wrapped = InstallError::Cheat(err.clone());
+            assert_eq!(wrapped.to_string(), err.to_string());
+        }
+        other => panic!("expected InstallError::Cheat, got {:?}", other),
+    }
+
+    match partition("sdb", "") {
+        Err(InstallError::MissingDisk(disk)) => assert_eq!(disk, "sdb"),
+        other => panic!("expected InstallError::MissingDisk, got {:?}", other),
+    }
+}
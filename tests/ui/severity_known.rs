@@ -0,0 +1,12 @@
+use leviso_cheat_guard::{severity, Severity};
+
+const CRITICAL: Severity = severity!("CRITICAL");
+
+fn main() {
+    assert_eq!(CRITICAL, Severity::Critical);
+    assert_eq!(severity!("HIGH"), Severity::High);
+    assert_eq!(severity!("MEDIUM"), Severity::Medium);
+    assert_eq!(severity!("LOW"), Severity::Low);
+    assert_eq!(severity!("critical"), Severity::Critical);
+    assert_eq!(severity!("High"), Severity::High);
+}
@@ -0,0 +1,5 @@
+use leviso_cheat_guard::severity;
+
+fn main() {
+    let _ = severity!("CRTICAL");
+}